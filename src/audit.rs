@@ -0,0 +1,205 @@
+//! Append-only audit log of allow/deny decisions.
+//!
+//! Every `SecurityEvent` becomes one JSON line carrying a sequence number that keeps counting
+//! across restarts, so a removed or reordered line shows up as a gap. The log is independent of
+//! the device output format and is fsynced periodically rather than on every write.
+
+use crate::config::MatchedRule;
+use crate::usb_monitor::SecurityEvent;
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Longest time a record may sit in the OS cache before the log is fsynced
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of unsynced records that forces an fsync regardless of time
+const SYNC_EVERY_RECORDS: usize = 32;
+
+pub struct AuditLog {
+    path: PathBuf,
+    file: File,
+    next_sequence: u64,
+    unsynced: usize,
+    last_sync: Instant,
+}
+
+impl AuditLog {
+    /// Open (or create) the audit log, continuing the sequence of any records already in it
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create audit log directory: {}", parent.display()))?;
+        }
+
+        let next_sequence = Self::last_sequence(path)?.map_or(1, |last| last + 1);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open audit log: {}", path.display()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            next_sequence,
+            unsynced: 0,
+            last_sync: Instant::now(),
+        })
+    }
+
+    /// Sequence number of the last record in an existing log
+    fn last_sequence(path: &Path) -> Result<Option<u64>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read audit log: {}", path.display())),
+        };
+
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("Failed to read audit log: {}", path.display()))?;
+            if let Some(sequence) = serde_json::from_str::<serde_json::Value>(&line)
+                .ok()
+                .and_then(|record| record.get("seq").and_then(|seq| seq.as_u64()))
+            {
+                last = Some(sequence);
+            }
+        }
+
+        Ok(last)
+    }
+
+    /// Append one decision, returning its sequence number
+    pub fn record(&mut self, event: &SecurityEvent, matched_rule: Option<MatchedRule<'_>>) -> Result<u64> {
+        let sequence = self.next_sequence;
+
+        let record = serde_json::json!({
+            "seq": sequence,
+            "timestamp": event.timestamp,
+            "event_type": event.event_type,
+            "action": event.action_taken,
+            "rule_id": event.rule_id,
+            "reason": event.reason,
+            "device": event.device_info,
+            "rule": matched_rule.map(|matched| serde_json::json!({
+                "list": matched.list,
+                "rule": matched.rule,
+            })),
+        });
+
+        let line = serde_json::to_string(&record).context("Failed to serialize audit record")?;
+        writeln!(self.file, "{}", line)
+            .with_context(|| format!("Failed to write audit log: {}", self.path.display()))?;
+
+        self.next_sequence += 1;
+        self.unsynced += 1;
+
+        if self.unsynced >= SYNC_EVERY_RECORDS || self.last_sync.elapsed() >= SYNC_INTERVAL {
+            self.sync()?;
+        }
+
+        Ok(sequence)
+    }
+
+    /// Flush written records to disk
+    pub fn sync(&mut self) -> Result<()> {
+        if self.unsynced == 0 {
+            return Ok(());
+        }
+
+        self.file.sync_data()
+            .with_context(|| format!("Failed to sync audit log: {}", self.path.display()))?;
+        self.unsynced = 0;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for AuditLog {
+    fn drop(&mut self) {
+        let _ = self.sync();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeviceRule;
+    use crate::usb_monitor::{ConnectionStatus, SecurityAction, SecurityEventType, UsbDeviceInfo};
+
+    fn event(event_type: SecurityEventType, action: SecurityAction) -> SecurityEvent {
+        SecurityEvent {
+            timestamp: chrono::Utc::now(),
+            event_type,
+            device_info: UsbDeviceInfo {
+                bus_number: 1,
+                device_address: 4,
+                vendor_id: 0x1234,
+                product_id: 0x5678,
+                device_version: 0x0100,
+                manufacturer: Some("Acme".to_string()),
+                product: Some("Widget".to_string()),
+                serial_number: None,
+                device_class: 8,
+                device_subclass: 0,
+                device_protocol: 0,
+                max_packet_size: 64,
+                num_configurations: 1,
+                timestamp: chrono::Utc::now(),
+                connection_status: ConnectionStatus::Connected,
+                fingerprint: String::new(),
+                raw_descriptor: None,
+            },
+            reason: "test".to_string(),
+            action_taken: action,
+            rule_id: None,
+        }
+    }
+
+    #[test]
+    fn test_sequence_numbers_are_contiguous_across_sessions() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-audit-test-{}", std::process::id()))
+            .join("audit.log");
+        let _ = fs::remove_file(&path);
+
+        let rule = DeviceRule {
+            vendor_id: Some(0x1234),
+            reason: "Untrusted vendor".to_string(),
+            ..DeviceRule::new()
+        };
+
+        {
+            let mut log = AuditLog::open(&path).unwrap();
+            for _ in 0..3 {
+                log.record(&event(SecurityEventType::DeviceAllowed, SecurityAction::Allowed), None).unwrap();
+            }
+        }
+
+        {
+            let mut log = AuditLog::open(&path).unwrap();
+            let matched = MatchedRule { list: "blacklist", rule: &rule };
+            for _ in 0..2 {
+                log.record(&event(SecurityEventType::DeviceBlocked, SecurityAction::Blocked), Some(matched)).unwrap();
+            }
+        }
+
+        let records: Vec<serde_json::Value> = fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let sequences: Vec<u64> = records.iter().map(|record| record["seq"].as_u64().unwrap()).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+        assert!(records[0]["rule"].is_null());
+        assert_eq!(records[4]["rule"]["list"], "blacklist");
+        assert_eq!(records[4]["rule"]["rule"]["reason"], "Untrusted vendor");
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,116 @@
+//! Enumeration benchmark behind the hidden `benchmark` subcommand.
+//!
+//! Runs `get_connected_devices` in a tight loop, first with the descriptor cache cleared before
+//! every scan (each device is opened for its strings) and then with the cache warm, so the cost
+//! of `device.open()` can be compared against plain enumeration on the same machine.
+
+use crate::usb_monitor::UsbMonitor;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Timings for one phase of the benchmark
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseReport {
+    pub iterations: usize,
+    pub scans_per_second: f64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+    /// Time spent opening devices and reading string descriptors
+    pub descriptor_read_ms: f64,
+    /// Share of the phase's scan time spent in descriptor reads, from 0 to 1
+    pub descriptor_read_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub duration_seconds: f64,
+    pub devices: usize,
+    pub descriptor_strings: bool,
+    /// Every scan opens every device
+    pub cold: PhaseReport,
+    /// Strings come from the descriptor cache, as during normal monitoring
+    pub warm: PhaseReport,
+}
+
+/// Benchmark enumeration for `duration`, split evenly between cold and warm scans
+pub fn run(monitor: &UsbMonitor, duration: Duration, descriptor_strings: bool) -> Result<BenchmarkReport> {
+    let devices = monitor.get_connected_devices()
+        .context("Failed to enumerate USB devices")?
+        .len();
+
+    let cold = run_phase(monitor, duration / 2, true)?;
+    monitor.get_connected_devices().context("Failed to enumerate USB devices")?;
+    let warm = run_phase(monitor, duration / 2, false)?;
+
+    Ok(BenchmarkReport {
+        duration_seconds: duration.as_secs_f64(),
+        devices,
+        descriptor_strings,
+        cold,
+        warm,
+    })
+}
+
+fn run_phase(monitor: &UsbMonitor, duration: Duration, clear_cache: bool) -> Result<PhaseReport> {
+    let mut latencies = Vec::new();
+    let read_time_before = monitor.descriptor_read_time();
+    let phase_start = Instant::now();
+
+    // Always take at least one sample so a zero duration still reports something
+    while latencies.is_empty() || phase_start.elapsed() < duration {
+        if clear_cache {
+            monitor.clear_descriptor_cache();
+        }
+
+        let scan_start = Instant::now();
+        monitor.get_connected_devices().context("Failed to enumerate USB devices")?;
+        latencies.push(scan_start.elapsed());
+    }
+
+    let scan_time: Duration = latencies.iter().sum();
+    let read_time = monitor.descriptor_read_time().saturating_sub(read_time_before);
+    latencies.sort();
+
+    Ok(PhaseReport {
+        iterations: latencies.len(),
+        scans_per_second: latencies.len() as f64 / phase_start.elapsed().as_secs_f64(),
+        p50_ms: millis(percentile(&latencies, 50)),
+        p99_ms: millis(percentile(&latencies, 99)),
+        descriptor_read_ms: millis(read_time),
+        descriptor_read_share: if scan_time.is_zero() {
+            0.0
+        } else {
+            read_time.as_secs_f64() / scan_time.as_secs_f64()
+        },
+    })
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100);
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_uses_nearest_rank() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&samples[..1], 99), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+}
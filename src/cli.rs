@@ -6,17 +6,269 @@ pub struct CliConfig {
     pub monitor_mode: bool,
     pub output_format: OutputFormat,
     pub config_file: Option<PathBuf>,
+    /// Directory holding `config.json`, overriding the default location
+    pub config_dir: Option<PathBuf>,
+    /// `--print-config-path`: report where the configuration lives and exit
+    pub print_config_path: bool,
+    /// `--strict`: treat configuration warnings as errors
+    pub strict: bool,
+    /// Skip opening devices for string descriptors (overrides `monitoring.descriptor_strings`)
+    pub no_descriptor_strings: bool,
+    /// `--raw-descriptors`: `device` or `all` (overrides `monitoring.raw_descriptors`)
+    pub raw_descriptors: Option<String>,
+    /// libusb log level from `--usb-debug` (overrides `monitoring.usb_debug`)
+    pub usb_debug: Option<u8>,
+    /// `--max-devices` (overrides `monitoring.max_devices`)
+    pub max_devices: Option<usize>,
     pub log_level: String,
     pub device_filter: Option<String>,
+    /// `--class`: only these device classes are listed or monitored
+    pub device_classes: Option<Vec<u8>>,
     pub continuous: bool,
+    /// `monitor --once`: a single scan even when `monitoring.auto_start` is set
+    pub once: bool,
     pub output_file: Option<PathBuf>,
+    pub sinks: Vec<SinkSpec>,
+    pub quiet: bool,
+    /// Grace period during which changes for devices present at startup are ignored
+    pub watch_new_only: Option<std::time::Duration>,
+    /// Interval for idle heartbeat records in continuous mode
+    pub heartbeat: Option<std::time::Duration>,
+    /// `monitor --snapshot-interval`: diff full enumerations on this cadence instead of polling
+    pub snapshot_interval: Option<std::time::Duration>,
+    /// Vendor and product ID requested by `get`, `check`, `tag` or `stats --device`
+    pub device_id: Option<(u16, u16)>,
+    /// Serial number that `check` also requires, or that picks the device `tag` labels
+    pub device_serial: Option<String>,
+    /// Label given to `tag`; empty removes the device's tag
+    pub device_tag: Option<String>,
+    /// Ordering of the `stats` table
+    pub stats_sort: StatsSort,
+    /// How long `stats` monitors before reporting
+    pub stats_duration: std::time::Duration,
+    /// NDJSON recording read by the `replay` subcommand
+    pub replay_file: Option<PathBuf>,
+    /// Playback speed multiplier for `replay`; 0 replays without delays
+    pub replay_speed: f64,
+    /// Columns shown by table output, in order
+    pub columns: Vec<TableColumn>,
+    /// Ordering of the `list` output
+    pub list_sort: ListSort,
+    /// Reverse the `list` ordering
+    pub list_reverse: bool,
+    /// Exit with `EXIT_BLOCKED_DEVICES` when `list` finds a device the policy would block
+    pub fail_on_blocked: bool,
+    /// How long the hidden `benchmark` subcommand runs
+    pub benchmark_duration: std::time::Duration,
+    /// Output file rotation from `--rotate`, overriding `output.rotate`
+    pub rotate: Option<FileRotation>,
+    /// Keep a `-latest` symlink to the current rotated file
+    pub latest_link: bool,
+    /// `--append` (the default) or `--truncate` for output files
+    pub file_mode: FileMode,
+    /// MQTT broker URL from `--mqtt-broker`, overriding `mqtt.broker`
+    pub mqtt_broker: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OutputFormat {
     Json,
+    Ndjson,
     Table,
     Csv,
+    Xml,
+    Toml,
+}
+
+impl OutputFormat {
+    /// Parse a format name as accepted on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(OutputFormat::Json),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "table" => Some(OutputFormat::Table),
+            "csv" => Some(OutputFormat::Csv),
+            "xml" => Some(OutputFormat::Xml),
+            "toml" => Some(OutputFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// The name accepted by `from_name`
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Table => "table",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Xml => "xml",
+            OutputFormat::Toml => "toml",
+        }
+    }
+}
+
+/// Sort order for the `stats` subcommand; all orders are descending
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsSort {
+    Connections,
+    Duration,
+    LastSeen,
+}
+
+impl StatsSort {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "connections" => Some(StatsSort::Connections),
+            "duration" => Some(StatsSort::Duration),
+            "last-seen" => Some(StatsSort::LastSeen),
+            _ => None,
+        }
+    }
+}
+
+/// Sort order for the `list` subcommand; ties fall back to bus and address
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListSort {
+    Bus,
+    Vendor,
+    Product,
+    Class,
+}
+
+impl ListSort {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bus" => Some(ListSort::Bus),
+            "vendor" => Some(ListSort::Vendor),
+            "product" => Some(ListSort::Product),
+            "class" => Some(ListSort::Class),
+            _ => None,
+        }
+    }
+}
+
+/// A column of the device table, selectable with `--columns`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableColumn {
+    Bus,
+    VidPid,
+    Address,
+    Manufacturer,
+    Product,
+    Class,
+    Timestamp,
+}
+
+impl TableColumn {
+    /// The default set, in display order
+    pub const ALL: [TableColumn; 7] = [
+        TableColumn::Bus,
+        TableColumn::VidPid,
+        TableColumn::Address,
+        TableColumn::Manufacturer,
+        TableColumn::Product,
+        TableColumn::Class,
+        TableColumn::Timestamp,
+    ];
+
+    /// Name accepted by `--columns`
+    pub fn name(&self) -> &'static str {
+        match self {
+            TableColumn::Bus => "bus",
+            TableColumn::VidPid => "vidpid",
+            TableColumn::Address => "address",
+            TableColumn::Manufacturer => "manufacturer",
+            TableColumn::Product => "product",
+            TableColumn::Class => "class",
+            TableColumn::Timestamp => "timestamp",
+        }
+    }
+
+    /// Parse a comma-separated column list such as `bus,vidpid,product`
+    pub fn parse_list(list: &str) -> anyhow::Result<Vec<Self>> {
+        let mut columns = Vec::new();
+
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let column = Self::ALL.into_iter()
+                .find(|column| column.name() == name.to_lowercase())
+                .ok_or_else(|| {
+                    let valid: Vec<&str> = Self::ALL.iter().map(|column| column.name()).collect();
+                    anyhow::anyhow!("Unknown column '{}'. Valid columns: {}", name, valid.join(", "))
+                })?;
+            columns.push(column);
+        }
+
+        if columns.is_empty() {
+            anyhow::bail!("--columns needs at least one column");
+        }
+        Ok(columns)
+    }
+}
+
+/// How output files are split over time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileRotation {
+    /// A single file that keeps growing
+    None,
+    /// A new `<name>-YYYY-MM-DD` file every UTC day
+    Daily,
+}
+
+impl FileRotation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(FileRotation::None),
+            "daily" => Some(FileRotation::Daily),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to an output file that already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileMode {
+    /// Keep the existing content and write after it
+    Append,
+    /// Empty the file when it is opened
+    Truncate,
+}
+
+/// A single `--sink format=...,path=...` output destination
+#[derive(Debug, Clone)]
+pub struct SinkSpec {
+    pub format: OutputFormat,
+    /// File to append to; `None` writes to stdout
+    pub path: Option<PathBuf>,
+}
+
+impl SinkSpec {
+    /// Parse a comma-separated `key=value` sink specification
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let mut format = None;
+        let mut path = None;
+
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid sink option '{}', expected key=value", part))?;
+
+            match key {
+                "format" => {
+                    format = Some(OutputFormat::from_name(value)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid sink format '{}'. Must be one of: {}", value, crate::config::OUTPUT_FORMATS.join(", ")))?);
+                }
+                "path" => {
+                    path = if value == "-" { None } else { Some(PathBuf::from(value)) };
+                }
+                _ => anyhow::bail!("Unknown sink option '{}'", key),
+            }
+        }
+
+        Ok(Self {
+            format: format.ok_or_else(|| anyhow::anyhow!("Sink '{}' is missing a format", spec))?,
+            path,
+        })
+    }
 }
 
 impl Default for CliConfig {
@@ -25,17 +277,47 @@ impl Default for CliConfig {
             monitor_mode: false,
             output_format: OutputFormat::Table,
             config_file: None,
+            config_dir: None,
+            print_config_path: false,
+            strict: false,
+            no_descriptor_strings: false,
+            raw_descriptors: None,
+            usb_debug: None,
+            max_devices: None,
+            mqtt_broker: None,
             log_level: "info".to_string(),
             device_filter: None,
+            device_classes: None,
             continuous: false,
+            once: false,
             output_file: None,
+            sinks: Vec::new(),
+            quiet: false,
+            watch_new_only: None,
+            heartbeat: None,
+            snapshot_interval: None,
+            device_id: None,
+            device_serial: None,
+            device_tag: None,
+            stats_sort: StatsSort::Connections,
+            stats_duration: std::time::Duration::from_secs(10),
+            replay_file: None,
+            replay_speed: 1.0,
+            columns: TableColumn::ALL.to_vec(),
+            list_sort: ListSort::Bus,
+            list_reverse: false,
+            fail_on_blocked: false,
+            benchmark_duration: std::time::Duration::from_secs(5),
+            rotate: None,
+            latest_link: false,
+            file_mode: FileMode::Append,
         }
     }
 }
 
 /// Build the CLI application structure
 pub fn build_cli() -> Command {
-    Command::new("IronWatch")
+    let cli = Command::new("IronWatch")
         .version("1.0.0")
         .author("KnivInstitute")
         .about("A modular CLI tool for monitoring USB device inputs")
@@ -47,7 +329,14 @@ pub fn build_cli() -> Command {
                         .short('c')
                         .long("continuous")
                         .action(clap::ArgAction::SetTrue)
-                        .help("Run in continuous monitoring mode")
+                        .help("Run in continuous monitoring mode (the default when monitoring.auto_start is set)")
+                )
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("continuous")
+                        .help("Scan once and exit, even when monitoring.auto_start is set")
                 )
                 .arg(
                     Arg::new("filter")
@@ -56,6 +345,7 @@ pub fn build_cli() -> Command {
                         .value_name("DEVICE_PATTERN")
                         .help("Filter devices by name pattern")
                 )
+                .arg(class_arg())
                 .arg(
                     Arg::new("output")
                         .short('o')
@@ -63,6 +353,66 @@ pub fn build_cli() -> Command {
                         .value_name("FILE")
                         .help("Output results to file")
                 )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(crate::config::OUTPUT_FORMATS)
+                        .default_value("table")
+                        .help("Output format")
+                )
+                .arg(
+                    Arg::new("sink")
+                        .long("sink")
+                        .value_name("SPEC")
+                        .action(clap::ArgAction::Append)
+                        .help("Add an output sink, e.g. format=ndjson,path=/var/log/iw.jsonl (repeatable)")
+                )
+                .arg(
+                    Arg::new("rotate")
+                        .long("rotate")
+                        .value_name("PERIOD")
+                        .value_parser(["none", "daily"])
+                        .help("Rotate output files; daily writes <name>-YYYY-MM-DD and rolls over at UTC midnight")
+                )
+                .arg(
+                    Arg::new("latest-link")
+                        .long("latest-link")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("With --rotate daily, keep a <name>-latest symlink to the current file")
+                )
+                .args(file_mode_args())
+                .arg(
+                    Arg::new("watch-new-only")
+                        .long("watch-new-only")
+                        .value_name("SECONDS")
+                        .num_args(0..=1)
+                        .default_missing_value("10")
+                        .value_parser(clap::value_parser!(u64))
+                        .requires("continuous")
+                        .help("Ignore changes for devices present at startup for SECONDS (default 10)")
+                )
+                .arg(
+                    Arg::new("heartbeat")
+                        .long("heartbeat")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .requires("continuous")
+                        .help("Emit a heartbeat record to JSON/NDJSON outputs after SECONDS without changes")
+                )
+                .arg(
+                    Arg::new("snapshot-interval")
+                        .long("snapshot-interval")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .requires("continuous")
+                        .help("Enumerate every SECONDS and print only devices added or removed since the previous snapshot")
+                )
+                .arg(columns_arg())
+        )
+        .subcommand(
+            Command::new("unblock-all")
+                .about("Monitor with device blocking suspended until restart, leaving the configuration file unchanged")
         )
         .subcommand(
             Command::new("list")
@@ -72,10 +422,195 @@ pub fn build_cli() -> Command {
                         .short('f')
                         .long("format")
                         .value_name("FORMAT")
-                        .value_parser(["json", "table", "csv"])
+                        .value_parser(crate::config::OUTPUT_FORMATS)
                         .default_value("table")
                         .help("Output format")
                 )
+                .arg(
+                    Arg::new("sink")
+                        .long("sink")
+                        .value_name("SPEC")
+                        .action(clap::ArgAction::Append)
+                        .help("Add an output sink, e.g. format=ndjson,path=/var/log/iw.jsonl (repeatable)")
+                )
+                .args(file_mode_args())
+                .arg(columns_arg())
+                .arg(class_arg())
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .value_name("KEY")
+                        .value_parser(["bus", "vendor", "product", "class"])
+                        .default_value("bus")
+                        .help("Sort devices by bus/address, vendor:product ID, product name or class")
+                )
+                .arg(
+                    Arg::new("reverse")
+                        .long("reverse")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Reverse the sort order")
+                )
+                .arg(
+                    Arg::new("fail-on-blocked")
+                        .long("fail-on-blocked")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Exit with status 3 if any connected device would be blocked")
+                )
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Exit with status 0 if a USB device is connected, 1 if not")
+                .arg(
+                    Arg::new("id")
+                        .value_name("VID:PID")
+                        .required(true)
+                        .help("Hexadecimal vendor and product ID, e.g. 046d:c52b")
+                )
+                .arg(
+                    Arg::new("serial")
+                        .long("serial")
+                        .value_name("SERIAL")
+                        .help("Also require this exact serial number")
+                )
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Give a device a friendly label, saved in the configuration file")
+                .arg(
+                    Arg::new("id")
+                        .value_name("VID:PID")
+                        .required(true)
+                        .help("Hexadecimal vendor and product ID, e.g. 046d:c52b")
+                )
+                .arg(
+                    Arg::new("tag")
+                        .value_name("TAG")
+                        .required(true)
+                        .help("Label to show for the device, e.g. \"Lab scope\"; an empty string removes it")
+                )
+                .arg(
+                    Arg::new("serial")
+                        .long("serial")
+                        .value_name("SERIAL")
+                        .help("Tag only the device with this serial number (default: the connected device's serial, if any)")
+                )
+        )
+        .subcommand(
+            Command::new("self-test")
+                .about("Check libusb, USB permissions and enumeration; exit with status 1 if any check fails")
+        )
+        .subcommand(
+            Command::new("get")
+                .about("Show a connected USB device by vendor and product ID")
+                .arg(
+                    Arg::new("id")
+                        .value_name("VID:PID")
+                        .required(true)
+                        .help("Hexadecimal vendor and product ID, e.g. 046d:c52b")
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(crate::config::OUTPUT_FORMATS)
+                        .default_value("table")
+                        .help("Output format")
+                )
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Monitor for a short session and print per-device statistics")
+                .arg(
+                    Arg::new("device")
+                        .long("device")
+                        .value_name("VID:PID")
+                        .help("Only show statistics for this device, e.g. 046d:c52b")
+                )
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .value_name("ORDER")
+                        .value_parser(["connections", "duration", "last-seen"])
+                        .default_value("connections")
+                        .help("Sort order (descending)")
+                )
+                .arg(
+                    Arg::new("duration")
+                        .short('d')
+                        .long("duration")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("10")
+                        .help("How long to monitor before reporting; 0 reports the current devices only")
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(crate::config::OUTPUT_FORMATS)
+                        .default_value("table")
+                        .help("Output format")
+                )
+        )
+        .subcommand(
+            Command::new("benchmark")
+                .about("Measure how long device enumeration takes")
+                .hide(true)
+                .arg(
+                    Arg::new("duration")
+                        .short('d')
+                        .long("duration")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("5")
+                        .help("Total run time, split between cold and cached scans")
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(crate::config::OUTPUT_FORMATS)
+                        .default_value("table")
+                        .help("Output format")
+                )
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Replay a recorded NDJSON change stream through rules and output")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("Recording produced by `monitor --continuous --format ndjson`")
+                )
+                .arg(
+                    Arg::new("speed")
+                        .long("speed")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("1")
+                        .help("Playback speed multiplier; 0 replays without delays")
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(crate::config::OUTPUT_FORMATS)
+                        .default_value("table")
+                        .help("Output format")
+                )
+                .arg(
+                    Arg::new("sink")
+                        .long("sink")
+                        .value_name("SPEC")
+                        .action(clap::ArgAction::Append)
+                        .help("Add an output sink, e.g. format=ndjson,path=/var/log/iw.jsonl (repeatable)")
+                )
+                .args(file_mode_args())
         )
         .subcommand(
             Command::new("config")
@@ -83,6 +618,22 @@ pub fn build_cli() -> Command {
                 .subcommand(
                     Command::new("show")
                         .about("Show current configuration")
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(clap::ArgAction::SetTrue)
+                                .help("Print only the configuration as JSON, for piping into other tools")
+                        )
+                )
+                .subcommand(
+                    Command::new("get")
+                        .about("Print one configuration value")
+                        .arg(
+                            Arg::new("key")
+                                .value_name("KEY")
+                                .required(true)
+                                .help("Configuration key, e.g. output.default_format")
+                        )
                 )
                 .subcommand(
                     Command::new("set")
@@ -106,7 +657,66 @@ pub fn build_cli() -> Command {
                 .short('c')
                 .long("config")
                 .value_name("FILE")
-                .help("Path to configuration file")
+                .help("Path to configuration file, or - to read it from stdin")
+        )
+        .arg(
+            Arg::new("config-dir")
+                .long("config-dir")
+                .value_name("DIR")
+                .env("IRONWATCH_CONFIG_DIR")
+                .help("Directory containing config.json (ignored when --config is given)")
+        )
+        .arg(
+            Arg::new("print-config-path")
+                .long("print-config-path")
+                .action(clap::ArgAction::SetTrue)
+                .help("Print the configuration file path and whether it exists, then exit")
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(clap::ArgAction::SetTrue)
+                .help("Fail on configuration warnings, such as an empty whitelist, instead of logging them")
+        )
+        .arg(
+            Arg::new("mqtt-broker")
+                .long("mqtt-broker")
+                .value_name("URL")
+                .global(true)
+                .help("Publish device events to this MQTT broker, e.g. mqtt://broker.local:1883 (needs the mqtt feature)")
+        )
+        .arg(
+            Arg::new("no-descriptor-strings")
+                .long("no-descriptor-strings")
+                .action(clap::ArgAction::SetTrue)
+                .help("Don't open devices to read strings; use the USB ID database for names (no serial numbers)")
+        )
+        .arg(
+            Arg::new("raw-descriptors")
+                .long("raw-descriptors")
+                .value_name("WHICH")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("device")
+                .value_parser(["device", "all"])
+                .global(true)
+                .help("Include each device's descriptor bytes as hex in JSON output; =all adds the configuration descriptors")
+        )
+        .arg(
+            Arg::new("usb-debug")
+                .long("usb-debug")
+                .value_name("LEVEL")
+                .global(true)
+                .value_parser(clap::value_parser!(u8).range(0..=4))
+                .help("libusb log level: 0 none, 1 error, 2 warning, 3 info, 4 debug (written to stderr)")
+        )
+        .arg(
+            Arg::new("max-devices")
+                .long("max-devices")
+                .value_name("N")
+                .global(true)
+                .value_parser(clap::value_parser!(usize))
+                .help("Track at most N devices per scan and report a device storm beyond that (0 for no limit)")
         )
         .arg(
             Arg::new("verbose")
@@ -115,6 +725,22 @@ pub fn build_cli() -> Command {
                 .action(clap::ArgAction::Count)
                 .help("Increase logging verbosity")
         )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("verbose")
+                .help("Suppress the banner, status lines and non-error logging")
+        );
+
+    #[cfg(feature = "tui")]
+    let cli = cli.subcommand(
+        Command::new("tui")
+            .about("Show a live device table and event log in the terminal")
+    );
+
+    cli
 }
 
 /// Parse command line arguments into configuration
@@ -125,9 +751,23 @@ pub fn parse_args(matches: &ArgMatches) -> anyhow::Result<CliConfig> {
     if let Some(config_file) = matches.get_one::<String>("config") {
         config.config_file = Some(PathBuf::from(config_file));
     }
+
+    if let Some(config_dir) = matches.get_one::<String>("config-dir") {
+        config.config_dir = Some(PathBuf::from(config_dir));
+    }
+
+    config.print_config_path = matches.get_flag("print-config-path");
+    config.strict = matches.get_flag("strict");
+    config.no_descriptor_strings = matches.get_flag("no-descriptor-strings");
+    config.raw_descriptors = matches.get_one::<String>("raw-descriptors").cloned();
+    config.usb_debug = matches.get_one::<u8>("usb-debug").copied();
+    config.max_devices = matches.get_one::<usize>("max-devices").copied();
+    config.mqtt_broker = matches.get_one::<String>("mqtt-broker").cloned();
     
     // Set log level based on verbosity
+    config.quiet = matches.get_flag("quiet");
     config.log_level = match matches.get_count("verbose") {
+        _ if config.quiet => "error".to_string(),
         0 => "info".to_string(),
         1 => "debug".to_string(),
         _ => "trace".to_string(),
@@ -138,23 +778,145 @@ pub fn parse_args(matches: &ArgMatches) -> anyhow::Result<CliConfig> {
         Some(("monitor", sub_matches)) => {
             config.monitor_mode = true;
             config.continuous = sub_matches.get_flag("continuous");
+            config.once = sub_matches.get_flag("once");
             
             if let Some(filter) = sub_matches.get_one::<String>("filter") {
                 config.device_filter = Some(filter.clone());
             }
+
+            if let Some(classes) = sub_matches.get_one::<String>("class") {
+                config.device_classes = Some(crate::config::DeviceRule::parse_device_classes(classes)?);
+            }
             
             if let Some(output) = sub_matches.get_one::<String>("output") {
                 config.output_file = Some(PathBuf::from(output));
             }
+
+            if let Some(format) = sub_matches.get_one::<String>("format") {
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
+            }
+
+            config.sinks = parse_sinks(sub_matches)?;
+            config.file_mode = parse_file_mode(sub_matches);
+
+            config.rotate = sub_matches.get_one::<String>("rotate")
+                .and_then(|rotate| FileRotation::from_name(rotate));
+            config.latest_link = sub_matches.get_flag("latest-link");
+
+            config.watch_new_only = sub_matches.get_one::<u64>("watch-new-only")
+                .map(|secs| std::time::Duration::from_secs(*secs));
+
+            config.heartbeat = sub_matches.get_one::<u64>("heartbeat")
+                .map(|secs| std::time::Duration::from_secs(*secs));
+
+            config.snapshot_interval = sub_matches.get_one::<u64>("snapshot-interval")
+                .map(|secs| std::time::Duration::from_secs(*secs));
+
+            if let Some(columns) = sub_matches.get_one::<String>("columns") {
+                config.columns = TableColumn::parse_list(columns)?;
+            }
         }
         Some(("list", sub_matches)) => {
             if let Some(format) = sub_matches.get_one::<String>("format") {
-                config.output_format = match format.as_str() {
-                    "json" => OutputFormat::Json,
-                    "csv" => OutputFormat::Csv,
-                    _ => OutputFormat::Table,
-                };
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
+            }
+
+            config.sinks = parse_sinks(sub_matches)?;
+            config.file_mode = parse_file_mode(sub_matches);
+
+            if let Some(columns) = sub_matches.get_one::<String>("columns") {
+                config.columns = TableColumn::parse_list(columns)?;
+            }
+
+            if let Some(sort) = sub_matches.get_one::<String>("sort") {
+                config.list_sort = ListSort::from_name(sort).unwrap_or(ListSort::Bus);
+            }
+            config.list_reverse = sub_matches.get_flag("reverse");
+
+            if let Some(classes) = sub_matches.get_one::<String>("class") {
+                config.device_classes = Some(crate::config::DeviceRule::parse_device_classes(classes)?);
+            }
+            config.fail_on_blocked = sub_matches.get_flag("fail-on-blocked");
+        }
+        Some(("config", sub_matches)) => {
+            // Nothing but the requested value may reach stdout
+            match sub_matches.subcommand() {
+                Some(("show", show_matches)) if show_matches.get_flag("json") => config.quiet = true,
+                Some(("get", _)) => config.quiet = true,
+                _ => {}
+            }
+        }
+        Some(("tui", _)) => {
+            // The TUI owns the terminal, so keep log output to errors unless asked for more
+            if matches.get_count("verbose") == 0 {
+                config.log_level = "error".to_string();
+            }
+            config.quiet = true;
+        }
+        Some(("check", sub_matches)) => {
+            if let Some(id) = sub_matches.get_one::<String>("id") {
+                config.device_id = Some(parse_vid_pid(id)?);
+            }
+            config.device_serial = sub_matches.get_one::<String>("serial").cloned();
+        }
+        Some(("tag", sub_matches)) => {
+            if let Some(id) = sub_matches.get_one::<String>("id") {
+                config.device_id = Some(parse_vid_pid(id)?);
+            }
+            config.device_serial = sub_matches.get_one::<String>("serial").cloned();
+            config.device_tag = sub_matches.get_one::<String>("tag").cloned();
+        }
+        Some(("get", sub_matches)) => {
+            if let Some(id) = sub_matches.get_one::<String>("id") {
+                config.device_id = Some(parse_vid_pid(id)?);
+            }
+
+            if let Some(format) = sub_matches.get_one::<String>("format") {
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
+            }
+        }
+        Some(("stats", sub_matches)) => {
+            if let Some(id) = sub_matches.get_one::<String>("device") {
+                config.device_id = Some(parse_vid_pid(id)?);
+            }
+
+            if let Some(sort) = sub_matches.get_one::<String>("sort") {
+                config.stats_sort = StatsSort::from_name(sort).unwrap_or(StatsSort::Connections);
+            }
+
+            if let Some(secs) = sub_matches.get_one::<u64>("duration") {
+                config.stats_duration = std::time::Duration::from_secs(*secs);
+            }
+
+            if let Some(format) = sub_matches.get_one::<String>("format") {
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
+            }
+        }
+        Some(("benchmark", sub_matches)) => {
+            if let Some(secs) = sub_matches.get_one::<u64>("duration") {
+                config.benchmark_duration = std::time::Duration::from_secs(*secs);
+            }
+
+            if let Some(format) = sub_matches.get_one::<String>("format") {
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
+            }
+        }
+        Some(("replay", sub_matches)) => {
+            config.replay_file = sub_matches.get_one::<String>("file").map(PathBuf::from);
+
+            if let Some(speed) = sub_matches.get_one::<f64>("speed") {
+                if !speed.is_finite() || *speed < 0.0 {
+                    anyhow::bail!("Invalid replay speed '{}', must be 0 or greater", speed);
+                }
+                config.replay_speed = *speed;
+            }
+
+            if let Some(format) = sub_matches.get_one::<String>("format") {
+                config.output_format = OutputFormat::from_name(format).unwrap_or(OutputFormat::Table);
             }
+
+            config.sinks = parse_sinks(sub_matches)?;
+            config.file_mode = parse_file_mode(sub_matches);
         }
         _ => {}
     }
@@ -162,6 +924,64 @@ pub fn parse_args(matches: &ArgMatches) -> anyhow::Result<CliConfig> {
     Ok(config)
 }
 
+/// `--columns` for subcommands with table output
+/// `--append` and `--truncate` for subcommands that write output files
+fn file_mode_args() -> [Arg; 2] {
+    [
+        Arg::new("append")
+            .long("append")
+            .action(clap::ArgAction::SetTrue)
+            .help("Add to existing output files (the default)"),
+        Arg::new("truncate")
+            .long("truncate")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("append")
+            .help("Overwrite existing output files instead of adding to them"),
+    ]
+}
+
+fn parse_file_mode(matches: &ArgMatches) -> FileMode {
+    if matches.get_flag("truncate") {
+        FileMode::Truncate
+    } else {
+        FileMode::Append
+    }
+}
+
+fn columns_arg() -> Arg {
+    Arg::new("columns")
+        .long("columns")
+        .value_name("COLUMNS")
+        .help("Table columns to show, in order: bus, vidpid, address, manufacturer, product, class, timestamp")
+}
+
+fn class_arg() -> Arg {
+    Arg::new("class")
+        .long("class")
+        .value_name("CLASSES")
+        .help("Only include these device classes, e.g. 0x08,0x03 or 0x08-0x0b (overrides filters.allowed_device_classes)")
+}
+
+/// Parse all `--sink` occurrences of a subcommand
+fn parse_sinks(matches: &ArgMatches) -> anyhow::Result<Vec<SinkSpec>> {
+    matches.get_many::<String>("sink")
+        .map(|specs| specs.map(|spec| SinkSpec::parse(spec)).collect())
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// Parse a hexadecimal `VID:PID` pair such as `046d:c52b`
+pub fn parse_vid_pid(id: &str) -> anyhow::Result<(u16, u16)> {
+    let (vid, pid) = id.split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Invalid device ID '{}', expected VID:PID", id))?;
+
+    let vid = u16::from_str_radix(vid.trim_start_matches("0x"), 16)
+        .map_err(|_| anyhow::anyhow!("Invalid vendor ID '{}' in '{}'", vid, id))?;
+    let pid = u16::from_str_radix(pid.trim_start_matches("0x"), 16)
+        .map_err(|_| anyhow::anyhow!("Invalid product ID '{}' in '{}'", pid, id))?;
+
+    Ok((vid, pid))
+}
+
 /// Print application banner
 pub fn print_banner() {
     println!(r#"
@@ -1,9 +1,16 @@
-use crate::usb_monitor::{UsbDeviceInfo, UsbDeviceChange};
-use crate::error::{Result, IronWatchError, GuiError};
+use crate::config::DeviceRule;
+use crate::usb_monitor::{ConnectionInterval, DeviceAnalytics, UsbDeviceInfo, UsbDeviceChange};
+use crate::error::{Result, GuiError};
+use log::warn;
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
-use tokio::sync::{mpsc, oneshot, broadcast};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{mpsc, broadcast, Notify};
 use std::time::Duration;
 
+/// Queued events beyond which stale device-list updates are dropped in favour of the latest
+const EVENT_BACKLOG_THRESHOLD: usize = 32;
+
 /// Messages sent from GUI to background monitoring thread
 #[derive(Debug, Clone)]
 pub enum MonitorCommand {
@@ -13,10 +20,18 @@ pub enum MonitorCommand {
     StopMonitoring,
     /// Refresh device list once
     RefreshDevices,
+    /// Send the current analytics once
+    RefreshAnalytics,
     /// Set device filter
     SetFilter(Option<String>),
     /// Update polling interval
     SetPollingInterval(Duration),
+    /// Add a rule to the blacklist and save the configuration
+    AddBlacklistRule(DeviceRule),
+    /// Add a rule to the whitelist and save the configuration
+    AddWhitelistRule(DeviceRule),
+    /// Suspend or resume device blocking until restart, without touching the configuration file
+    SetBlockingSuspended(bool),
     /// Shutdown the monitoring thread
     Shutdown,
 }
@@ -32,6 +47,10 @@ pub enum MonitorEvent {
     DeviceChanged(UsbDeviceChange),
     /// Multiple device changes
     DevicesChanged(Vec<UsbDeviceChange>),
+    /// Session analytics, sent alongside every device list refresh
+    AnalyticsUpdated(DeviceAnalytics),
+    /// Connection intervals from the retained history, sent with the analytics
+    TimelineUpdated(Vec<ConnectionInterval>),
     /// Monitoring started successfully
     MonitoringStarted,
     /// Monitoring stopped
@@ -42,6 +61,8 @@ pub enum MonitorEvent {
     PermissionError(String),
     /// USB subsystem unavailable
     UsbUnavailable(String),
+    /// Device blocking was suspended (`true`) or resumed for this session
+    BlockingSuspended(bool),
 }
 
 /// Status of the monitoring system
@@ -54,13 +75,51 @@ pub enum MonitoringStatus {
     Error(String),
 }
 
+/// Receiving end of the event channel, plus events already drained while coalescing
+struct EventQueue {
+    receiver: mpsc::UnboundedReceiver<MonitorEvent>,
+    backlog: VecDeque<MonitorEvent>,
+}
+
+impl EventQueue {
+    /// Drain the channel, keeping only the most recent device list; returns how many lists were dropped
+    fn coalesce(&mut self, queued_events: &AtomicUsize) -> usize {
+        while let Ok(event) = self.receiver.try_recv() {
+            queued_events.fetch_sub(1, Ordering::Relaxed);
+            self.backlog.push_back(event);
+        }
+        
+        let is_device_list = |event: &MonitorEvent| {
+            matches!(event, MonitorEvent::DevicesLoaded(_) | MonitorEvent::DevicesUpdated(_))
+        };
+        
+        let Some(latest) = self.backlog.iter().rposition(is_device_list) else {
+            return 0;
+        };
+        
+        let before = self.backlog.len();
+        let mut index = 0;
+        self.backlog.retain(|event| {
+            let keep = !is_device_list(event) || index == latest;
+            index += 1;
+            keep
+        });
+        
+        before - self.backlog.len()
+    }
+}
+
 /// Communication hub for GUI-background thread coordination
 #[derive(Clone)]
 pub struct CommunicationHub {
     /// Channel for sending commands to monitoring thread
     command_sender: mpsc::UnboundedSender<MonitorCommand>,
     /// Channel for receiving events from monitoring thread
-    event_receiver: Arc<Mutex<mpsc::UnboundedReceiver<MonitorEvent>>>,
+    event_queue: Arc<Mutex<EventQueue>>,
+    /// Number of events sent but not yet drained from the channel
+    queued_events: Arc<AtomicUsize>,
+    /// Woken for every event sent, so `recv_event` can wait without polling
+    event_notify: Arc<Notify>,
     /// Broadcast channel for status updates
     status_sender: broadcast::Sender<MonitoringStatus>,
     /// Current monitoring status
@@ -78,10 +137,17 @@ impl CommunicationHub {
         
         let current_status = Arc::new(Mutex::new(MonitoringStatus::Stopped));
         let current_devices = Arc::new(Mutex::new(Vec::new()));
+        let queued_events = Arc::new(AtomicUsize::new(0));
+        let event_notify = Arc::new(Notify::new());
         
         let hub = Self {
             command_sender,
-            event_receiver: Arc::new(Mutex::new(event_receiver)),
+            event_queue: Arc::new(Mutex::new(EventQueue {
+                receiver: event_receiver,
+                backlog: VecDeque::new(),
+            })),
+            queued_events: queued_events.clone(),
+            event_notify: event_notify.clone(),
             status_sender: status_sender.clone(),
             current_status: current_status.clone(),
             current_devices: current_devices.clone(),
@@ -90,6 +156,8 @@ impl CommunicationHub {
         let receiver = CommunicationReceiver {
             command_receiver,
             event_sender,
+            queued_events,
+            event_notify,
             status_sender,
             current_status,
             current_devices,
@@ -105,13 +173,51 @@ impl CommunicationHub {
         Ok(())
     }
     
-    /// Try to receive an event from the monitoring thread (non-blocking)
+    /// Try to receive an event from the monitoring thread (non-blocking).
+    ///
+    /// When the consumer has fallen behind, redundant device lists are coalesced so only the
+    /// latest one is delivered; all other events are kept in order.
     pub fn try_recv_event(&self) -> Option<MonitorEvent> {
-        if let Ok(mut receiver) = self.event_receiver.try_lock() {
-            receiver.try_recv().ok()
-        } else {
-            None
+        let mut queue = self.event_queue.try_lock().ok()?;
+        
+        let depth = self.queued_events.load(Ordering::Relaxed);
+        if depth > EVENT_BACKLOG_THRESHOLD {
+            let dropped = queue.coalesce(&self.queued_events);
+            if dropped > 0 {
+                warn!("UI fell behind by {} events; skipped {} stale device list updates", depth, dropped);
+            }
+        }
+        
+        if let Some(event) = queue.backlog.pop_front() {
+            return Some(event);
         }
+        
+        let event = queue.receiver.try_recv().ok()?;
+        self.queued_events.fetch_sub(1, Ordering::Relaxed);
+        Some(event)
+    }
+    
+    /// Wait for the next event; `None` once the monitoring thread has exited and every event was received
+    pub async fn recv_event(&self) -> Option<MonitorEvent> {
+        loop {
+            if let Some(event) = self.try_recv_event() {
+                return Some(event);
+            }
+            if self.command_sender.is_closed() {
+                return None;
+            }
+            // A notification sent since the check above is kept, so no event is missed
+            tokio::select! {
+                _ = self.event_notify.notified() => {}
+                _ = self.command_sender.closed() => {}
+            }
+        }
+    }
+    
+    /// Number of events waiting to be received
+    pub fn queued_events(&self) -> usize {
+        let backlog = self.event_queue.lock().map(|queue| queue.backlog.len()).unwrap_or(0);
+        self.queued_events.load(Ordering::Relaxed) + backlog
     }
     
     /// Get current monitoring status
@@ -144,6 +250,26 @@ impl CommunicationHub {
         self.send_command(MonitorCommand::RefreshDevices)
     }
     
+    /// Request the current analytics
+    pub fn refresh_analytics(&self) -> Result<()> {
+        self.send_command(MonitorCommand::RefreshAnalytics)
+    }
+    
+    /// Add a blacklist rule
+    pub fn add_blacklist_rule(&self, rule: DeviceRule) -> Result<()> {
+        self.send_command(MonitorCommand::AddBlacklistRule(rule))
+    }
+    
+    /// Add a whitelist rule
+    pub fn add_whitelist_rule(&self, rule: DeviceRule) -> Result<()> {
+        self.send_command(MonitorCommand::AddWhitelistRule(rule))
+    }
+    
+    /// Allow every device until restart, or resume enforcing the rules
+    pub fn set_blocking_suspended(&self, suspended: bool) -> Result<()> {
+        self.send_command(MonitorCommand::SetBlockingSuspended(suspended))
+    }
+    
     /// Set device filter
     pub fn set_filter(&self, filter: Option<String>) -> Result<()> {
         self.send_command(MonitorCommand::SetFilter(filter))
@@ -159,6 +285,8 @@ impl CommunicationHub {
 pub struct CommunicationReceiver {
     command_receiver: mpsc::UnboundedReceiver<MonitorCommand>,
     event_sender: mpsc::UnboundedSender<MonitorEvent>,
+    queued_events: Arc<AtomicUsize>,
+    event_notify: Arc<Notify>,
     status_sender: broadcast::Sender<MonitoringStatus>,
     current_status: Arc<Mutex<MonitoringStatus>>,
     current_devices: Arc<Mutex<Vec<UsbDeviceInfo>>>,
@@ -192,6 +320,8 @@ impl CommunicationReceiver {
         
         self.event_sender.send(event)
             .map_err(|_| GuiError::communication_error("Failed to send event to GUI thread"))?;
+        self.queued_events.fetch_add(1, Ordering::Relaxed);
+        self.event_notify.notify_one();
         Ok(())
     }
     
@@ -231,6 +361,16 @@ impl CommunicationReceiver {
         self.send_event(MonitorEvent::PermissionError(error.to_string()))
     }
     
+    /// Send USB unavailable event
+    pub fn send_usb_unavailable(&self, error: &str) -> Result<()> {
+        self.send_event(MonitorEvent::UsbUnavailable(error.to_string()))
+    }
+    
+    /// Send blocking suspended or resumed event
+    pub fn send_blocking_suspended(&self, suspended: bool) -> Result<()> {
+        self.send_event(MonitorEvent::BlockingSuspended(suspended))
+    }
+    
     /// Send devices loaded event
     pub fn send_devices_loaded(&self, devices: Vec<UsbDeviceInfo>) -> Result<()> {
         self.send_event(MonitorEvent::DevicesLoaded(devices))
@@ -241,10 +381,25 @@ impl CommunicationReceiver {
         self.send_event(MonitorEvent::DevicesUpdated(devices))
     }
     
+    /// Send analytics updated event
+    pub fn send_analytics_updated(&self, analytics: DeviceAnalytics) -> Result<()> {
+        self.send_event(MonitorEvent::AnalyticsUpdated(analytics))
+    }
+    
+    /// Send timeline updated event
+    pub fn send_timeline_updated(&self, timeline: Vec<ConnectionInterval>) -> Result<()> {
+        self.send_event(MonitorEvent::TimelineUpdated(timeline))
+    }
+    
     /// Send device change event
     pub fn send_device_change(&self, change: UsbDeviceChange) -> Result<()> {
         self.send_event(MonitorEvent::DeviceChanged(change))
     }
+    
+    /// Send all changes from one monitoring cycle as a single event
+    pub fn send_devices_changed(&self, changes: Vec<UsbDeviceChange>) -> Result<()> {
+        self.send_event(MonitorEvent::DevicesChanged(changes))
+    }
 }
 
 /// Helper for graceful shutdown coordination
@@ -252,6 +407,12 @@ pub struct ShutdownCoordinator {
     shutdown_flag: Arc<std::sync::atomic::AtomicBool>,
 }
 
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ShutdownCoordinator {
     pub fn new() -> Self {
         Self {
@@ -300,6 +461,24 @@ mod tests {
         assert!(matches!(event, Some(MonitorEvent::DevicesLoaded(_))));
     }
     
+    #[test]
+    fn test_lagging_consumer_gets_only_latest_device_list() {
+        let (hub, receiver) = CommunicationHub::new();
+        
+        for _ in 0..EVENT_BACKLOG_THRESHOLD {
+            receiver.send_devices_updated(vec![]).unwrap();
+        }
+        receiver.send_monitoring_started().unwrap();
+        receiver.send_devices_updated(vec![]).unwrap();
+        assert_eq!(hub.queued_events(), EVENT_BACKLOG_THRESHOLD + 2);
+        
+        let events: Vec<_> = std::iter::from_fn(|| hub.try_recv_event()).collect();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], MonitorEvent::MonitoringStarted));
+        assert!(matches!(events[1], MonitorEvent::DevicesUpdated(_)));
+        assert_eq!(hub.queued_events(), 0);
+    }
+    
     #[test]
     fn test_shutdown_coordinator() {
         let mut coordinator = ShutdownCoordinator::new();
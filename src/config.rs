@@ -1,9 +1,64 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
 use log::{info, debug, warn};
 use dirs::config_dir;
+use crate::rule_expr::RuleExpr;
+
+/// Environment variable that overrides the configuration directory
+pub const CONFIG_DIR_ENV: &str = "IRONWATCH_CONFIG_DIR";
+
+/// File name of the configuration inside the configuration directory
+const CONFIG_FILE_NAME: &str = "config.json";
+
+/// `--config -`: read the configuration from stdin; it is never saved
+pub const STDIN_CONFIG_PATH: &str = "-";
+
+/// Message shown when a device is blocked and `device_rules.block_message` is not set
+pub const DEFAULT_BLOCK_MESSAGE: &str = "This USB device is not permitted on this computer. Contact your administrator if you need it.";
+
+/// Key paths accepted by `set_value`/`get_value`, and overridable from the environment
+const SETTABLE_KEYS: &[&str] = &[
+    "monitoring.poll_interval_ms",
+    "monitoring.auto_start",
+    "monitoring.track_input_events",
+    "monitoring.detect_suspicious_activity",
+    "monitoring.udev_enrichment",
+    "monitoring.suspend_window_ms",
+    "monitoring.device_identity",
+    "monitoring.descriptor_strings",
+    "monitoring.usb_debug",
+    "monitoring.enumeration_retries",
+    "monitoring.enumeration_timeout_ms",
+    "monitoring.raw_descriptors",
+    "monitoring.max_devices",
+    "monitoring.snapshot_interval_secs",
+    "monitoring.snapshot_keep",
+    "output.default_format",
+    "output.color_output",
+    "output.include_metadata",
+    "output.rotate",
+    "output.latest_link",
+    "output.include_tags",
+    "logging.level",
+    "logging.format",
+    "logging.file_logging",
+    "filters.ignored_vendors",
+    "filters.ignored_products",
+    "filters.name_patterns",
+    "filters.name_patterns_mode",
+    "device_rules.enforce",
+    "device_rules.block_message",
+    "mqtt.broker",
+    "mqtt.topic_prefix",
+    "gui.device_colors",
+    "analytics.merge_serialless_by_vidpid",
+];
+
+/// Port used when an MQTT broker URL doesn't name one
+const DEFAULT_MQTT_PORT: u16 = 1883;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -12,6 +67,15 @@ pub struct Config {
     pub logging: LoggingConfig,
     pub filters: FilterConfig,
     pub device_rules: DeviceRulesConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub gui: GuiConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    /// Friendly labels such as "Lab scope", keyed by device fingerprint
+    #[serde(default)]
+    pub device_tags: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,8 +84,140 @@ pub struct MonitoringConfig {
     pub auto_start: bool,
     pub track_input_events: bool,
     pub detect_suspicious_activity: bool,
+    /// Fill missing descriptor strings from udev properties (Linux only)
+    #[serde(default)]
+    pub udev_enrichment: bool,
+    /// Report a device that disconnects and returns with the same serial within this window
+    /// as suspended instead of disconnected/reconnected (0 disables)
+    #[serde(default)]
+    pub suspend_window_ms: u64,
+    /// How devices are keyed for change detection and statistics
+    #[serde(default)]
+    pub device_identity: DeviceIdentity,
+    /// Open devices to read manufacturer/product/serial strings; when off, names come from
+    /// the USB ID database and serial numbers are unavailable
+    #[serde(default = "default_descriptor_strings")]
+    pub descriptor_strings: bool,
+    /// libusb's own log level: 0 none, 1 error, 2 warning, 3 info, 4 debug
+    #[serde(default)]
+    pub usb_debug: u8,
+    /// Retries for an enumeration failing with a transient USB error (Busy, Timeout, Pipe)
+    #[serde(default = "default_enumeration_retries")]
+    pub enumeration_retries: u32,
+    /// Longest wait for one device to answer while its strings are read (0 waits indefinitely)
+    #[serde(default = "default_enumeration_timeout_ms")]
+    pub enumeration_timeout_ms: u64,
+    /// Descriptor bytes read from each device for JSON export; off unless asked for, since it
+    /// opens every device
+    #[serde(default)]
+    pub raw_descriptors: RawDescriptors,
+    /// Most devices tracked per cycle; more is reported as a device storm (0 disables)
+    #[serde(default)]
+    pub max_devices: usize,
+    /// Write an analytics snapshot this often (0 disables)
+    #[serde(default)]
+    pub snapshot_interval_secs: u64,
+    /// Directory for analytics snapshots; defaults to `ironwatch/snapshots` in the local data directory
+    #[serde(default)]
+    pub snapshot_dir: Option<PathBuf>,
+    /// Number of most recent snapshots kept
+    #[serde(default = "default_snapshot_keep")]
+    pub snapshot_keep: usize,
+}
+
+/// Highest `monitoring.usb_debug` level (libusb debug output)
+pub const MAX_USB_DEBUG: u8 = 4;
+
+/// Default `monitoring.enumeration_retries`
+pub const DEFAULT_ENUMERATION_RETRIES: u32 = 3;
+
+fn default_enumeration_retries() -> u32 {
+    DEFAULT_ENUMERATION_RETRIES
+}
+
+/// Default `monitoring.enumeration_timeout_ms`
+pub const DEFAULT_ENUMERATION_TIMEOUT_MS: u64 = 1000;
+
+fn default_enumeration_timeout_ms() -> u64 {
+    DEFAULT_ENUMERATION_TIMEOUT_MS
+}
+
+fn default_descriptor_strings() -> bool {
+    true
+}
+
+fn default_snapshot_keep() -> usize {
+    48
+}
+
+/// Strategy for the key that identifies a device across polls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceIdentity {
+    /// `vid:pid:bus:address`: one key per physical slot
+    #[default]
+    PortBased,
+    /// The serial number alone, so a device keeps its key on any port
+    SerialBased,
+    /// `vid:pid:serial`, which tolerates vendors reusing serials across products
+    VidPidSerial,
+}
+
+impl DeviceIdentity {
+    /// Parse the name used in the config file and `config set`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "port_based" => Some(DeviceIdentity::PortBased),
+            "serial_based" => Some(DeviceIdentity::SerialBased),
+            "vid_pid_serial" => Some(DeviceIdentity::VidPidSerial),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceIdentity::PortBased => "port_based",
+            DeviceIdentity::SerialBased => "serial_based",
+            DeviceIdentity::VidPidSerial => "vid_pid_serial",
+        }
+    }
+}
+
+/// Which descriptors `monitoring.raw_descriptors` reads from each device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawDescriptors {
+    /// None; devices are only opened for their strings
+    #[default]
+    Off,
+    /// The 18-byte device descriptor
+    Device,
+    /// The device descriptor followed by every configuration descriptor
+    All,
+}
+
+impl RawDescriptors {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(RawDescriptors::Off),
+            "device" => Some(RawDescriptors::Device),
+            "all" => Some(RawDescriptors::All),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RawDescriptors::Off => "off",
+            RawDescriptors::Device => "device",
+            RawDescriptors::All => "all",
+        }
+    }
 }
 
+/// Output format names accepted by `--format` and `output.default_format`
+pub const OUTPUT_FORMATS: [&str; 6] = ["json", "ndjson", "table", "csv", "xml", "toml"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputConfig {
     pub default_format: String,
@@ -29,6 +225,19 @@ pub struct OutputConfig {
     pub include_metadata: bool,
     pub color_output: bool,
     pub max_log_entries: usize,
+    /// Output file rotation: `none`, or `daily` for one file per UTC day
+    #[serde(default = "default_output_rotate")]
+    pub rotate: String,
+    /// With daily rotation, keep a `<name>-latest` symlink pointing at the current file
+    #[serde(default)]
+    pub latest_link: bool,
+    /// Add each device's tag from `device_tags` to device listings and exports
+    #[serde(default)]
+    pub include_tags: bool,
+}
+
+fn default_output_rotate() -> String {
+    "none".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,14 +247,149 @@ pub struct LoggingConfig {
     pub log_file_path: Option<PathBuf>,
     pub max_log_file_size_mb: u64,
     pub rotate_logs: bool,
+    /// Application log record format: `text` or `json` (one object per line)
+    #[serde(default = "default_log_format")]
+    pub format: String,
+    /// Append-only JSON-lines record of every allow/deny decision
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilterConfig {
+    /// Devices from these vendors are left out of listings and never generate events
     pub ignored_vendors: Vec<u16>,
+    /// Devices with these product IDs, from any vendor, are left out likewise
     pub ignored_products: Vec<u16>,
     pub allowed_device_classes: Option<Vec<u8>>,
+    /// Glob patterns (`*`, `?`, case-insensitive) matched against product and manufacturer names
     pub name_patterns: Vec<String>,
+    /// Whether devices matching `name_patterns` are hidden or the only ones shown
+    #[serde(default)]
+    pub name_patterns_mode: NamePatternsMode,
+}
+
+/// What `filters.name_patterns` does with the devices it matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamePatternsMode {
+    /// Matching devices are hidden
+    #[default]
+    Ignore,
+    /// Only matching devices are shown
+    Allow,
+}
+
+impl NamePatternsMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ignore" => Some(NamePatternsMode::Ignore),
+            "allow" => Some(NamePatternsMode::Allow),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamePatternsMode::Ignore => "ignore",
+            NamePatternsMode::Allow => "allow",
+        }
+    }
+}
+
+/// Publishing device events to an MQTT broker (needs the `mqtt` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// Broker URL such as `mqtt://broker.local:1883`; publishing is off when unset
+    #[serde(default)]
+    pub broker: Option<String>,
+    /// Events go to `<topic_prefix>/<host>/device`
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "ironwatch".to_string()
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: None,
+            topic_prefix: default_mqtt_topic_prefix(),
+        }
+    }
+}
+
+/// How session analytics are counted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Count serial-less devices with the same VID:PID as one unique device, whatever port they use.
+    ///
+    /// Per-port statistics are unaffected; identical serial-less devices in use at once also count once.
+    #[serde(default)]
+    pub merge_serialless_by_vidpid: bool,
+}
+
+/// Desktop GUI preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuiConfig {
+    /// What the color dot in the device table stands for
+    #[serde(default)]
+    pub device_colors: DeviceColoring,
+}
+
+/// Basis for device colors in the GUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceColoring {
+    /// One color per vendor ID
+    #[default]
+    Vendor,
+    /// One color per USB device class
+    Class,
+}
+
+impl DeviceColoring {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vendor" => Some(DeviceColoring::Vendor),
+            "class" => Some(DeviceColoring::Class),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceColoring::Vendor => "vendor",
+            DeviceColoring::Class => "class",
+        }
+    }
+}
+
+/// Host and port of a broker URL (`mqtt://host[:port]`, `tcp://host[:port]` or `host[:port]`)
+pub fn parse_mqtt_broker(url: &str) -> Result<(String, u16)> {
+    let address = url.strip_prefix("mqtt://")
+        .or_else(|| url.strip_prefix("tcp://"))
+        .unwrap_or(url)
+        .trim_end_matches('/');
+    if address.contains("://") {
+        anyhow::bail!("Unsupported MQTT broker URL '{}'. Use mqtt://host:port", url);
+    }
+
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().with_context(|| format!("Invalid port in MQTT broker URL '{}'", url))?),
+        None => (address, DEFAULT_MQTT_PORT),
+    };
+    if host.is_empty() || host.contains(['/', ' ']) {
+        anyhow::bail!("Invalid host in MQTT broker URL '{}'", url);
+    }
+
+    Ok((host.to_string(), port))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,36 +400,214 @@ pub struct DeviceRulesConfig {
     pub whitelisted_devices: Vec<DeviceRule>,
     pub auto_block_suspicious: bool,
     pub block_threshold: u32,
+    /// Actively detach/reset blocked devices instead of only labelling them
+    #[serde(default)]
+    pub enforce: bool,
+    /// Message shown to the user when a device is blocked
+    #[serde(default)]
+    pub block_message: Option<String>,
+}
+
+impl DeviceRulesConfig {
+    /// The configured block message, or the default one
+    pub fn block_message(&self) -> &str {
+        self.block_message.as_deref().unwrap_or(DEFAULT_BLOCK_MESSAGE)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DeviceRule {
+    /// Stable identifier so events can name the rule that fired; filled in on load for older configs
+    #[serde(default)]
+    pub id: String,
     pub vendor_id: Option<u16>,
     pub product_id: Option<u16>,
     pub device_class: Option<u8>,
+    /// Matches a device whose class is any of these, e.g. mass storage and smart card
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_classes: Option<Vec<u8>>,
     pub manufacturer: Option<String>,
     pub product_name: Option<String>,
     pub serial_number: Option<String>,
+    /// Regex the serial number must match, e.g. `^[0-9A-F]{12}$`; checked in addition to `serial_number`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_pattern: Option<String>,
+    /// Boolean expression over device fields, e.g. `class == 0x08 && manufacturer != "Kingston"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
     pub reason: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub enabled: bool,
 }
 
+/// The rule that decided an allow/deny, and the list it came from
+#[derive(Debug, Clone, Copy)]
+pub struct MatchedRule<'a> {
+    /// `whitelist` or `blacklist`
+    pub list: &'static str,
+    pub rule: &'a DeviceRule,
+}
+
+/// Outcome of running a device through the whitelist/blacklist rules
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyDecision {
+    pub blocked: bool,
+    pub reason: Option<String>,
+}
+
+impl PolicyDecision {
+    /// Short status label for display ("ALLOWED" or "BLOCKED")
+    pub fn status(&self) -> &'static str {
+        if self.blocked { "BLOCKED" } else { "ALLOWED" }
+    }
+}
+
+/// 64-bit FNV-1a, used for rule ids because it is stable across builds and platforms
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Parse a comma-separated list of hex IDs such as `0x1d6b,8087`; an empty list clears the setting
+fn parse_id_list(list: &str) -> Result<Vec<u16>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            let hex = id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")).unwrap_or(id);
+            u16::from_str_radix(hex, 16).with_context(|| format!("Invalid hex ID: {}", id))
+        })
+        .collect()
+}
+
+fn format_id_list(ids: &[u16]) -> String {
+    ids.iter().map(|id| format!("0x{:04x}", id)).collect::<Vec<_>>().join(",")
+}
+
+/// Compile a rule's serial pattern once and reuse it; an invalid pattern is reported once and
+/// yields `None`, which makes the rule match nothing
+fn compiled_serial_pattern(pattern: &str) -> Option<regex::Regex> {
+    static CACHE: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<String, Option<regex::Regex>>>> =
+        std::sync::OnceLock::new();
+    
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    cache.entry(pattern.to_string())
+        .or_insert_with(|| match regex::Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                warn!("Disabling rule with invalid serial_pattern '{}': {}", pattern, e);
+                None
+            }
+        })
+        .clone()
+}
+
+/// Parse a rule's expression once and reuse it; an invalid expression is reported once and
+/// yields `None`, which makes the rule match nothing
+fn compiled_expression(source: &str) -> Option<std::sync::Arc<RuleExpr>> {
+    static CACHE: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<String, Option<std::sync::Arc<RuleExpr>>>>> =
+        std::sync::OnceLock::new();
+    
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    cache.entry(source.to_string())
+        .or_insert_with(|| match RuleExpr::parse(source) {
+            Ok(expr) => Some(std::sync::Arc::new(expr)),
+            Err(e) => {
+                warn!("Disabling rule with invalid expression '{}': {}", source, e);
+                None
+            }
+        })
+        .clone()
+}
+
+impl Default for DeviceRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DeviceRule {
     pub fn new() -> Self {
+        static CREATED: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        
+        let created_at = chrono::Utc::now();
+        // Rules created in the same instant still get distinct ids
+        let seed = format!(
+            "{}:{}:{}",
+            created_at.timestamp_nanos_opt().unwrap_or_default(),
+            std::process::id(),
+            CREATED.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        );
+        
         Self {
+            id: format!("{:016x}", fnv1a(seed.as_bytes())),
             vendor_id: None,
             product_id: None,
             device_class: None,
+            device_classes: None,
             manufacturer: None,
             product_name: None,
             serial_number: None,
+            serial_pattern: None,
+            expression: None,
             reason: String::new(),
-            created_at: chrono::Utc::now(),
+            created_at,
             enabled: true,
         }
     }
     
+    /// Parse a class list such as `0x08,0x0b` or `8-11`; values are hex with `0x`, decimal otherwise
+    pub fn parse_device_classes(list: &str) -> Result<Vec<u8>> {
+        let parse = |value: &str| -> Result<u8> {
+            let value = value.trim();
+            match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+                Some(hex) => u8::from_str_radix(hex, 16),
+                None => value.parse(),
+            }.with_context(|| format!("Invalid device class: {}", value))
+        };
+        
+        let mut classes = Vec::new();
+        for part in list.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse(start)?, parse(end)?);
+                    if start > end {
+                        anyhow::bail!("Invalid device class range: {}", part);
+                    }
+                    classes.extend(start..=end);
+                }
+                None => classes.push(parse(part)?),
+            }
+        }
+        
+        if classes.is_empty() {
+            anyhow::bail!("Device class list is empty");
+        }
+        classes.sort_unstable();
+        classes.dedup();
+        Ok(classes)
+    }
+    
+    /// Whether two rules match on the same criteria, ignoring id, reason, creation time and state
+    pub fn same_criteria(&self, other: &DeviceRule) -> bool {
+        let criteria = |rule: &DeviceRule| DeviceRule {
+            id: String::new(),
+            reason: String::new(),
+            created_at: chrono::DateTime::<chrono::Utc>::MIN_UTC,
+            enabled: true,
+            ..rule.clone()
+        };
+        criteria(self) == criteria(other)
+    }
+    
+    /// Id derived from the rule's contents, for rules saved before ids existed
+    fn content_id(&self) -> String {
+        let content = serde_json::to_string(&DeviceRule { id: String::new(), ..self.clone() })
+            .unwrap_or_default();
+        format!("{:016x}", fnv1a(content.as_bytes()))
+    }
+    
     pub fn matches_device(&self, device: &crate::usb_monitor::UsbDeviceInfo) -> bool {
         // Check vendor ID
         if let Some(vid) = self.vendor_id {
@@ -108,6 +630,12 @@ impl DeviceRule {
             }
         }
         
+        if let Some(ref classes) = self.device_classes {
+            if !classes.contains(&device.device_class) {
+                return false;
+            }
+        }
+        
         // Check manufacturer
         if let Some(ref manufacturer) = self.manufacturer {
             if let Some(ref device_manufacturer) = device.manufacturer {
@@ -141,6 +669,21 @@ impl DeviceRule {
             }
         }
         
+        if let Some(ref pattern) = self.serial_pattern {
+            let is_match = compiled_serial_pattern(pattern).is_some_and(|regex| {
+                device.serial_number.as_deref().is_some_and(|serial| regex.is_match(serial))
+            });
+            if !is_match {
+                return false;
+            }
+        }
+        
+        if let Some(ref expression) = self.expression {
+            if !compiled_expression(expression).is_some_and(|expr| expr.matches(device)) {
+                return false;
+            }
+        }
+        
         true
     }
 }
@@ -153,6 +696,18 @@ impl Default for Config {
                 auto_start: false,
                 track_input_events: true,
                 detect_suspicious_activity: true,
+                udev_enrichment: false,
+                suspend_window_ms: 0,
+                device_identity: DeviceIdentity::PortBased,
+                descriptor_strings: default_descriptor_strings(),
+                usb_debug: 0,
+                enumeration_retries: DEFAULT_ENUMERATION_RETRIES,
+                enumeration_timeout_ms: DEFAULT_ENUMERATION_TIMEOUT_MS,
+                raw_descriptors: RawDescriptors::Off,
+                max_devices: 0,
+                snapshot_interval_secs: 0,
+                snapshot_dir: None,
+                snapshot_keep: default_snapshot_keep(),
             },
             output: OutputConfig {
                 default_format: "table".to_string(),
@@ -160,6 +715,9 @@ impl Default for Config {
                 include_metadata: true,
                 color_output: true,
                 max_log_entries: 1000,
+                rotate: default_output_rotate(),
+                latest_link: false,
+                include_tags: false,
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -167,12 +725,15 @@ impl Default for Config {
                 log_file_path: None,
                 max_log_file_size_mb: 10,
                 rotate_logs: true,
+                format: default_log_format(),
+                audit_log_path: None,
             },
             filters: FilterConfig {
                 ignored_vendors: vec![],
                 ignored_products: vec![],
                 allowed_device_classes: None,
                 name_patterns: vec![],
+                name_patterns_mode: NamePatternsMode::Ignore,
             },
             device_rules: DeviceRulesConfig {
                 blacklist_enabled: true,
@@ -181,15 +742,41 @@ impl Default for Config {
                 whitelisted_devices: vec![],
                 auto_block_suspicious: false,
                 block_threshold: 5,
+                enforce: false,
+                block_message: None,
             },
+            mqtt: MqttConfig::default(),
+            gui: GuiConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            device_tags: HashMap::new(),
         }
     }
 }
 
+/// The tag for `device`: one set for its own fingerprint, which includes the serial when it has
+/// one, or else one set for its VID:PID
+pub fn device_tag<'a>(tags: &'a HashMap<String, String>, device: &crate::usb_monitor::UsbDeviceInfo) -> Option<&'a str> {
+    tags.get(&device.fingerprint)
+        .or_else(|| tags.get(&crate::usb_monitor::device_fingerprint(device.vendor_id, device.product_id, None)))
+        .map(String::as_str)
+}
+
 #[derive(Debug)]
 pub struct ConfigManager {
     config: Config,
     config_path: PathBuf,
+    /// Values taken from `IRONWATCH_*` environment variables or command-line flags, never written back on save
+    env_overrides: Vec<EnvOverride>,
+    /// Session-only escape hatch: no device is blocked until restart
+    blocking_suspended: bool,
+}
+
+/// A configuration value overridden from the environment or the command line
+#[derive(Debug, Clone)]
+struct EnvOverride {
+    key_path: &'static str,
+    /// Value from the file (or defaults) that is persisted instead of the override
+    file_value: String,
 }
 
 impl ConfigManager {
@@ -200,31 +787,113 @@ impl ConfigManager {
             None => Self::get_default_config_path()?,
         };
 
-        let config = if config_path.exists() {
+        let config = if config_path == Path::new(STDIN_CONFIG_PATH) {
+            Self::load_from_stdin()?
+        } else if config_path.exists() {
             Self::load_from_file(&config_path)?
         } else {
             info!("No configuration file found, using defaults");
             Config::default()
         };
 
-        Ok(Self {
+        let mut manager = Self {
             config,
             config_path,
-        })
+            env_overrides: Vec::new(),
+            blocking_suspended: false,
+        };
+        manager.apply_env_overrides()?;
+
+        Ok(manager)
+    }
+
+    /// Wrap a configuration built in memory, e.g. by a library user; it has no file and is never saved
+    pub fn from_config(config: Config) -> Self {
+        Self {
+            config: Self::finish_loading(config),
+            config_path: PathBuf::new(),
+            env_overrides: Vec::new(),
+            blocking_suspended: false,
+        }
+    }
+
+    /// Name of the environment variable overriding a key path, e.g. `IRONWATCH_LOGGING_LEVEL`
+    pub fn env_var_name(key_path: &str) -> String {
+        format!("IRONWATCH_{}", key_path.replace('.', "_").to_uppercase())
     }
 
-    /// Get the default configuration file path
-    fn get_default_config_path() -> Result<PathBuf> {
-        let config_dir = config_dir()
-            .context("Could not determine config directory")?;
+    /// Overlay `IRONWATCH_*` environment variables on top of the loaded configuration
+    fn apply_env_overrides(&mut self) -> Result<()> {
+        for &key_path in SETTABLE_KEYS {
+            let var = Self::env_var_name(key_path);
+            let Ok(value) = std::env::var(&var) else {
+                continue;
+            };
+
+            let file_value = self.get_value(key_path)?;
+            Self::apply_value(&mut self.config, key_path, &value)
+                .with_context(|| format!("Invalid value in environment variable {}", var))?;
+
+            info!("Configuration override from {}: {} = {}", var, key_path, value);
+            self.env_overrides.push(EnvOverride { key_path, file_value });
+        }
+
+        Ok(())
+    }
+
+    /// Override a value for this run only (e.g. from a command-line flag); it is not saved
+    pub fn override_value(&mut self, key_path: &str, value: &str) -> Result<()> {
+        let key_path = *SETTABLE_KEYS.iter()
+            .find(|key| **key == key_path)
+            .with_context(|| format!("Unknown configuration key: {}", key_path))?;
+
+        // Keep the original file value if the environment already overrode this key
+        let file_value = match self.env_overrides.iter().position(|o| o.key_path == key_path) {
+            Some(index) => self.env_overrides.remove(index).file_value,
+            None => self.get_value(key_path)?,
+        };
+
+        Self::apply_value(&mut self.config, key_path, value)?;
+        self.env_overrides.push(EnvOverride { key_path, file_value });
+        Ok(())
+    }
+
+    /// Get the configuration file path inside a specific configuration directory
+    pub fn config_path_in(dir: &Path) -> PathBuf {
+        dir.join(CONFIG_FILE_NAME)
+    }
+
+    /// The file `new` would load for `custom_path`, without creating anything
+    pub fn resolve_config_path(custom_path: Option<PathBuf>) -> Result<PathBuf> {
+        match custom_path {
+            Some(path) => Ok(path),
+            None => Ok(Self::config_path_in(&Self::default_config_dir()?)),
+        }
+    }
+
+    /// Get the default configuration directory.
+    ///
+    /// `IRONWATCH_CONFIG_DIR` takes precedence; otherwise the platform config directory is used
+    /// (`$XDG_CONFIG_HOME` or `~/.config` on Linux, as resolved by `dirs`).
+    fn default_config_dir() -> Result<PathBuf> {
+        match std::env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => Ok(config_dir()
+                .context("Could not determine config directory")?
+                .join("ironwatch")),
+        }
+    }
+
+    /// Get the default configuration file path, creating its directory if needed
+    pub fn get_default_config_path() -> Result<PathBuf> {
+        let app_config_dir = Self::default_config_dir()?;
         
-        let app_config_dir = config_dir.join("ironwatch");
         if !app_config_dir.exists() {
             fs::create_dir_all(&app_config_dir)
-                .context("Failed to create config directory")?;
+                .with_context(|| format!("Failed to create config directory: {}", app_config_dir.display()))?;
         }
 
-        Ok(app_config_dir.join("config.json"))
+        Ok(Self::config_path_in(&app_config_dir))
     }
 
     /// Load configuration from file
@@ -234,15 +903,54 @@ impl ConfigManager {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
         
-        let config: Config = serde_json::from_str(&content)
+        let config = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
         
+        Ok(Self::finish_loading(config))
+    }
+
+    fn load_from_stdin() -> Result<Config> {
+        debug!("Loading configuration from stdin");
+        
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)
+            .context("Failed to read configuration from stdin")?;
+        
+        let config = serde_json::from_str(&content)
+            .context("Failed to parse configuration from stdin")?;
+        
+        Ok(Self::finish_loading(config))
+    }
+
+    fn finish_loading(mut config: Config) -> Config {
+        // Derived from the rule's contents, so the id stays the same on every load until saved
+        let rules = &mut config.device_rules;
+        for rule in rules.blacklisted_devices.iter_mut().chain(rules.whitelisted_devices.iter_mut()) {
+            if rule.id.is_empty() {
+                rule.id = rule.content_id();
+            }
+        }
+        
         info!("Configuration loaded successfully");
-        Ok(config)
+        config
+    }
+
+    /// Whether the configuration was piped in with `--config -`
+    pub fn is_from_stdin(&self) -> bool {
+        self.config_path == Path::new(STDIN_CONFIG_PATH)
     }
 
     /// Save current configuration to file
     pub fn save(&self) -> Result<()> {
+        if self.is_from_stdin() {
+            warn!("Configuration was read from stdin, so changes are not saved");
+            return Ok(());
+        }
+        if self.config_path.as_os_str().is_empty() {
+            warn!("Configuration was given in memory, so changes are not saved");
+            return Ok(());
+        }
+        
         debug!("Saving configuration to: {}", self.config_path.display());
         
         // Ensure parent directory exists
@@ -253,7 +961,13 @@ impl ConfigManager {
             }
         }
 
-        let content = serde_json::to_string_pretty(&self.config)
+        // Persist file values for anything overridden from the environment
+        let mut persisted = self.config.clone();
+        for o in &self.env_overrides {
+            Self::apply_value(&mut persisted, o.key_path, &o.file_value)?;
+        }
+
+        let content = serde_json::to_string_pretty(&persisted)
             .context("Failed to serialize configuration")?;
         
         fs::write(&self.config_path, content)
@@ -277,53 +991,187 @@ impl ConfigManager {
     pub fn set_value(&mut self, key_path: &str, value: &str) -> Result<()> {
         debug!("Setting config value: {} = {}", key_path, value);
         
+        Self::apply_value(&mut self.config, key_path, value)?;
+        
+        // An explicit update replaces any environment override and is persisted on save
+        self.env_overrides.retain(|o| o.key_path != key_path);
+        
+        info!("Configuration updated: {} = {}", key_path, value);
+        Ok(())
+    }
+
+    /// Apply a value to a configuration by key path
+    fn apply_value(config: &mut Config, key_path: &str, value: &str) -> Result<()> {
         match key_path {
             "monitoring.poll_interval_ms" => {
-                self.config.monitoring.poll_interval_ms = value.parse()
+                config.monitoring.poll_interval_ms = value.parse()
                     .context("Invalid poll_interval_ms value")?;
             }
             "monitoring.auto_start" => {
-                self.config.monitoring.auto_start = value.parse()
+                config.monitoring.auto_start = value.parse()
                     .context("Invalid auto_start value")?;
             }
             "monitoring.track_input_events" => {
-                self.config.monitoring.track_input_events = value.parse()
+                config.monitoring.track_input_events = value.parse()
                     .context("Invalid track_input_events value")?;
             }
             "monitoring.detect_suspicious_activity" => {
-                self.config.monitoring.detect_suspicious_activity = value.parse()
+                config.monitoring.detect_suspicious_activity = value.parse()
                     .context("Invalid detect_suspicious_activity value")?;
             }
+            "monitoring.udev_enrichment" => {
+                config.monitoring.udev_enrichment = value.parse()
+                    .context("Invalid udev_enrichment value")?;
+            }
+            "monitoring.suspend_window_ms" => {
+                config.monitoring.suspend_window_ms = value.parse()
+                    .context("Invalid suspend_window_ms value")?;
+            }
+            "monitoring.device_identity" => {
+                config.monitoring.device_identity = DeviceIdentity::from_name(value)
+                    .context("Invalid device_identity. Must be: port_based, serial_based, or vid_pid_serial")?;
+            }
+            "monitoring.raw_descriptors" => {
+                config.monitoring.raw_descriptors = RawDescriptors::from_name(value)
+                    .context("Invalid raw_descriptors. Must be: off, device, or all")?;
+            }
+            "monitoring.descriptor_strings" => {
+                config.monitoring.descriptor_strings = value.parse()
+                    .context("Invalid descriptor_strings value")?;
+            }
+            "monitoring.usb_debug" => {
+                let level: u8 = value.parse()
+                    .context("Invalid usb_debug value")?;
+                if level > MAX_USB_DEBUG {
+                    anyhow::bail!("usb_debug must be between 0 and {}", MAX_USB_DEBUG);
+                }
+                config.monitoring.usb_debug = level;
+            }
+            "monitoring.enumeration_retries" => {
+                config.monitoring.enumeration_retries = value.parse()
+                    .context("Invalid enumeration_retries value")?;
+            }
+            "monitoring.enumeration_timeout_ms" => {
+                config.monitoring.enumeration_timeout_ms = value.parse()
+                    .context("Invalid enumeration_timeout_ms value")?;
+            }
+            "monitoring.max_devices" => {
+                config.monitoring.max_devices = value.parse()
+                    .context("Invalid max_devices value")?;
+            }
+            "monitoring.snapshot_interval_secs" => {
+                config.monitoring.snapshot_interval_secs = value.parse()
+                    .context("Invalid snapshot_interval_secs value")?;
+            }
+            "monitoring.snapshot_keep" => {
+                let keep: usize = value.parse()
+                    .context("Invalid snapshot_keep value")?;
+                if keep == 0 {
+                    anyhow::bail!("snapshot_keep must be at least 1");
+                }
+                config.monitoring.snapshot_keep = keep;
+            }
             "output.default_format" => {
-                if !["json", "table", "csv"].contains(&value) {
-                    anyhow::bail!("Invalid output format. Must be: json, table, or csv");
+                if !OUTPUT_FORMATS.contains(&value) {
+                    anyhow::bail!("Invalid output format. Must be one of: {}", OUTPUT_FORMATS.join(", "));
                 }
-                self.config.output.default_format = value.to_string();
+                config.output.default_format = value.to_string();
             }
             "output.color_output" => {
-                self.config.output.color_output = value.parse()
+                config.output.color_output = value.parse()
                     .context("Invalid color_output value")?;
             }
             "output.include_metadata" => {
-                self.config.output.include_metadata = value.parse()
+                config.output.include_metadata = value.parse()
                     .context("Invalid include_metadata value")?;
             }
+            "output.rotate" => {
+                if !["none", "daily"].contains(&value) {
+                    anyhow::bail!("Invalid output rotation. Must be: none or daily");
+                }
+                config.output.rotate = value.to_string();
+            }
+            "output.latest_link" => {
+                config.output.latest_link = value.parse()
+                    .context("Invalid latest_link value")?;
+            }
+            "output.include_tags" => {
+                config.output.include_tags = value.parse()
+                    .context("Invalid include_tags value")?;
+            }
             "logging.level" => {
                 if !["error", "warn", "info", "debug", "trace"].contains(&value) {
                     anyhow::bail!("Invalid log level. Must be: error, warn, info, debug, or trace");
                 }
-                self.config.logging.level = value.to_string();
+                config.logging.level = value.to_string();
+            }
+            "logging.format" => {
+                if !["text", "json"].contains(&value) {
+                    anyhow::bail!("Invalid log format. Must be: text or json");
+                }
+                config.logging.format = value.to_string();
             }
             "logging.file_logging" => {
-                self.config.logging.file_logging = value.parse()
+                config.logging.file_logging = value.parse()
                     .context("Invalid file_logging value")?;
             }
+            "device_rules.enforce" => {
+                config.device_rules.enforce = value.parse()
+                    .context("Invalid enforce value")?;
+            }
+            "device_rules.block_message" => {
+                // An empty value goes back to the default message
+                config.device_rules.block_message = Some(value.trim())
+                    .filter(|message| !message.is_empty())
+                    .map(str::to_string);
+            }
+            "filters.ignored_vendors" => {
+                config.filters.ignored_vendors = parse_id_list(value)?;
+            }
+            "filters.ignored_products" => {
+                config.filters.ignored_products = parse_id_list(value)?;
+            }
+            "filters.name_patterns" => {
+                config.filters.name_patterns = value.split(',')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            "filters.name_patterns_mode" => {
+                config.filters.name_patterns_mode = NamePatternsMode::from_name(value)
+                    .context("Invalid name_patterns_mode. Must be: ignore or allow")?;
+            }
+            "mqtt.broker" => {
+                // An empty value turns publishing off
+                let broker = value.trim();
+                if !broker.is_empty() {
+                    parse_mqtt_broker(broker)?;
+                }
+                config.mqtt.broker = Some(broker)
+                    .filter(|broker| !broker.is_empty())
+                    .map(str::to_string);
+            }
+            "mqtt.topic_prefix" => {
+                let prefix = value.trim().trim_matches('/');
+                if prefix.is_empty() || prefix.contains(['#', '+']) {
+                    anyhow::bail!("Invalid MQTT topic prefix. Must be non-empty without '#' or '+'");
+                }
+                config.mqtt.topic_prefix = prefix.to_string();
+            }
+            "gui.device_colors" => {
+                config.gui.device_colors = DeviceColoring::from_name(value)
+                    .context("Invalid device_colors. Must be: vendor or class")?;
+            }
+            "analytics.merge_serialless_by_vidpid" => {
+                config.analytics.merge_serialless_by_vidpid = value.parse()
+                    .context("Invalid merge_serialless_by_vidpid value")?;
+            }
             _ => {
                 anyhow::bail!("Unknown configuration key: {}", key_path);
             }
         }
         
-        info!("Configuration updated: {} = {}", key_path, value);
         Ok(())
     }
 
@@ -334,11 +1182,36 @@ impl ConfigManager {
             "monitoring.auto_start" => self.config.monitoring.auto_start.to_string(),
             "monitoring.track_input_events" => self.config.monitoring.track_input_events.to_string(),
             "monitoring.detect_suspicious_activity" => self.config.monitoring.detect_suspicious_activity.to_string(),
+            "monitoring.udev_enrichment" => self.config.monitoring.udev_enrichment.to_string(),
+            "monitoring.suspend_window_ms" => self.config.monitoring.suspend_window_ms.to_string(),
+            "monitoring.device_identity" => self.config.monitoring.device_identity.name().to_string(),
+            "monitoring.descriptor_strings" => self.config.monitoring.descriptor_strings.to_string(),
+            "monitoring.usb_debug" => self.config.monitoring.usb_debug.to_string(),
+            "monitoring.enumeration_retries" => self.config.monitoring.enumeration_retries.to_string(),
+            "monitoring.enumeration_timeout_ms" => self.config.monitoring.enumeration_timeout_ms.to_string(),
+            "monitoring.raw_descriptors" => self.config.monitoring.raw_descriptors.name().to_string(),
+            "monitoring.max_devices" => self.config.monitoring.max_devices.to_string(),
+            "monitoring.snapshot_interval_secs" => self.config.monitoring.snapshot_interval_secs.to_string(),
+            "monitoring.snapshot_keep" => self.config.monitoring.snapshot_keep.to_string(),
             "output.default_format" => self.config.output.default_format.clone(),
             "output.color_output" => self.config.output.color_output.to_string(),
             "output.include_metadata" => self.config.output.include_metadata.to_string(),
+            "output.rotate" => self.config.output.rotate.clone(),
+            "output.latest_link" => self.config.output.latest_link.to_string(),
+            "output.include_tags" => self.config.output.include_tags.to_string(),
             "logging.level" => self.config.logging.level.clone(),
+            "logging.format" => self.config.logging.format.clone(),
             "logging.file_logging" => self.config.logging.file_logging.to_string(),
+            "device_rules.enforce" => self.config.device_rules.enforce.to_string(),
+            "device_rules.block_message" => self.config.device_rules.block_message().to_string(),
+            "mqtt.broker" => self.config.mqtt.broker.clone().unwrap_or_default(),
+            "filters.ignored_vendors" => format_id_list(&self.config.filters.ignored_vendors),
+            "filters.ignored_products" => format_id_list(&self.config.filters.ignored_products),
+            "filters.name_patterns" => self.config.filters.name_patterns.join(","),
+            "filters.name_patterns_mode" => self.config.filters.name_patterns_mode.name().to_string(),
+            "mqtt.topic_prefix" => self.config.mqtt.topic_prefix.clone(),
+            "gui.device_colors" => self.config.gui.device_colors.name().to_string(),
+            "analytics.merge_serialless_by_vidpid" => self.config.analytics.merge_serialless_by_vidpid.to_string(),
             _ => anyhow::bail!("Unknown configuration key: {}", key_path),
         };
         
@@ -352,15 +1225,58 @@ impl ConfigManager {
     }
 
     /// Validate current configuration
-    pub fn validate(&self) -> Result<()> {
+    ///
+    /// Rule combinations from `rule_warnings` are logged as warnings, or rejected when `strict`.
+    pub fn validate(&self, strict: bool) -> Result<()> {
         // Validate monitoring settings
         if self.config.monitoring.poll_interval_ms < 100 {
             anyhow::bail!("Poll interval must be at least 100ms");
         }
 
+        if self.config.monitoring.snapshot_keep == 0 {
+            anyhow::bail!("snapshot_keep must be at least 1");
+        }
+
+        if self.config.monitoring.usb_debug > MAX_USB_DEBUG {
+            anyhow::bail!("usb_debug must be between 0 and {}", MAX_USB_DEBUG);
+        }
+
         // Validate output format
-        if !["json", "table", "csv"].contains(&self.config.output.default_format.as_str()) {
-            anyhow::bail!("Invalid default output format");
+        if !OUTPUT_FORMATS.contains(&self.config.output.default_format.as_str()) {
+            anyhow::bail!("Invalid default output format '{}'. Must be one of: {}",
+                          self.config.output.default_format, OUTPUT_FORMATS.join(", "));
+        }
+
+        if self.config.output.max_log_entries == 0 {
+            anyhow::bail!("max_log_entries must be at least 1");
+        }
+
+        if !["none", "daily"].contains(&self.config.output.rotate.as_str()) {
+            anyhow::bail!("Invalid output rotation");
+        }
+
+        // Validate rule expressions
+        let rules = &self.config.device_rules;
+        for rule in rules.whitelisted_devices.iter().chain(&rules.blacklisted_devices) {
+            if let Some(ref expression) = rule.expression {
+                RuleExpr::parse(expression)
+                    .with_context(|| format!("Invalid expression in device rule {}", rule.id))?;
+            }
+        }
+
+        for warning in self.rule_warnings() {
+            if strict {
+                anyhow::bail!("{} (rejected by --strict)", warning);
+            }
+            warn!("{}", warning);
+        }
+
+        if let Some(ref broker) = self.config.mqtt.broker {
+            parse_mqtt_broker(broker).context("Invalid mqtt.broker")?;
+        }
+
+        if self.config.mqtt.topic_prefix.is_empty() {
+            anyhow::bail!("mqtt.topic_prefix must not be empty");
         }
 
         // Validate log level
@@ -368,6 +1284,10 @@ impl ConfigManager {
             anyhow::bail!("Invalid log level");
         }
 
+        if !["text", "json"].contains(&self.config.logging.format.as_str()) {
+            anyhow::bail!("Invalid log format");
+        }
+
         // Validate log file size
         if self.config.logging.max_log_file_size_mb > 100 {
             warn!("Large log file size configured: {}MB", self.config.logging.max_log_file_size_mb);
@@ -377,6 +1297,31 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Rule setups that are valid but likely to lock the user out or not do what they expect
+    pub fn rule_warnings(&self) -> Vec<String> {
+        let rules = &self.config.device_rules;
+        let mut warnings = Vec::new();
+
+        if rules.whitelist_enabled && !rules.whitelisted_devices.iter().any(|rule| rule.enabled) {
+            warnings.push(
+                "The whitelist is enabled but has no enabled rules, so every device is blocked, including keyboards and mice"
+                    .to_string(),
+            );
+        }
+
+        // A device on both lists is blocked, which makes the whitelist entry pointless
+        for blacklisted in &rules.blacklisted_devices {
+            if let Some(whitelisted) = rules.whitelisted_devices.iter().find(|rule| rule.same_criteria(blacklisted)) {
+                warnings.push(format!(
+                    "Blacklist rule {} and whitelist rule {} match the same devices; the blacklist wins, so they are blocked",
+                    blacklisted.id, whitelisted.id
+                ));
+            }
+        }
+
+        warnings
+    }
+
     /// Get the configuration file path
     pub fn get_config_path(&self) -> &PathBuf {
         &self.config_path
@@ -438,28 +1383,68 @@ impl ConfigManager {
         Ok(())
     }
     
-    /// Check if a device should be blocked based on current rules
-    pub fn should_block_device(&self, device: &crate::usb_monitor::UsbDeviceInfo) -> (bool, Option<String>) {
+    /// Check if a device should be blocked based on current rules.
+    ///
+    /// Also returns the rule that decided: the blacklist rule that blocked the device, or the
+    /// whitelist rule that admitted it. A device blocked for not being whitelisted has none.
+    pub fn should_block_device(&self, device: &crate::usb_monitor::UsbDeviceInfo) -> (bool, Option<String>, Option<MatchedRule<'_>>) {
+        if self.blocking_suspended {
+            return (false, None, None);
+        }
+
+        let rules = &self.config.device_rules;
+        
         // If whitelist is enabled, only allow whitelisted devices
-        if self.config.device_rules.whitelist_enabled {
-            let is_whitelisted = self.config.device_rules.whitelisted_devices.iter()
-                .any(|rule| rule.enabled && rule.matches_device(device));
-            
-            if !is_whitelisted {
-                return (true, Some("Device not in whitelist".to_string()));
+        let whitelisted = if rules.whitelist_enabled {
+            match Self::first_matching_rule("whitelist", &rules.whitelisted_devices, device) {
+                Some(matched) => Some(matched),
+                None => return (true, Some("Device not in whitelist".to_string()), None),
             }
-        }
+        } else {
+            None
+        };
         
         // Check blacklist
-        if self.config.device_rules.blacklist_enabled {
-            for rule in &self.config.device_rules.blacklisted_devices {
-                if rule.enabled && rule.matches_device(device) {
-                    return (true, Some(rule.reason.clone()));
-                }
+        if rules.blacklist_enabled {
+            if let Some(matched) = Self::first_matching_rule("blacklist", &rules.blacklisted_devices, device) {
+                return (true, Some(matched.rule.reason.clone()), Some(matched));
             }
         }
         
-        (false, None)
+        (false, None, whitelisted)
+    }
+    
+    /// Allow every device for the rest of this session, or resume enforcing the rules.
+    /// Never saved: a restart always enforces again.
+    pub fn set_blocking_suspended(&mut self, suspended: bool) {
+        if suspended != self.blocking_suspended {
+            if suspended {
+                warn!("Device blocking suspended for this session; all devices are allowed");
+            } else {
+                info!("Device blocking resumed");
+            }
+        }
+        self.blocking_suspended = suspended;
+    }
+
+    pub fn is_blocking_suspended(&self) -> bool {
+        self.blocking_suspended
+    }
+
+    fn first_matching_rule<'a>(
+        list: &'static str,
+        rules: &'a [DeviceRule],
+        device: &crate::usb_monitor::UsbDeviceInfo,
+    ) -> Option<MatchedRule<'a>> {
+        rules.iter()
+            .find(|rule| rule.enabled && rule.matches_device(device))
+            .map(|rule| MatchedRule { list, rule })
+    }
+    
+    /// Evaluate the device rules for a device without recording a security event
+    pub fn evaluate_device(&self, device: &crate::usb_monitor::UsbDeviceInfo) -> PolicyDecision {
+        let (blocked, reason, _) = self.should_block_device(device);
+        PolicyDecision { blocked, reason }
     }
     
     /// Enable or disable blacklist
@@ -483,4 +1468,293 @@ impl ConfigManager {
     pub fn get_whitelisted_devices(&self) -> &[DeviceRule] {
         &self.config.device_rules.whitelisted_devices
     }
-}
\ No newline at end of file
+
+    /// Tag the device with this fingerprint; an empty tag removes it
+    pub fn set_device_tag(&mut self, fingerprint: &str, tag: &str) {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            self.config.device_tags.remove(fingerprint);
+            info!("Tag removed from device {}", fingerprint);
+        } else {
+            self.config.device_tags.insert(fingerprint.to_string(), tag.to_string());
+            info!("Device {} tagged as \"{}\"", fingerprint, tag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_dir_env_override() {
+        let dir = std::env::temp_dir()
+            .join(format!("ironwatch-config-dir-{}", std::process::id()))
+            .join("nested");
+        std::env::set_var(CONFIG_DIR_ENV, &dir);
+
+        let manager = ConfigManager::new(None).unwrap();
+        std::env::remove_var(CONFIG_DIR_ENV);
+
+        assert_eq!(manager.get_config_path(), &dir.join("config.json"));
+        assert!(dir.is_dir());
+
+        manager.save().unwrap();
+        assert!(dir.join("config.json").is_file());
+
+        fs::remove_dir_all(dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_env_override_is_not_saved() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-env-override-{}.json", std::process::id()));
+        let var = ConfigManager::env_var_name("logging.level");
+        assert_eq!(var, "IRONWATCH_LOGGING_LEVEL");
+
+        std::env::set_var(&var, "debug");
+        let manager = ConfigManager::new(Some(path.clone())).unwrap();
+        std::env::remove_var(&var);
+
+        assert_eq!(manager.get_value("logging.level").unwrap(), "debug");
+
+        manager.save().unwrap();
+        let saved: Config = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(saved.logging.level, "info");
+    }
+
+    #[test]
+    fn test_config_from_stdin_is_never_saved() {
+        let manager = ConfigManager {
+            config: Config::default(),
+            config_path: PathBuf::from(STDIN_CONFIG_PATH),
+            env_overrides: Vec::new(),
+            blocking_suspended: false,
+        };
+        assert!(manager.is_from_stdin());
+        manager.save().unwrap();
+        assert!(!Path::new(STDIN_CONFIG_PATH).exists());
+    }
+
+    fn device_of_class(device_class: u8) -> crate::usb_monitor::UsbDeviceInfo {
+        crate::usb_monitor::UsbDeviceInfo {
+            bus_number: 1,
+            device_address: 2,
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            device_version: 0x0100,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            device_class,
+            device_subclass: 0,
+            device_protocol: 0,
+            max_packet_size: 64,
+            num_configurations: 1,
+            timestamp: chrono::Utc::now(),
+            connection_status: crate::usb_monitor::ConnectionStatus::Connected,
+            fingerprint: String::new(),
+            raw_descriptor: None,
+        }
+    }
+
+    #[test]
+    fn test_single_class_rule_matches_only_that_class() {
+        let rule = DeviceRule {
+            device_class: Some(0x08),
+            ..DeviceRule::new()
+        };
+
+        assert!(rule.matches_device(&device_of_class(0x08)));
+        assert!(!rule.matches_device(&device_of_class(0x0b)));
+    }
+
+    #[test]
+    fn test_multi_class_rule_matches_any_listed_class() {
+        let rule = DeviceRule {
+            device_classes: Some(DeviceRule::parse_device_classes("0x08, 0x0b").unwrap()),
+            ..DeviceRule::new()
+        };
+
+        assert!(rule.matches_device(&device_of_class(0x08)));
+        assert!(rule.matches_device(&device_of_class(0x0b)));
+        assert!(!rule.matches_device(&device_of_class(0x03)));
+
+        assert_eq!(DeviceRule::parse_device_classes("8-10,0x0b").unwrap(), vec![8, 9, 10, 11]);
+        assert!(DeviceRule::parse_device_classes("0x0b-0x08").is_err());
+        assert!(DeviceRule::parse_device_classes("storage").is_err());
+    }
+
+    #[test]
+    fn test_serial_pattern_rule() {
+        let mut device = device_of_class(0x08);
+        let rule = DeviceRule {
+            serial_pattern: Some("^[0-9A-F]{12}$".to_string()),
+            ..DeviceRule::new()
+        };
+
+        device.serial_number = Some("0123456789AB".to_string());
+        assert!(rule.matches_device(&device));
+        device.serial_number = Some("0123456789ABCD".to_string());
+        assert!(!rule.matches_device(&device));
+        device.serial_number = None;
+        assert!(!rule.matches_device(&device));
+
+        // An invalid pattern disables the rule instead of failing
+        let invalid = DeviceRule {
+            serial_pattern: Some("([0-9".to_string()),
+            ..DeviceRule::new()
+        };
+        device.serial_number = Some("([0-9".to_string());
+        assert!(!invalid.matches_device(&device));
+    }
+
+    #[test]
+    fn test_expression_rule_blocks_alongside_structured_rules() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-expression-rule-{}-missing.json", std::process::id()));
+        let mut manager = ConfigManager::new(Some(path)).unwrap();
+        manager.add_blacklisted_device(DeviceRule {
+            expression: Some("class == 0x08 && manufacturer != \"Kingston\"".to_string()),
+            reason: "Unapproved storage".to_string(),
+            ..DeviceRule::new()
+        }).unwrap();
+
+        let mut storage = device_of_class(0x08);
+        assert!(manager.should_block_device(&storage).0);
+        storage.manufacturer = Some("Kingston".to_string());
+        assert!(!manager.should_block_device(&storage).0);
+        assert!(!manager.should_block_device(&device_of_class(0x03)).0);
+        manager.validate(false).unwrap();
+
+        manager.config.device_rules.blacklisted_devices[0].expression = Some("class == ".to_string());
+        assert!(manager.validate(false).is_err());
+        assert!(!manager.should_block_device(&device_of_class(0x08)).0);
+    }
+
+    #[test]
+    fn test_suspended_blocking_allows_everything_until_resumed() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-suspend-{}-missing.json", std::process::id()));
+        let mut manager = ConfigManager::new(Some(path)).unwrap();
+        manager.config.device_rules.whitelist_enabled = true;
+        let keyboard = device_of_class(0x03);
+        assert!(manager.should_block_device(&keyboard).0);
+
+        manager.set_blocking_suspended(true);
+        assert!(!manager.should_block_device(&keyboard).0);
+
+        manager.set_blocking_suspended(false);
+        assert!(manager.should_block_device(&keyboard).0);
+    }
+
+    #[test]
+    fn test_rules_without_ids_get_the_same_id_on_every_load() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-rule-ids-{}.json", std::process::id()));
+
+        let mut config = Config::default();
+        config.device_rules.blacklisted_devices.push(DeviceRule {
+            id: String::new(),
+            vendor_id: Some(0x1234),
+            reason: "Legacy rule".to_string(),
+            ..DeviceRule::new()
+        });
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+        let first = ConfigManager::new(Some(path.clone())).unwrap();
+        let second = ConfigManager::new(Some(path.clone())).unwrap();
+        fs::remove_file(&path).ok();
+
+        let id = &first.get_blacklisted_devices()[0].id;
+        assert!(!id.is_empty());
+        assert_eq!(id, &second.get_blacklisted_devices()[0].id);
+        assert_ne!(DeviceRule::new().id, DeviceRule::new().id);
+    }
+
+    #[test]
+    fn test_empty_whitelist_and_rules_on_both_lists_warn() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-rule-warnings-{}-missing.json", std::process::id()));
+        let mut manager = ConfigManager::new(Some(path)).unwrap();
+        assert!(manager.rule_warnings().is_empty());
+
+        manager.config.device_rules.whitelist_enabled = true;
+        assert_eq!(manager.rule_warnings().len(), 1);
+        assert!(manager.validate(false).is_ok());
+        assert!(manager.validate(true).is_err());
+
+        let rule = DeviceRule {
+            vendor_id: Some(0x0951),
+            reason: "Approved storage".to_string(),
+            ..DeviceRule::new()
+        };
+        manager.config.device_rules.whitelisted_devices.push(rule.clone());
+        assert!(manager.rule_warnings().is_empty());
+
+        manager.config.device_rules.blacklisted_devices.push(DeviceRule {
+            id: DeviceRule::new().id,
+            reason: "Unapproved storage".to_string(),
+            ..rule
+        });
+        let warnings = manager.rule_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("the blacklist wins"));
+    }
+
+    #[test]
+    fn test_every_output_format_is_a_valid_default_format() {
+        for format in OUTPUT_FORMATS {
+            let mut manager = ConfigManager::from_config(Config::default());
+            manager.set_value("output.default_format", format).unwrap();
+            manager.validate(false).unwrap();
+        }
+        assert!(ConfigManager::apply_value(&mut Config::default(), "output.default_format", "yaml").is_err());
+    }
+
+    #[test]
+    fn test_ignored_vendor_list_is_settable_in_hex() {
+        let mut config = Config::default();
+        ConfigManager::apply_value(&mut config, "filters.ignored_vendors", "0x1d6b, 8087").unwrap();
+        assert_eq!(config.filters.ignored_vendors, vec![0x1d6b, 0x8087]);
+        assert_eq!(format_id_list(&config.filters.ignored_vendors), "0x1d6b,0x8087");
+
+        assert!(ConfigManager::apply_value(&mut config, "filters.ignored_vendors", "usb").is_err());
+        ConfigManager::apply_value(&mut config, "filters.ignored_vendors", "").unwrap();
+        assert!(config.filters.ignored_vendors.is_empty());
+    }
+
+    #[test]
+    fn test_device_tag_prefers_the_serial_fingerprint() {
+        let mut manager = ConfigManager::from_config(Config::default());
+        manager.set_device_tag(&crate::usb_monitor::device_fingerprint(0x1234, 0x5678, None), "Lab scope");
+        manager.set_device_tag(&crate::usb_monitor::device_fingerprint(0x1234, 0x5678, Some("A1")), " CEO's YubiKey ");
+
+        let mut device = device_of_class(0x03);
+        device.serial_number = Some("A1".to_string());
+        device.update_fingerprint();
+        assert_eq!(device_tag(&manager.get_config().device_tags, &device), Some("CEO's YubiKey"));
+
+        // Other serials and serial-less devices with the same VID:PID get the VID:PID tag
+        device.serial_number = Some("B2".to_string());
+        device.update_fingerprint();
+        assert_eq!(device_tag(&manager.get_config().device_tags, &device), Some("Lab scope"));
+
+        manager.set_device_tag(&crate::usb_monitor::device_fingerprint(0x1234, 0x5678, None), "");
+        assert_eq!(device_tag(&manager.get_config().device_tags, &device), None);
+        assert_eq!(manager.get_config().device_tags.len(), 1);
+    }
+
+    #[test]
+    fn test_mqtt_broker_urls() {
+        assert_eq!(parse_mqtt_broker("mqtt://broker.local:1884").unwrap(), ("broker.local".to_string(), 1884));
+        assert_eq!(parse_mqtt_broker("tcp://10.0.0.5").unwrap(), ("10.0.0.5".to_string(), 1883));
+        assert_eq!(parse_mqtt_broker("broker.local").unwrap(), ("broker.local".to_string(), 1883));
+
+        for url in ["", "mqtt://", "http://broker.local", "mqtt://broker.local:port", "mqtt://broker.local:70000"] {
+            assert!(parse_mqtt_broker(url).is_err(), "'{}' should be rejected", url);
+        }
+    }
+}
@@ -1,5 +1,4 @@
 use std::fmt;
-use anyhow::Result as AnyhowResult;
 
 /// Custom error types for IronWatch application
 #[derive(Debug)]
@@ -60,6 +59,8 @@ pub enum GuiError {
     ResourceLoadFailed(String),
     /// Threading/async communication error
     CommunicationError(String),
+    /// Failed to export data to a file
+    ExportFailed(String),
 }
 
 #[derive(Debug)]
@@ -118,6 +119,7 @@ impl fmt::Display for GuiError {
             GuiError::WindowCreationFailed(msg) => write!(f, "Window creation failed: {}", msg),
             GuiError::ResourceLoadFailed(msg) => write!(f, "Resource loading failed: {}", msg),
             GuiError::CommunicationError(msg) => write!(f, "GUI communication error: {}", msg),
+            GuiError::ExportFailed(msg) => write!(f, "Export failed: {}", msg),
         }
     }
 }
@@ -215,6 +217,10 @@ impl GuiError {
     pub fn communication_error(msg: impl Into<String>) -> IronWatchError {
         IronWatchError::GuiError(GuiError::CommunicationError(msg.into()))
     }
+    
+    pub fn export_failed(msg: impl Into<String>) -> IronWatchError {
+        IronWatchError::GuiError(GuiError::ExportFailed(msg.into()))
+    }
 }
 
 /// Check if the current user has sufficient permissions for USB access
@@ -256,6 +262,15 @@ pub fn get_user_friendly_message(error: &IronWatchError) -> (String, Option<Stri
             "Configuration file not found".to_string(),
             Some("A default configuration will be created automatically.".to_string())
         ),
+        IronWatchError::GuiError(GuiError::ExportFailed(msg)) => (msg.clone(), None),
+        IronWatchError::IoError(e) if e.kind() == std::io::ErrorKind::PermissionDenied => (
+            format!("Permission denied: {}", e),
+            Some("Run IronWatch from a folder you can write to, or check the folder's permissions.".to_string())
+        ),
+        IronWatchError::IoError(e) => (
+            format!("File access failed: {}", e),
+            Some("Check that the disk is not full and the folder still exists.".to_string())
+        ),
         _ => (error.to_string(), None),
     }
 }
\ No newline at end of file
@@ -1,10 +1,211 @@
-use crate::usb_monitor::{UsbDeviceInfo, DeviceStatistics, DeviceAnalytics, SecurityEvent, SecurityEventType, SecurityAction};
+use crate::usb_monitor::{UsbDeviceInfo, UsbDeviceChange, ConnectionStatus, ConnectionInterval, DeviceStatistics, DeviceAnalytics, SecurityEvent, SecurityEventType, SecurityAction};
 use crate::communication::{CommunicationHub, MonitorEvent, MonitoringStatus};
-use crate::error::{Result, get_user_friendly_message};
-use crate::system_tray::{SystemTray, TrayMessage};
+use crate::config::{device_tag, DeviceColoring};
+use crate::error::{GuiError, Result, get_user_friendly_message};
+use crate::system_tray::{NotificationUrgency, SystemTray, TrayMessage};
+use crate::rule_editor::{RuleEditor, RuleEditorAction, RuleList};
 
 use eframe::egui::{self, *};
-use std::time::Instant;
+use egui_extras::{Column, TableBuilder};
+use egui_plot::{Bar, BarChart, Line, Plot, PlotPoints};
+use palette::{Hsv, IntoColor, Srgb};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Keyboard shortcuts shown in the Settings tab
+const KEYBOARD_SHORTCUTS: &[(&str, &str)] = &[
+    ("Ctrl+1 … Ctrl+6", "Switch tabs"),
+    ("Ctrl+R", "Refresh devices"),
+    ("Ctrl+M", "Toggle monitoring"),
+    ("/", "Search devices"),
+];
+
+/// Default window for batching device-change notifications
+const DEFAULT_NOTIFICATION_WINDOW: Duration = Duration::from_millis(1000);
+
+/// Minimum gap between two batched notifications
+const MIN_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Seconds a device row takes to fade in or out of the device table
+const ANIMATION_DURATION: f32 = 0.8;
+
+/// Seconds a newly connected device stays highlighted
+const HIGHLIGHT_DURATION: f32 = 2.0;
+
+/// Samples kept for the activity plots
+const MAX_ACTIVITY_POINTS: usize = 100;
+
+/// Most recent activity samples drawn in the top panel's device count sparkline
+const SPARKLINE_POINTS: usize = 60;
+
+/// Frames averaged by the FPS counter
+const FPS_SAMPLES: usize = 60;
+
+/// Fade state of a row in the device table
+struct DeviceAnimation {
+    started: Instant,
+    /// The device that left, kept so its row can fade out; `None` while fading in
+    departed: Option<UsbDeviceInfo>,
+}
+
+impl DeviceAnimation {
+    fn progress(&self, now: Instant) -> f32 {
+        (now.duration_since(self.started).as_secs_f32() / ANIMATION_DURATION).clamp(0.0, 1.0)
+    }
+
+    /// Row opacity: ease-out when appearing, ease-in when leaving
+    fn alpha(&self, now: Instant) -> f32 {
+        let progress = self.progress(now);
+        match self.departed {
+            Some(_) => 1.0 - progress.powi(2),
+            None => 1.0 - (1.0 - progress).powi(2),
+        }
+    }
+
+    /// Strength of the highlight behind a newly connected device, from 1 down to 0
+    fn highlight(&self, now: Instant) -> f32 {
+        match self.departed {
+            Some(_) => 0.0,
+            None => (1.0 - now.duration_since(self.started).as_secs_f32() / HIGHLIGHT_DURATION).max(0.0),
+        }
+    }
+
+    fn finished(&self, now: Instant) -> bool {
+        let duration = if self.departed.is_some() { ANIMATION_DURATION } else { HIGHLIGHT_DURATION.max(ANIMATION_DURATION) };
+        now.duration_since(self.started).as_secs_f32() >= duration
+    }
+}
+
+/// One point of the activity plots, taken whenever the device list changes
+struct ActivitySample {
+    /// Seconds since the window opened
+    seconds: f64,
+    device_count: f64,
+    connection_events: f64,
+}
+
+/// A desktop notification ready to be shown
+struct TrayNotice {
+    title: String,
+    message: String,
+    urgency: NotificationUrgency,
+}
+
+impl TrayNotice {
+    fn new(title: &str, message: String, urgency: NotificationUrgency) -> Self {
+        Self { title: title.to_string(), message, urgency }
+    }
+}
+
+/// Coalesces bursts of device changes into a single notification.
+///
+/// Blocked devices bypass the batching entirely and are always shown immediately.
+struct NotificationThrottle {
+    window: Duration,
+    pending: Vec<UsbDeviceChange>,
+    window_start: Option<Instant>,
+    last_shown: Option<Instant>,
+}
+
+impl NotificationThrottle {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+            window_start: None,
+            last_shown: None,
+        }
+    }
+    
+    /// Queue a change; returns a notification to show right away for blocked devices
+    fn push(&mut self, change: UsbDeviceChange, now: Instant) -> Option<TrayNotice> {
+        if let UsbDeviceChange::Blocked(device, reason) = &change {
+            return Some(TrayNotice::new(
+                "Device BLOCKED",
+                format!("{} was blocked: {}", Self::describe(device), reason),
+                NotificationUrgency::Critical,
+            ));
+        }
+        
+        self.window_start.get_or_insert(now);
+        self.pending.push(change);
+        None
+    }
+    
+    /// Flush the batch once its window has elapsed and the rate limit allows
+    fn poll(&mut self, now: Instant) -> Option<TrayNotice> {
+        let window_start = self.window_start?;
+        
+        if now.duration_since(window_start) < self.window {
+            return None;
+        }
+        
+        if let Some(last_shown) = self.last_shown {
+            if now.duration_since(last_shown) < MIN_NOTIFICATION_INTERVAL {
+                return None;
+            }
+        }
+        
+        let changes = std::mem::take(&mut self.pending);
+        self.window_start = None;
+        self.last_shown = Some(now);
+        
+        Some(Self::summarize(&changes))
+    }
+    
+    /// Flush the pending batch regardless of the window, e.g. on exit
+    fn take_pending(&mut self) -> Option<TrayNotice> {
+        self.window_start.take()?;
+        let changes = std::mem::take(&mut self.pending);
+        Some(Self::summarize(&changes))
+    }
+    
+    /// Time until the pending batch may be flushed, if there is one
+    fn next_flush_in(&self, now: Instant) -> Option<Duration> {
+        let window_start = self.window_start?;
+        let window_end = window_start + self.window;
+        let rate_end = self.last_shown.map_or(window_end, |last| last + MIN_NOTIFICATION_INTERVAL);
+        
+        Some(window_end.max(rate_end).saturating_duration_since(now))
+    }
+    
+    /// "Name (vid:pid)" for notification bodies
+    fn describe(device: &UsbDeviceInfo) -> String {
+        format!("{} ({:04x}:{:04x})", device.display_name(), device.vendor_id, device.product_id)
+    }
+    
+    fn summarize(changes: &[UsbDeviceChange]) -> TrayNotice {
+        if let [change] = changes {
+            let title = match change.get_change_type() {
+                "DISCONNECTED" => "Device disconnected",
+                "RECONNECTED" => "Device reconnected",
+                "SUSPENDED" => "Device suspended",
+                _ => "Device connected",
+            };
+            return TrayNotice::new(title, Self::describe(change.get_device_info()), NotificationUrgency::Normal);
+        }
+        
+        let count = |verb: &str| {
+            let n = changes.iter().filter(|c| match c {
+                UsbDeviceChange::Connected(_) => verb == "connected",
+                UsbDeviceChange::Disconnected(_) => verb == "disconnected",
+                UsbDeviceChange::Reconnected(_) => verb == "reconnected",
+                UsbDeviceChange::Suspended(_) => verb == "suspended",
+                UsbDeviceChange::Blocked(..) => false,
+                _ => false,
+            }).count();
+            (n > 0).then(|| format!("{} {} {}", n, if n == 1 { "device" } else { "devices" }, verb))
+        };
+        
+        let parts: Vec<String> = ["connected", "disconnected", "reconnected", "suspended"]
+            .into_iter()
+            .filter_map(count)
+            .collect();
+        
+        TrayNotice::new("USB Devices Changed", parts.join(", "), NotificationUrgency::Normal)
+    }
+}
 
 pub struct IronWatchGui {
     // Core state
@@ -15,12 +216,25 @@ pub struct IronWatchGui {
     // System tray
     system_tray: Option<SystemTray>,
     tray_sender: Option<std::sync::mpsc::Sender<TrayMessage>>,
+    window_visible: bool,
+    notifications: NotificationThrottle,
     
     // UI state
     current_tab: Tab,
     
     // Animation state
     last_refresh: Instant,
+    device_animations: HashMap<String, DeviceAnimation>,
+    
+    // Activity tracking
+    session_start: Instant,
+    activity: VecDeque<ActivitySample>,
+    connection_events: u64,
+    
+    // Performance
+    last_frame: Instant,
+    frame_times: VecDeque<f32>,
+    fps: f32,
     
     // Filtering
     search_filter: String,
@@ -29,20 +243,99 @@ pub struct IronWatchGui {
     show_settings: bool,
     dark_mode: bool,
     show_animations: bool,
+    compact_view: bool,
+    device_colors: DeviceColoring,
+    /// Colors already computed, keyed by coloring mode and vendor ID or class code
+    color_cache: RefCell<HashMap<(DeviceColoring, u16), Color32>>,
+    confirm_reset: bool,
+    /// Settings from before the last reset, restorable for the rest of the session
+    settings_before_reset: Option<SettingsSnapshot>,
     
     // Error handling
     last_error: Option<String>,
     error_message: Option<String>,
     show_permission_dialog: bool,
+    /// Device rule problems found at startup, shown until dismissed
+    config_warnings: Vec<String>,
+    export_outcome: Option<ExportOutcome>,
+    /// Why the monitoring service is running without USB access, shown as a banner
+    usb_unavailable: Option<String>,
+    /// `monitoring.auto_start`, until the service reports whether USB is available
+    pending_auto_start: bool,
+    
+    // Blocked device alert
+    block_message: String,
+    blocked_alert: Option<UsbDeviceInfo>,
+    
+    /// Friendly labels from `device_tags`, keyed by fingerprint
+    device_tags: HashMap<String, String>,
+    /// `output.include_tags`: also write the tags into exports
+    include_tags: bool,
     
     // Statistics
     device_analytics: Option<DeviceAnalytics>,
+    connection_timeline: Vec<ConnectionInterval>,
+    export_kind: ExportKind,
     selected_device_stats: Option<(String, DeviceStatistics)>,
     
     // Security
     security_events: Vec<SecurityEvent>,
     show_security_details: bool,
     selected_security_event: Option<usize>,
+    /// Open "Add Rule" form
+    rule_editor: Option<RuleEditor>,
+    confirm_unblock_all: bool,
+    /// Rule enforcement is suspended until restart ("Unblock All")
+    blocking_suspended: bool,
+    /// Format of the Devices tab's "Export Filtered"
+    #[cfg(feature = "cli")]
+    filtered_export_format: crate::cli::OutputFormat,
+    /// Format of the Statistics tab's timeline export
+    #[cfg(feature = "cli")]
+    timeline_export_format: crate::cli::OutputFormat,
+}
+
+/// The Settings tab's values, kept so a reset can be undone
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SettingsSnapshot {
+    dark_mode: bool,
+    show_animations: bool,
+    device_colors: DeviceColoring,
+    notification_window: Duration,
+}
+
+impl Default for SettingsSnapshot {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            show_animations: true,
+            device_colors: DeviceColoring::default(),
+            notification_window: DEFAULT_NOTIFICATION_WINDOW,
+        }
+    }
+}
+
+/// Result of the last export, shown in a dialog until dismissed
+#[derive(Debug, Clone)]
+enum ExportOutcome {
+    Written(std::path::PathBuf),
+    Failed { message: String, suggestion: Option<String> },
+}
+
+/// What the Statistics tab's "Export Data" button writes
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ExportKind {
+    History,
+    Timeline,
+}
+
+impl ExportKind {
+    fn label(&self) -> &'static str {
+        match self {
+            ExportKind::History => "Device history (JSON)",
+            ExportKind::Timeline => "Connection timeline",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -55,12 +348,6 @@ pub enum Tab {
     Settings,
 }
 
-impl Default for IronWatchGui {
-    fn default() -> Self {
-        panic!("Use IronWatchGui::new() instead of Default::default()")
-    }
-}
-
 impl IronWatchGui {
     pub fn new(cc: &eframe::CreationContext<'_>, communication_hub: CommunicationHub) -> Self {
         let mut style = (*cc.egui_ctx.style()).clone();
@@ -79,26 +366,65 @@ impl IronWatchGui {
             }
         };
         
+        let (config, config_warnings) = crate::config::ConfigManager::new(None)
+            .map(|manager| (manager.get_config().clone(), manager.rule_warnings()))
+            .unwrap_or_default();
+        for warning in &config_warnings {
+            log::warn!("{}", warning);
+        }
+        let block_message = config.device_rules.block_message().to_string();
+        
         let app = Self {
             devices: Vec::new(),
             communication_hub,
             monitoring_status: MonitoringStatus::Stopped,
             system_tray,
             tray_sender,
+            window_visible: true,
+            notifications: NotificationThrottle::new(DEFAULT_NOTIFICATION_WINDOW),
             current_tab: Tab::Dashboard,
             last_refresh: Instant::now(),
+            device_animations: HashMap::new(),
+            session_start: Instant::now(),
+            activity: VecDeque::with_capacity(MAX_ACTIVITY_POINTS),
+            connection_events: 0,
+            last_frame: Instant::now(),
+            frame_times: VecDeque::with_capacity(FPS_SAMPLES),
+            fps: 0.0,
             search_filter: String::new(),
             show_settings: false,
             dark_mode: true,
             show_animations: true,
+            compact_view: false,
+            device_colors: config.gui.device_colors,
+            color_cache: RefCell::new(HashMap::new()),
+            confirm_reset: false,
+            confirm_unblock_all: false,
+            blocking_suspended: false,
+            settings_before_reset: None,
             last_error: None,
             error_message: None,
             show_permission_dialog: false,
+            config_warnings,
+            export_outcome: None,
+            usb_unavailable: None,
+            pending_auto_start: config.monitoring.auto_start,
+            block_message,
+            blocked_alert: None,
+            device_tags: config.device_tags.clone(),
+            include_tags: config.output.include_tags,
             device_analytics: None,
+            connection_timeline: Vec::new(),
+            export_kind: ExportKind::History,
             selected_device_stats: None,
             security_events: Vec::new(),
             show_security_details: false,
             selected_security_event: None,
+            rule_editor: None,
+            #[cfg(feature = "cli")]
+            filtered_export_format: crate::cli::OutputFormat::Json,
+            #[cfg(feature = "cli")]
+            timeline_export_format: crate::cli::OutputFormat::Json,
         };
         
         let _ = app.communication_hub.refresh_devices();
@@ -108,29 +434,33 @@ impl IronWatchGui {
     fn process_monitoring_events(&mut self) {
         while let Some(event) = self.communication_hub.try_recv_event() {
             match event {
-                MonitorEvent::DevicesLoaded(devices) | MonitorEvent::DevicesUpdated(devices) => {
-                    self.devices = devices;
+                MonitorEvent::DevicesLoaded(devices) => {
+                    self.set_devices(devices, false);
+                }
+                MonitorEvent::DevicesUpdated(devices) => {
+                    self.set_devices(devices, true);
+                    // The first device list means USB initialized, unless it was reported unavailable first
+                    if std::mem::take(&mut self.pending_auto_start) {
+                        log::info!("Starting monitoring automatically (monitoring.auto_start)");
+                        let _ = self.communication_hub.start_monitoring();
+                    }
                 }
                 MonitorEvent::DeviceChanged(change) => {
                     log::info!("Device change: {:?}", change);
-                    // Show notification
-                    let title = "USB Device Change";
-                    let device_info = change.get_device_info();
-                    let product_name = device_info.product.as_deref().unwrap_or("Unknown Device");
-                    let message = format!("Device {} detected", product_name);
-                    self.show_tray_notification(title, &message);
+                    self.queue_notification(change);
                     // Refresh device list after change
                     let _ = self.communication_hub.refresh_devices();
                 }
                 MonitorEvent::DevicesChanged(changes) => {
                     log::info!("Multiple device changes: {} devices", changes.len());
-                    let title = "USB Devices Changed";
-                    let message = format!("{} devices changed", changes.len());
-                    self.show_tray_notification(title, &message);
+                    for change in changes {
+                        self.queue_notification(change);
+                    }
                     // Refresh device list after changes
                     let _ = self.communication_hub.refresh_devices();
                 }
                 MonitorEvent::MonitoringStarted => {
+                    self.usb_unavailable = None;
                     self.monitoring_status = MonitoringStatus::Running;
                     self.update_tray_icon();
                 }
@@ -147,12 +477,116 @@ impl IronWatchGui {
                 }
                 MonitorEvent::UsbUnavailable(error) => {
                     self.last_error = Some(format!("USB unavailable: {}", error));
+                    if self.pending_auto_start {
+                        log::warn!("USB is unavailable, not starting monitoring automatically");
+                        self.pending_auto_start = false;
+                    }
+                    self.usb_unavailable = Some(error);
+                }
+                MonitorEvent::AnalyticsUpdated(analytics) => {
+                    self.device_analytics = Some(analytics);
+                }
+                MonitorEvent::TimelineUpdated(timeline) => {
+                    self.connection_timeline = timeline;
+                }
+                MonitorEvent::BlockingSuspended(suspended) => {
+                    self.blocking_suspended = suspended;
                 }
             }
         }
     }
     
-    fn process_tray_messages(&mut self) {
+    fn device_key(device: &UsbDeviceInfo) -> String {
+        format!("{}:{}:{}:{}", device.vendor_id, device.product_id, device.bus_number, device.device_address)
+    }
+    
+    /// Replace the device list, fading rows in and out when `animate` is set
+    fn set_devices(&mut self, devices: Vec<UsbDeviceInfo>, animate: bool) {
+        if animate {
+            let now = Instant::now();
+            let previous: HashSet<String> = self.devices.iter().map(Self::device_key).collect();
+            let current: HashSet<String> = devices.iter().map(Self::device_key).collect();
+            
+            for key in current.difference(&previous) {
+                self.device_animations.insert(key.clone(), DeviceAnimation { started: now, departed: None });
+            }
+            
+            for device in self.devices.iter().filter(|device| !current.contains(&Self::device_key(device))) {
+                self.device_animations.insert(Self::device_key(device), DeviceAnimation {
+                    started: now,
+                    departed: Some(device.clone()),
+                });
+            }
+        }
+        
+        self.devices = devices;
+        
+        self.activity.push_back(ActivitySample {
+            seconds: self.session_start.elapsed().as_secs_f64(),
+            device_count: self.devices.len() as f64,
+            connection_events: self.connection_events as f64,
+        });
+        if self.activity.len() > MAX_ACTIVITY_POINTS {
+            self.activity.pop_front();
+        }
+    }
+    
+    /// Drop finished animations and update the FPS counter
+    fn update_animations(&mut self) {
+        let now = Instant::now();
+        self.device_animations.retain(|_, animation| !animation.finished(now));
+        
+        self.frame_times.push_back(now.duration_since(self.last_frame).as_secs_f32());
+        self.last_frame = now;
+        if self.frame_times.len() > FPS_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        let average = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
+        self.fps = if average > 0.0 { 1.0 / average } else { 0.0 };
+    }
+    
+    /// A stable color per ID, spread around the hue circle by the golden angle
+    fn golden_angle_color(id: u16) -> Color32 {
+        let hue = (id as f32 * 137.508) % 360.0;
+        let rgb: Srgb = Hsv::new(hue, 0.7, 0.9).into_color();
+        Color32::from_rgb((rgb.red * 255.0) as u8, (rgb.green * 255.0) as u8, (rgb.blue * 255.0) as u8)
+    }
+    
+    /// The device's color under the current coloring mode
+    fn device_color(&self, device: &UsbDeviceInfo) -> Color32 {
+        let id = match self.device_colors {
+            DeviceColoring::Vendor => device.vendor_id,
+            DeviceColoring::Class => device.device_class as u16,
+        };
+        *self.color_cache.borrow_mut()
+            .entry((self.device_colors, id))
+            .or_insert_with(|| Self::golden_angle_color(id))
+    }
+    
+    fn class_name(class: u8) -> &'static str {
+        match class {
+            0x00 => "Per-interface",
+            0x01 => "Audio",
+            0x02 => "Communications",
+            0x03 => "HID",
+            0x05 => "Physical",
+            0x06 => "Image",
+            0x07 => "Printer",
+            0x08 => "Mass Storage",
+            0x09 => "Hub",
+            0x0A => "CDC Data",
+            0x0B => "Smart Card",
+            0x0E => "Video",
+            0x0F => "Personal Healthcare",
+            0xE0 => "Wireless Controller",
+            0xEF => "Miscellaneous",
+            0xFE => "Application Specific",
+            0xFF => "Vendor Specific",
+            _ => "Unknown",
+        }
+    }
+    
+    fn process_tray_messages(&mut self, ctx: &egui::Context) {
         if let Some(tray) = &self.system_tray {
             let mut messages = Vec::new();
             
@@ -163,10 +597,13 @@ impl IronWatchGui {
             for message in messages {
                 match message {
                     TrayMessage::Show => {
-                        log::info!("Show window requested from system tray");
+                        self.set_window_visible(ctx, true);
                     }
                     TrayMessage::Hide => {
-                        log::info!("Hide window requested from system tray");
+                        self.set_window_visible(ctx, false);
+                    }
+                    TrayMessage::ToggleWindow => {
+                        self.set_window_visible(ctx, !self.window_visible);
                     }
                     TrayMessage::ToggleMonitoring => {
                         self.toggle_monitoring();
@@ -179,14 +616,24 @@ impl IronWatchGui {
                         log::info!("About requested from system tray");
                     }
                     TrayMessage::Quit => {
+                        // Closing the viewport returns from `run_native`, which stops the service
                         log::info!("Quit requested from system tray");
-                        std::process::exit(0);
+                        self.set_window_visible(ctx, true);
+                        ctx.send_viewport_cmd(ViewportCommand::Close);
                     }
                 }
             }
         }
     }
     
+    fn set_window_visible(&mut self, ctx: &egui::Context, visible: bool) {
+        self.window_visible = visible;
+        ctx.send_viewport_cmd(ViewportCommand::Visible(visible));
+        if visible {
+            ctx.send_viewport_cmd(ViewportCommand::Focus);
+        }
+    }
+    
     fn is_monitoring_active(&self) -> bool {
         matches!(self.monitoring_status, MonitoringStatus::Running)
     }
@@ -205,20 +652,96 @@ impl IronWatchGui {
     fn update_tray_icon(&mut self) {
         if let Some(tray) = &self.system_tray {
             let is_monitoring = self.is_monitoring_active();
-            if let Err(e) = tray.update_icon(is_monitoring) {
+            if let Err(e) = tray.update_icon(is_monitoring, self.blocked_alert.is_some()) {
                 log::warn!("Failed to update tray icon: {}", e);
             }
+            tray.update_monitoring_label(is_monitoring);
         }
     }
     
-    fn show_tray_notification(&self, title: &str, message: &str) {
+    /// Queue a change for a batched notification; blocked devices are notified immediately
+    fn queue_notification(&mut self, change: UsbDeviceChange) {
+        self.connection_events += 1;
+        
+        if let UsbDeviceChange::Blocked(device, _) = &change {
+            self.blocked_alert = Some(device.clone());
+            self.update_tray_icon();
+        }
+        
+        if let Some(notice) = self.notifications.push(change, Instant::now()) {
+            self.show_tray_notification(&notice);
+        }
+    }
+    
+    /// Show the pending batched notification once its window has elapsed
+    fn flush_notifications(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        
+        if let Some(notice) = self.notifications.poll(now) {
+            self.show_tray_notification(&notice);
+        }
+        
+        if let Some(delay) = self.notifications.next_flush_in(now) {
+            ctx.request_repaint_after(delay);
+        }
+    }
+    
+    fn show_tray_notification(&self, notice: &TrayNotice) {
         if let Some(tray) = &self.system_tray {
-            if let Err(e) = tray.show_notification(title, message) {
+            if let Err(e) = tray.show_notification(&notice.title, &notice.message, notice.urgency) {
                 log::warn!("Failed to show tray notification: {}", e);
             }
         }
     }
     
+    fn search_box_id() -> egui::Id {
+        egui::Id::new("device_search")
+    }
+    
+    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        // Don't steal keystrokes while the user is typing in a text field or filling in a rule
+        if ctx.wants_keyboard_input() || self.rule_editor.is_some() {
+            return;
+        }
+        
+        const TAB_KEYS: [(Key, Tab); 6] = [
+            (Key::Num1, Tab::Dashboard),
+            (Key::Num2, Tab::Devices),
+            (Key::Num3, Tab::Monitoring),
+            (Key::Num4, Tab::Statistics),
+            (Key::Num5, Tab::Security),
+            (Key::Num6, Tab::Settings),
+        ];
+        
+        for (key, tab) in TAB_KEYS {
+            if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, key)) {
+                self.current_tab = tab;
+            }
+        }
+        
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::R)) {
+            let _ = self.communication_hub.refresh_devices();
+        }
+        
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::M)) {
+            self.toggle_monitoring();
+        }
+        
+        let focus_search = ctx.input_mut(|i| {
+            let pressed = i.consume_key(Modifiers::NONE, Key::Slash);
+            if pressed {
+                // Keep the slash itself from being typed into the search box
+                i.events.retain(|event| !matches!(event, Event::Text(text) if text == "/"));
+            }
+            pressed
+        });
+        
+        if focus_search {
+            self.current_tab = Tab::Devices;
+            ctx.memory_mut(|memory| memory.request_focus(Self::search_box_id()));
+        }
+    }
+    
     fn render_top_panel(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.add_space(4.0);
@@ -236,9 +759,48 @@ impl IronWatchGui {
                     
                     ui.separator();
                     ui.label(format!("📱 {} devices", self.devices.len()));
+                    if let Some(ignored) = self.device_analytics.as_ref().map(|a| a.ignored_devices).filter(|n| *n > 0) {
+                        ui.colored_label(Color32::GRAY, format!("({} hidden by filters)", ignored))
+                            .on_hover_text("Devices from filters.ignored_vendors or filters.ignored_products are not shown");
+                    }
+                    self.render_device_sparkline(ui);
+                    
+                    ui.separator();
+                    ui.small(format!("FPS: {:.0}", self.fps));
                 });
             });
             
+            if let Some(reason) = &self.usb_unavailable {
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(120, 30, 30))
+                    .inner_margin(6.0)
+                    .rounding(4.0)
+                    .show(ui, |ui| {
+                        ui.colored_label(
+                            Color32::WHITE,
+                            format!("⚠ USB unavailable, running without monitoring: {}", reason),
+                        );
+                    });
+            }
+            
+            if self.blocking_suspended {
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(150, 90, 0))
+                    .inner_margin(6.0)
+                    .rounding(4.0)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                Color32::WHITE,
+                                "⚠ Device blocking is suspended: all devices are allowed until IronWatch restarts",
+                            );
+                            if ui.button("Resume Blocking").clicked() {
+                                let _ = self.communication_hub.set_blocking_suspended(false);
+                            }
+                        });
+                    });
+            }
+            
             ui.add_space(4.0);
             ui.separator();
             
@@ -313,8 +875,8 @@ impl IronWatchGui {
             .show(ui, |ui| {
                 for (i, device) in self.devices.iter().enumerate().take(5) {
                     ui.horizontal(|ui| {
-                        ui.colored_label(Color32::BLUE, "●");
-                        ui.label(device.product.as_deref().unwrap_or("Unknown Device"));
+                        ui.colored_label(Self::status_color(&device.connection_status), "●");
+                        ui.label(device.display_name());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.small(format!("{:04X}:{:04X}", device.vendor_id, device.product_id));
                         });
@@ -329,6 +891,68 @@ impl IronWatchGui {
                     ui.label("No devices found. Click 'Refresh Devices' to scan.");
                 }
             });
+        
+        ui.add_space(30.0);
+        
+        ui.heading("Activity Overview");
+        ui.add_space(10.0);
+        self.render_activity_plot(ui, "dashboard_activity", 150.0, |sample| sample.device_count, Color32::BLUE, "Devices");
+    }
+    
+    /// Device count over the last few samples, painted directly so it is cheap every frame
+    fn render_device_sparkline(&self, ui: &mut egui::Ui) {
+        let (rect, response) = ui.allocate_exact_size(vec2(60.0, 16.0), Sense::hover());
+        let skip = self.activity.len().saturating_sub(SPARKLINE_POINTS);
+        let samples = || self.activity.iter().skip(skip).map(|sample| sample.device_count as f32);
+        
+        let count = samples().count();
+        if count < 2 || !ui.is_rect_visible(rect) {
+            return;
+        }
+        
+        let (min, max) = samples().fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)));
+        // A flat line sits in the middle instead of on the bottom edge
+        let range = if max > min { max - min } else { 2.0 };
+        let base = if max > min { min } else { min - 1.0 };
+        let step = rect.width() / (SPARKLINE_POINTS - 1) as f32;
+        let start = rect.right() - step * (count - 1) as f32;
+        
+        let points: Vec<Pos2> = samples()
+            .enumerate()
+            .map(|(i, value)| pos2(start + step * i as f32, rect.bottom() - (value - base) / range * rect.height()))
+            .collect();
+        ui.painter().add(Shape::line(points, Stroke::new(1.5, Color32::from_rgb(100, 200, 100))));
+        
+        response.on_hover_text(format!("Device count over the last {} samples: {} to {}", count, min, max));
+    }
+    
+    /// Line plot of one activity series over the session
+    fn render_activity_plot(
+        &self,
+        ui: &mut egui::Ui,
+        id: &str,
+        height: f32,
+        value: fn(&ActivitySample) -> f64,
+        color: Color32,
+        name: &str,
+    ) {
+        if self.activity.is_empty() {
+            ui.label("No activity data yet...");
+            return;
+        }
+        
+        let points: PlotPoints = self.activity
+            .iter()
+            .map(|sample| [sample.seconds, value(sample)])
+            .collect();
+        
+        Plot::new(id)
+            .height(height)
+            .allow_scroll(false)
+            .x_axis_label("Seconds")
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(points).color(color).width(2.0).name(name));
+            });
     }
     
     fn render_devices_tab(&mut self, ui: &mut egui::Ui) {
@@ -339,6 +963,10 @@ impl IronWatchGui {
                 if ui.button("🔄 Refresh").clicked() {
                     let _ = self.communication_hub.refresh_devices();
                 }
+                
+                ui.separator();
+                ui.checkbox(&mut self.compact_view, "Compact");
+                ui.checkbox(&mut self.show_animations, "Animations");
             });
         });
         
@@ -347,50 +975,210 @@ impl IronWatchGui {
         // Search filter
         ui.horizontal(|ui| {
             ui.label("Search:");
-            ui.text_edit_singleline(&mut self.search_filter);
+            ui.add(egui::TextEdit::singleline(&mut self.search_filter)
+                .id(Self::search_box_id())
+                .hint_text("Press / to search"));
             
             if ui.button("Clear").clicked() {
                 self.search_filter.clear();
             }
+            
+            #[cfg(feature = "cli")]
+            {
+                use crate::cli::OutputFormat;
+                
+                ui.separator();
+                ComboBox::from_id_source("filtered_export_format")
+                    .selected_text(self.filtered_export_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in [OutputFormat::Json, OutputFormat::Ndjson, OutputFormat::Table, OutputFormat::Csv, OutputFormat::Xml, OutputFormat::Toml] {
+                            let name = format.name();
+                            ui.selectable_value(&mut self.filtered_export_format, format, name);
+                        }
+                    });
+                
+                if ui.button("📤 Export Filtered").on_hover_text("Export the devices matching the search").clicked() {
+                    let result = self.export_filtered_devices();
+                    self.report_export(result);
+                }
+            }
         });
         
         ui.add_space(10.0);
         
-        // Device list
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            egui::Grid::new("device_grid")
-                .striped(true)
-                .show(ui, |ui| {
-                    // Header
-                    ui.strong("Manufacturer");
-                    ui.strong("Product");
-                    ui.strong("VID:PID");
-                    ui.strong("Bus");
-                    ui.strong("Class");
-                    ui.end_row();
+        self.render_color_legend(ui);
+        ui.add_space(10.0);
+        
+        self.render_device_table(ui);
+    }
+    
+    /// Whether the device's manufacturer or product contains the lowercased search text
+    fn matches_search(device: &UsbDeviceInfo, search_lower: &str) -> bool {
+        search_lower.is_empty()
+            || device.manufacturer.as_deref().unwrap_or("").to_lowercase().contains(search_lower)
+            || device.product.as_deref().unwrap_or("").to_lowercase().contains(search_lower)
+    }
+    
+    /// Write the devices matching the search through the CLI's output formats
+    #[cfg(feature = "cli")]
+    fn export_filtered_devices(&self) -> Result<std::path::PathBuf> {
+        self.write_filtered_devices().map_err(|e| match e.downcast::<std::io::Error>() {
+            Ok(io_error) => io_error.into(),
+            Err(e) => GuiError::export_failed(format!("{:#}", e)),
+        })
+    }
+    
+    #[cfg(feature = "cli")]
+    fn write_filtered_devices(&self) -> anyhow::Result<std::path::PathBuf> {
+        use crate::cli::FileMode;
+        use crate::output::{FileSink, OutputManager};
+        
+        let search_lower = self.search_filter.to_lowercase();
+        let devices: Vec<UsbDeviceInfo> = self.devices.iter()
+            .filter(|device| Self::matches_search(device, &search_lower))
+            .cloned()
+            .collect();
+        
+        let format = self.filtered_export_format.clone();
+        let extension = match format {
+            crate::cli::OutputFormat::Table => "txt",
+            _ => format.name(),
+        };
+        let path = std::path::PathBuf::from(format!(
+            "ironwatch_devices_{}.{}",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+            extension,
+        ));
+        
+        let sink = FileSink::open(format.clone(), &path, FileMode::Truncate)?;
+        let mut output = OutputManager::with_sinks(format, vec![Box::new(sink)], false, true);
+        let filter = Some(self.search_filter.trim())
+            .filter(|search| !search.is_empty())
+            .map(|search| format!("search: {}", search));
+        output.set_filter_description(filter);
+        if self.include_tags {
+            output.set_device_tags(self.device_tags.clone());
+        }
+        output.display_devices(&devices)?;
+        Ok(path)
+    }
+    
+    /// One entry per vendor or class among the devices matching the search
+    fn render_color_legend(&self, ui: &mut egui::Ui) {
+        let search_lower = self.search_filter.to_lowercase();
+        let mut entries: BTreeMap<u16, (Color32, String)> = BTreeMap::new();
+        
+        for device in self.devices.iter().filter(|device| Self::matches_search(device, &search_lower)) {
+            let (id, name) = match self.device_colors {
+                DeviceColoring::Vendor => (
+                    device.vendor_id,
+                    format!("{:04X} {}", device.vendor_id, device.manufacturer.as_deref().unwrap_or("Unknown")),
+                ),
+                DeviceColoring::Class => (
+                    device.device_class as u16,
+                    format!("{:02X} {}", device.device_class, Self::class_name(device.device_class)),
+                ),
+            };
+            entries.entry(id).or_insert_with(|| (self.device_color(device), name));
+        }
+        
+        if entries.is_empty() {
+            return;
+        }
+        
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let heading = match self.device_colors {
+                    DeviceColoring::Vendor => "Vendors:",
+                    DeviceColoring::Class => "Classes:",
+                };
+                ui.strong(heading);
+                for (color, name) in entries.values() {
+                    ui.colored_label(*color, "●");
+                    ui.label(name);
+                    ui.add_space(8.0);
+                }
+            });
+        });
+    }
+    
+    /// Device table; new devices fade in with a highlight and removed ones fade out
+    fn render_device_table(&self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let search_lower = self.search_filter.to_lowercase();
+        
+        // Current devices, then the ones still fading out
+        let departed = self.device_animations.values().filter_map(|animation| animation.departed.as_ref());
+        let rows: Vec<(&UsbDeviceInfo, f32, f32)> = self.devices.iter()
+            .chain(departed.filter(|_| self.show_animations))
+            .filter(|device| Self::matches_search(device, &search_lower))
+            .map(|device| {
+                let animation = self.device_animations.get(&Self::device_key(device)).filter(|_| self.show_animations);
+                let alpha = animation.map_or(1.0, |animation| animation.alpha(now));
+                let highlight = animation.map_or(0.0, |animation| animation.highlight(now));
+                (device, alpha, highlight)
+            })
+            .collect();
+        
+        let text_color = ui.visuals().text_color();
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        let row_height = if self.compact_view { text_height + 4.0 } else { text_height + 8.0 };
+        
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::exact(30.0))
+            .column(Column::initial(140.0).range(80.0..=250.0))
+            .column(Column::initial(180.0).range(100.0..=300.0))
+            .column(Column::initial(120.0).range(60.0..=250.0))
+            .column(Column::exact(80.0))
+            .column(Column::exact(40.0))
+            .column(Column::exact(50.0))
+            .column(Column::remainder())
+            .header(20.0, |mut header| {
+                header.col(|ui| { ui.strong(""); });
+                header.col(|ui| { ui.strong("Manufacturer"); });
+                header.col(|ui| { ui.strong("Product"); });
+                header.col(|ui| { ui.strong("Tag"); });
+                header.col(|ui| { ui.strong("VID:PID"); });
+                header.col(|ui| { ui.strong("Bus"); });
+                header.col(|ui| { ui.strong("Class"); });
+                header.col(|ui| { ui.strong("Status"); });
+            })
+            .body(|mut body| {
+                for (device, alpha, highlight) in rows {
+                    let fade = |color: Color32| color.gamma_multiply(alpha);
                     
-                    // Devices
-                    for device in &self.devices {
-                        // Apply search filter
-                        if !self.search_filter.is_empty() {
-                            let search_lower = self.search_filter.to_lowercase();
-                            let matches = device.manufacturer.as_deref().unwrap_or("").to_lowercase().contains(&search_lower)
-                                || device.product.as_deref().unwrap_or("").to_lowercase().contains(&search_lower);
-                            
-                            if !matches {
-                                continue;
+                    body.row(row_height, |mut row| {
+                        row.col(|ui| {
+                            if highlight > 0.0 {
+                                ui.painter().circle_filled(
+                                    ui.available_rect_before_wrap().center(),
+                                    8.0 + highlight * 4.0,
+                                    Color32::from_rgba_unmultiplied(255, 255, 0, (100.0 * highlight) as u8),
+                                );
                             }
-                        }
+                            ui.colored_label(fade(self.device_color(device)), "●");
+                        });
                         
-                        ui.label(device.manufacturer.as_deref().unwrap_or("Unknown"));
-                        ui.label(device.product.as_deref().unwrap_or("Unknown"));
-                        ui.monospace(format!("{:04X}:{:04X}", device.vendor_id, device.product_id));
-                        ui.label(device.bus_number.to_string());
-                        ui.monospace(format!("{:02X}", device.device_class));
-                        ui.end_row();
-                    }
-                });
-        });
+                        let text = fade(text_color);
+                        row.col(|ui| { ui.colored_label(text, device.manufacturer.as_deref().unwrap_or("Unknown")); });
+                        row.col(|ui| { ui.colored_label(text, device.product.as_deref().unwrap_or("Unknown")); });
+                        row.col(|ui| {
+                            if let Some(tag) = device_tag(&self.device_tags, device) {
+                                ui.colored_label(text, RichText::new(tag).italics());
+                            }
+                        });
+                        row.col(|ui| { ui.colored_label(text, RichText::new(format!("{:04X}:{:04X}", device.vendor_id, device.product_id)).monospace()); });
+                        row.col(|ui| { ui.colored_label(text, device.bus_number.to_string()); });
+                        row.col(|ui| { ui.colored_label(text, RichText::new(format!("{:02X}", device.device_class)).monospace()); });
+                        row.col(|ui| {
+                            ui.colored_label(fade(Self::status_color(&device.connection_status)), format!("{:?}", device.connection_status));
+                        });
+                    });
+                }
+            });
     }
     
     fn render_monitoring_tab(&mut self, ui: &mut egui::Ui) {
@@ -440,6 +1228,16 @@ impl IronWatchGui {
         ui.separator();
         ui.add_space(10.0);
         ui.label(format!("Current device count: {}", self.devices.len()));
+        
+        ui.add_space(20.0);
+        
+        ui.heading("Device Count");
+        self.render_activity_plot(ui, "monitoring_device_count", 150.0, |sample| sample.device_count, Color32::BLUE, "Devices");
+        
+        ui.add_space(10.0);
+        
+        ui.heading("Connection Events");
+        self.render_activity_plot(ui, "monitoring_connection_events", 150.0, |sample| sample.connection_events, Color32::GREEN, "Events");
     }
     
     fn render_statistics_tab(&mut self, ui: &mut egui::Ui) {
@@ -452,6 +1250,8 @@ impl IronWatchGui {
         if let Some(analytics) = &self.device_analytics {
             ui.label(format!("Total Devices Seen: {}", analytics.total_devices_seen));
             ui.label(format!("Unique Devices: {}", analytics.unique_devices));
+            ui.label(format!("Unique Fingerprints: {}", analytics.unique_fingerprints));
+            ui.label(format!("Hidden by Filters: {}", analytics.ignored_devices));
             ui.label(format!("Blocked Devices: {}", analytics.blocked_devices));
             ui.label(format!("Security Violations: {}", analytics.security_violations));
             
@@ -522,47 +1322,40 @@ impl IronWatchGui {
 
         ui.add_space(30.0);
 
+        // Device count over the session
+        ui.heading("Device Count");
+        ui.add_space(10.0);
+        self.render_activity_plot(ui, "statistics_device_count", 150.0, |sample| sample.device_count, Color32::BLUE, "Devices");
+
+        ui.add_space(30.0);
+
         // Connection Frequency Chart
         ui.heading("Connection Frequency (Last 24 Hours)");
         ui.add_space(10.0);
-        if let Some(analytics) = &self.device_analytics {
-            if !analytics.connection_frequency.is_empty() {
-                let max_connections = analytics.connection_frequency.iter().map(|(_, count)| count).max().unwrap_or(&0);
-                
-                egui::ScrollArea::horizontal()
-                    .max_height(150.0)
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            for (timestamp, count) in &analytics.connection_frequency {
-                                let height = if *max_connections > 0 {
-                                    (*count as f32 / *max_connections as f32) * 100.0
-                                } else {
-                                    0.0
-                                };
-                                
-                                let hour = timestamp.format("%H").to_string();
-                                ui.vertical(|ui| {
-                                    ui.label(hour);
-                                    ui.add_space(5.0);
-                                    ui.allocate_ui(egui::vec2(20.0, 100.0), |ui| {
-                                        let rect = ui.available_rect_before_wrap();
-                                        ui.painter().rect_filled(
-                                            rect,
-                                            5.0,
-                                            Color32::from_rgb(100, 150, 255)
-                                        );
-                                        ui.painter().rect_filled(
-                                            rect.shrink(2.0),
-                                            3.0,
-                                            Color32::from_rgb(150, 200, 255)
-                                        );
-                                    });
-                                    ui.label(count.to_string());
-                                });
-                            }
-                        });
+        match &self.device_analytics {
+            Some(analytics) if !analytics.connection_frequency.is_empty() => {
+                let now = chrono::Utc::now();
+                let bars: Vec<Bar> = analytics.connection_frequency
+                    .iter()
+                    .map(|(timestamp, count)| {
+                        let hours_ago = (now - *timestamp).num_minutes() as f64 / 60.0;
+                        Bar::new(-hours_ago, *count as f64)
+                            .width(0.8)
+                            .name(timestamp.format("%H:00").to_string())
+                    })
+                    .collect();
+
+                Plot::new("statistics_connection_frequency")
+                    .height(150.0)
+                    .allow_scroll(false)
+                    .x_axis_label("Hours ago")
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(BarChart::new(bars).color(Color32::from_rgb(100, 150, 255)).name("Connections"));
                     });
             }
+            _ => {
+                ui.label("No connections recorded yet.");
+            }
         }
 
         ui.add_space(30.0);
@@ -573,47 +1366,96 @@ impl IronWatchGui {
                 self.refresh_analytics();
             }
             
+            ComboBox::from_id_source("export_kind")
+                .selected_text(self.export_kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in [ExportKind::History, ExportKind::Timeline] {
+                        ui.selectable_value(&mut self.export_kind, kind, kind.label());
+                    }
+                });
+
+            #[cfg(feature = "cli")]
+            if self.export_kind == ExportKind::Timeline {
+                use crate::cli::OutputFormat;
+
+                ComboBox::from_id_source("timeline_export_format")
+                    .selected_text(self.timeline_export_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in [OutputFormat::Json, OutputFormat::Ndjson, OutputFormat::Table, OutputFormat::Csv, OutputFormat::Xml, OutputFormat::Toml] {
+                            let name = format.name();
+                            ui.selectable_value(&mut self.timeline_export_format, format, name);
+                        }
+                    });
+            }
+
             if ui.button("📤 Export Data").clicked() {
-                self.export_analytics_data();
+                let result = match self.export_kind {
+                    ExportKind::History => self.export_analytics_data(),
+                    ExportKind::Timeline => self.export_timeline(),
+                };
+                self.report_export(result);
             }
         });
     }
     
     fn refresh_analytics(&mut self) {
-        // For now, we'll simulate analytics data since we don't have direct access to USB monitor
-        // In a real implementation, this would come from the communication hub
-        use crate::usb_monitor::DeviceAnalytics;
-        use std::collections::HashMap;
-        
-        let mut analytics = DeviceAnalytics {
-            device_class_distribution: HashMap::new(),
-            vendor_distribution: HashMap::new(),
-            connection_frequency: Vec::new(),
-            total_devices_seen: self.devices.len() as u32,
-            unique_devices: self.devices.len() as u32,
-            blocked_devices: 0,
-            security_violations: 0,
+        let _ = self.communication_hub.refresh_analytics();
+        self.last_refresh = Instant::now();
+    }
+    
+    /// Log an export's result and show it in the export dialog
+    fn report_export(&mut self, result: Result<std::path::PathBuf>) {
+        self.export_outcome = Some(match result {
+            Ok(path) => {
+                log::info!("Data exported successfully to: {}", path.display());
+                // Exports go to the working directory, so show where that is
+                ExportOutcome::Written(std::fs::canonicalize(&path).unwrap_or(path))
+            }
+            Err(e) => {
+                log::error!("Failed to export data: {}", e);
+                let (message, suggestion) = get_user_friendly_message(&e);
+                ExportOutcome::Failed { message, suggestion }
+            }
+        });
+    }
+    
+    /// Write the per-device connection intervals for Gantt-style charts, in the picked format
+    #[cfg(feature = "cli")]
+    fn export_timeline(&self) -> Result<std::path::PathBuf> {
+        use crate::output::{HistoryExport, OutputManager};
+
+        let format = self.timeline_export_format.clone();
+        let extension = match format {
+            crate::cli::OutputFormat::Table => "txt",
+            _ => format.name(),
         };
-        
-        // Generate sample analytics from current devices
-        for device in &self.devices {
-            *analytics.device_class_distribution.entry(device.device_class).or_insert(0) += 1;
-            *analytics.vendor_distribution.entry(device.vendor_id).or_insert(0) += 1;
-        }
-        
-        // Generate sample connection frequency (last 24 hours)
+        let export_path = std::path::PathBuf::from(format!(
+            "ironwatch_timeline_{}.{}",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+            extension,
+        ));
+
+        OutputManager::with_sinks(format, Vec::new(), false, true)
+            .export_device_history(HistoryExport::Timeline(&self.connection_timeline), &export_path)
+            .map_err(|e| match e.downcast::<std::io::Error>() {
+                Ok(io_error) => io_error.into(),
+                Err(e) => GuiError::export_failed(format!("{:#}", e)),
+            })?;
+        Ok(export_path)
+    }
+
+    /// Write the per-device connection intervals for Gantt-style charts
+    #[cfg(not(feature = "cli"))]
+    fn export_timeline(&self) -> Result<std::path::PathBuf> {
         let now = chrono::Utc::now();
-        for hour in 0..24 {
-            let hour_start = now - chrono::Duration::hours(24 - hour);
-            let connections = if hour % 3 == 0 { 2 } else { 0 }; // Sample data
-            analytics.connection_frequency.push((hour_start, connections));
-        }
-        
-        self.device_analytics = Some(analytics);
-        self.last_refresh = Instant::now();
+        let export_path = std::path::PathBuf::from(format!("ironwatch_timeline_{}.json", now.format("%Y%m%d_%H%M%S")));
+        let document = crate::snapshot::timeline_json(&self.connection_timeline, now);
+
+        std::fs::write(&export_path, serde_json::to_string_pretty(&document)?)?;
+        Ok(export_path)
     }
     
-    fn export_analytics_data(&mut self) {
+    fn export_analytics_data(&self) -> Result<std::path::PathBuf> {
         use std::path::PathBuf;
         use chrono::Utc;
         
@@ -621,60 +1463,62 @@ impl IronWatchGui {
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
         let export_path = PathBuf::from(format!("ironwatch_export_{}.json", timestamp));
         
-        if let Some(analytics) = &self.device_analytics {
-            // Create device stats from current devices
-            let mut device_stats = Vec::new();
-            for device in &self.devices {
-                let key = format!("{}:{}:{}:{}", 
-                    device.vendor_id, device.product_id, device.bus_number, device.device_address);
-                let stats = crate::usb_monitor::DeviceStatistics {
-                    total_connections: 1,
-                    total_disconnections: 0,
-                    total_blocked: 0,
-                    first_seen: device.timestamp,
-                    last_seen: device.timestamp,
-                    connection_duration: std::time::Duration::ZERO,
-                    connection_count: 1,
-                };
-                device_stats.push((key, stats));
-            }
-            
-            // Export to JSON format
-            let export_data = serde_json::json!({
-                "export_timestamp": Utc::now(),
-                "export_format": "json",
-                "summary": {
-                    "total_devices": self.devices.len(),
-                    "total_connections": analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>(),
-                    "unique_devices": analytics.unique_devices,
-                    "blocked_devices": analytics.blocked_devices,
-                    "security_violations": analytics.security_violations,
-                    "device_classes": analytics.device_class_distribution.len(),
-                    "vendors": analytics.vendor_distribution.len(),
-                },
-                "current_devices": self.devices,
-                "device_statistics": device_stats,
-                "analytics": {
-                    "device_class_distribution": analytics.device_class_distribution,
-                    "vendor_distribution": analytics.vendor_distribution,
-                    "connection_frequency": analytics.connection_frequency,
-                },
-                "security_events": self.security_events
-            });
-            
-            match serde_json::to_string_pretty(&export_data) {
-                Ok(json_string) => {
-                    if let Err(e) = std::fs::write(&export_path, json_string) {
-                        log::error!("Failed to export data: {}", e);
-                    } else {
-                        log::info!("Data exported successfully to: {}", export_path.display());
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to serialize export data: {}", e);
-                }
-            }
+        let Some(analytics) = &self.device_analytics else {
+            return Err(GuiError::export_failed("No analytics received yet. Start monitoring or refresh analytics first."));
+        };
+        
+        // Create device stats from current devices
+        let mut device_stats = Vec::new();
+        for device in &self.devices {
+            let key = format!("{}:{}:{}:{}", 
+                device.vendor_id, device.product_id, device.bus_number, device.device_address);
+            let stats = crate::usb_monitor::DeviceStatistics {
+                total_connections: 1,
+                total_disconnections: 0,
+                total_blocked: 0,
+                first_seen: device.timestamp,
+                last_seen: device.timestamp,
+                connection_duration: std::time::Duration::ZERO,
+                connection_count: 1,
+            };
+            device_stats.push((key, stats));
         }
+        
+        let current_devices: Vec<serde_json::Value> = self.devices.iter().map(|device| {
+            let mut value = serde_json::json!(device);
+            if let Some(tag) = device_tag(&self.device_tags, device).filter(|_| self.include_tags) {
+                value["tag"] = serde_json::json!(tag);
+            }
+            value
+        }).collect();
+        
+        // Export to JSON format
+        let export_data = serde_json::json!({
+            "export_timestamp": Utc::now(),
+            "export_format": "json",
+            "summary": {
+                "total_devices": self.devices.len(),
+                "total_connections": analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>(),
+                "unique_devices": analytics.unique_devices,
+                "unique_fingerprints": analytics.unique_fingerprints,
+                "blocked_devices": analytics.blocked_devices,
+                "security_violations": analytics.security_violations,
+                "device_classes": analytics.device_class_distribution.len(),
+                "vendors": analytics.vendor_distribution.len(),
+            },
+            "current_devices": current_devices,
+            "device_statistics": device_stats,
+            "analytics": {
+                "device_class_distribution": analytics.device_class_distribution,
+                "vendor_distribution": analytics.vendor_distribution,
+                "connection_frequency": analytics.connection_frequency,
+            },
+            "security_events": self.security_events
+        });
+        
+        std::fs::write(&export_path, serde_json::to_string_pretty(&export_data)?)?;
+        
+        Ok(export_path)
     }
     
     fn render_security_tab(&mut self, ui: &mut egui::Ui) {
@@ -718,6 +1562,7 @@ impl IronWatchGui {
                                 SecurityEventType::DeviceAllowed => ("✅", Color32::GREEN),
                                 SecurityEventType::RuleViolation => ("⚠️", Color32::from_rgb(255, 165, 0)),
                                 SecurityEventType::SuspiciousActivity => ("🔍", Color32::YELLOW),
+                                _ => ("•", Color32::GRAY),
                             };
                             
                             ui.colored_label(color, icon);
@@ -727,7 +1572,7 @@ impl IronWatchGui {
                                 ui.horizontal(|ui| {
                                     ui.strong(format!("{} - {}", 
                                         event.timestamp.format("%H:%M:%S"),
-                                        event.device_info.product.as_deref().unwrap_or("Unknown Device")
+                                        event.device_info.display_name()
                                     ));
                                     
                                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -755,6 +1600,9 @@ impl IronWatchGui {
                             ui.add_space(10.0);
                             ui.group(|ui| {
                                 ui.label("Device Details:");
+                                if let Some(tag) = device_tag(&self.device_tags, &event.device_info) {
+                                    ui.label(format!("Tag: {}", tag));
+                                }
                                 ui.label(format!("Manufacturer: {}", event.device_info.manufacturer.as_deref().unwrap_or("Unknown")));
                                 ui.label(format!("Product: {}", event.device_info.product.as_deref().unwrap_or("Unknown")));
                                 ui.label(format!("Serial: {}", event.device_info.serial_number.as_deref().unwrap_or("Unknown")));
@@ -786,8 +1634,13 @@ impl IronWatchGui {
             ui.add_space(10.0);
             
             if ui.button("➕ Add Rule").clicked() {
-                // TODO: Show add rule dialog
-                log::info!("Add rule requested");
+                self.rule_editor = Some(RuleEditor::new(RuleList::Blacklist));
+            }
+            
+            ui.add_space(10.0);
+            
+            if !self.blocking_suspended && ui.button("🛑 Unblock All").clicked() {
+                self.confirm_unblock_all = true;
             }
             
             ui.add_space(10.0);
@@ -832,6 +1685,12 @@ impl IronWatchGui {
         ui.checkbox(&mut self.dark_mode, "Dark Mode");
         ui.checkbox(&mut self.show_animations, "Enable Animations");
         
+        ui.horizontal(|ui| {
+            ui.label("Color devices by:");
+            ui.radio_value(&mut self.device_colors, DeviceColoring::Vendor, "Vendor");
+            ui.radio_value(&mut self.device_colors, DeviceColoring::Class, "Class");
+        });
+        
         ui.add_space(20.0);
         
         ui.heading("System Integration");
@@ -845,6 +1704,7 @@ impl IronWatchGui {
                     Ok((tray, sender)) => {
                         self.system_tray = Some(tray);
                         self.tray_sender = Some(sender);
+                        self.update_tray_icon();
                         log::info!("System tray enabled");
                     }
                     Err(e) => {
@@ -866,6 +1726,15 @@ impl IronWatchGui {
             ui.label("System tray is disabled");
         }
         
+        let mut window_ms = self.notifications.window.as_millis() as u64;
+        if ui.add(egui::Slider::new(&mut window_ms, 0..=5000)
+            .text("Notification batching window (ms)"))
+            .on_hover_text("Changes within this window are combined into one notification. Blocked devices are always notified immediately.")
+            .changed()
+        {
+            self.notifications.window = Duration::from_millis(window_ms);
+        }
+        
         ui.add_space(20.0);
         
         if ui.button("💾 Save Settings").clicked() {
@@ -873,10 +1742,35 @@ impl IronWatchGui {
             log::info!("Settings saved (placeholder)");
         }
         
-        if ui.button("🔄 Reset to Defaults").clicked() {
-            self.dark_mode = true;
-            self.show_animations = true;
-        }
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Reset to Defaults").clicked() {
+                self.confirm_reset = true;
+            }
+            
+            if let Some(previous) = self.settings_before_reset {
+                if ui.button("↩ Undo Reset").clicked() {
+                    self.apply_settings(previous);
+                    self.settings_before_reset = None;
+                }
+            }
+        });
+        
+        ui.add_space(30.0);
+        ui.separator();
+        ui.add_space(10.0);
+        
+        ui.heading("Keyboard Shortcuts");
+        ui.add_space(10.0);
+        
+        egui::Grid::new("shortcuts_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for (keys, action) in KEYBOARD_SHORTCUTS {
+                    ui.monospace(*keys);
+                    ui.label(*action);
+                    ui.end_row();
+                }
+            });
         
         ui.add_space(30.0);
         ui.separator();
@@ -891,6 +1785,112 @@ impl IronWatchGui {
         ui.small("Built with Rust + egui");
     }
     
+    fn settings_snapshot(&self) -> SettingsSnapshot {
+        SettingsSnapshot {
+            dark_mode: self.dark_mode,
+            show_animations: self.show_animations,
+            device_colors: self.device_colors,
+            notification_window: self.notifications.window,
+        }
+    }
+    
+    fn apply_settings(&mut self, settings: SettingsSnapshot) {
+        self.dark_mode = settings.dark_mode;
+        self.show_animations = settings.show_animations;
+        self.device_colors = settings.device_colors;
+        self.notifications.window = settings.notification_window;
+    }
+    
+    fn render_rule_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.rule_editor else {
+            return;
+        };
+        
+        let mut action = None;
+        egui::Window::new("➕ Add Device Rule")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                action = editor.show(ui);
+            });
+        
+        match action {
+            Some(RuleEditorAction::Save(list, rule)) => {
+                let sent = match list {
+                    RuleList::Blacklist => self.communication_hub.add_blacklist_rule(*rule),
+                    RuleList::Whitelist => self.communication_hub.add_whitelist_rule(*rule),
+                };
+                if let Err(e) = sent {
+                    self.last_error = Some(format!("Failed to add rule: {}", e));
+                }
+                self.rule_editor = None;
+            }
+            Some(RuleEditorAction::Cancel) => {
+                self.rule_editor = None;
+            }
+            None => {}
+        }
+    }
+    
+    fn render_reset_confirmation(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Reset Settings?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("All settings will return to their defaults.");
+                ui.label("You can undo this from the Settings tab until IronWatch exits.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Reset").clicked() {
+                        // Resetting twice keeps the settings from before the first reset
+                        let current = self.settings_snapshot();
+                        if current != SettingsSnapshot::default() {
+                            self.settings_before_reset = Some(current);
+                        }
+                        self.apply_settings(SettingsSnapshot::default());
+                        self.confirm_reset = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_reset = false;
+                    }
+                });
+            });
+    }
+    
+    fn render_unblock_all_confirmation(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Unblock All Devices?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("No device will be blocked until IronWatch restarts.");
+                ui.label("The configuration file is not changed.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Unblock All").clicked() {
+                        let _ = self.communication_hub.set_blocking_suspended(true);
+                        self.confirm_unblock_all = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_unblock_all = false;
+                    }
+                });
+            });
+    }
+    
+    fn status_color(status: &ConnectionStatus) -> Color32 {
+        match status {
+            ConnectionStatus::Connected => Color32::BLUE,
+            ConnectionStatus::Reconnected => Color32::YELLOW,
+            ConnectionStatus::Disconnected => Color32::GRAY,
+            ConnectionStatus::Blocked => Color32::RED,
+            ConnectionStatus::Suspended => Color32::from_rgb(120, 120, 200),
+            _ => Color32::GRAY,
+        }
+    }
+    
     fn render_stat_card(&self, ui: &mut egui::Ui, title: &str, value: &str, color: Color32) {
         egui::Frame::none()
             .fill(color.gamma_multiply(0.1))
@@ -909,9 +1909,16 @@ impl eframe::App for IronWatchGui {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Process monitoring events
         self.process_monitoring_events();
+        self.update_animations();
         
         // Process tray messages
-        self.process_tray_messages();
+        self.process_tray_messages(ctx);
+        
+        // Show batched device-change notifications
+        self.flush_notifications(ctx);
+        
+        // Global keyboard shortcuts
+        self.handle_keyboard_shortcuts(ctx);
         
         // Auto-refresh devices periodically
         if self.last_refresh.elapsed().as_secs() >= 5 {
@@ -936,6 +1943,23 @@ impl eframe::App for IronWatchGui {
                 });
         }
         
+        // Warn about rules that could lock the user out
+        if !self.config_warnings.is_empty() {
+            egui::Window::new("⚠ Check Device Rules")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    for warning in &self.config_warnings {
+                        ui.label(format!("• {}", warning));
+                    }
+                    ui.add_space(10.0);
+                    if ui.button("OK").clicked() {
+                        self.config_warnings.clear();
+                    }
+                });
+        }
+        
         // Show permission dialog if needed
         if self.show_permission_dialog {
             egui::Window::new("Permission Required")
@@ -952,13 +1976,86 @@ impl eframe::App for IronWatchGui {
                 });
         }
         
-        // Request repaint for animations
-        if self.show_animations {
+        if self.confirm_reset {
+            self.render_reset_confirmation(ctx);
+        }
+        
+        if self.confirm_unblock_all {
+            self.render_unblock_all_confirmation(ctx);
+        }
+        
+        // Report the last export's file, or why it failed
+        if let Some(outcome) = self.export_outcome.clone() {
+            let title = match outcome {
+                ExportOutcome::Written(_) => "✅ Export Complete",
+                ExportOutcome::Failed { .. } => "❌ Export Failed",
+            };
+            egui::Window::new(title)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    match &outcome {
+                        ExportOutcome::Written(path) => {
+                            ui.label("Data was exported to:");
+                            ui.monospace(path.display().to_string());
+                        }
+                        ExportOutcome::Failed { message, suggestion } => {
+                            ui.colored_label(Color32::RED, message);
+                            if let Some(suggestion) = suggestion {
+                                ui.add_space(5.0);
+                                ui.label(suggestion);
+                            }
+                        }
+                    }
+                    ui.add_space(10.0);
+                    if ui.button("OK").clicked() {
+                        self.export_outcome = None;
+                    }
+                });
+        }
+        
+        self.render_rule_editor(ctx);
+        
+        // Show the block message for the most recently blocked device
+        if let Some(device) = self.blocked_alert.clone() {
+            egui::Window::new("🚫 USB Device Blocked")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(RichText::new(&self.block_message).size(16.0).strong().color(Color32::RED));
+                    ui.add_space(10.0);
+                    ui.label(format!(
+                        "{} ({:04x}:{:04x})",
+                        device.display_name(),
+                        device.vendor_id,
+                        device.product_id,
+                    ));
+                    ui.add_space(10.0);
+                    if ui.button("OK").clicked() {
+                        self.blocked_alert = None;
+                        self.update_tray_icon();
+                    }
+                });
+        }
+        
+        // Request repaint for animations, continuously while rows are fading
+        if self.show_animations && !self.device_animations.is_empty() {
+            ctx.request_repaint();
+        } else if self.show_animations {
             ctx.request_repaint_after(std::time::Duration::from_millis(100));
         }
     }
     
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Don't lose a batched notification that was still waiting for its window
+        if let Some(notice) = self.notifications.take_pending() {
+            self.show_tray_notification(&notice);
+        }
+        
+        let _ = self.communication_hub.stop_monitoring();
+        
         // Clean up system tray on exit
         if self.system_tray.is_some() {
             log::info!("Cleaning up system tray on exit");
@@ -0,0 +1,124 @@
+//! Tests that exercise the real rusb path against a virtual USB device.
+//!
+//! Built only with `--features hardware-tests`, and skipped at runtime unless
+//! `IRONWATCH_HARDWARE_TESTS=1` is set. They need Linux, root, and a USB device
+//! controller such as `dummy_hcd` (`modprobe dummy_hcd`). The `g_zero` gadget is
+//! loaded and unloaded to plug and unplug a loopback device (0525:a4a0):
+//!
+//! ```text
+//! sudo modprobe dummy_hcd
+//! sudo IRONWATCH_HARDWARE_TESTS=1 cargo test --features hardware-tests hardware_tests
+//! ```
+
+use crate::usb_monitor::{UsbDeviceChange, UsbDeviceInfo, UsbMonitor};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const ENABLE_ENV: &str = "IRONWATCH_HARDWARE_TESTS";
+
+/// Gadget Zero's vendor and product ID
+const GADGET_ZERO_ID: (u16, u16) = (0x0525, 0xa4a0);
+
+/// How long to wait for the host side to notice the gadget appearing or vanishing
+const ENUMERATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why the virtual USB facility can't be used, or `None` if it can
+fn unavailable_reason() -> Option<String> {
+    if std::env::var(ENABLE_ENV).map_or(true, |value| value != "1") {
+        return Some(format!("{} is not set to 1", ENABLE_ENV));
+    }
+
+    if !cfg!(target_os = "linux") {
+        return Some("virtual USB gadgets are only supported on Linux".to_string());
+    }
+
+    let has_udc = std::fs::read_dir("/sys/class/udc")
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if !has_udc {
+        return Some("no USB device controller in /sys/class/udc (try `modprobe dummy_hcd`)".to_string());
+    }
+
+    if Path::new("/sys/module/g_zero").exists() {
+        return Some("g_zero is already loaded; unload it so the test controls plug events".to_string());
+    }
+
+    None
+}
+
+/// Skip the calling test with an explanation when the facility is unavailable
+macro_rules! require_virtual_usb {
+    () => {
+        if let Some(reason) = unavailable_reason() {
+            eprintln!("skipping hardware test: {}", reason);
+            return;
+        }
+    };
+}
+
+fn modprobe(args: &[&str]) {
+    let status = Command::new("modprobe")
+        .args(args)
+        .status()
+        .expect("failed to run modprobe");
+    assert!(status.success(), "modprobe {:?} failed (are you root?)", args);
+}
+
+fn is_gadget(device: &UsbDeviceInfo) -> bool {
+    (device.vendor_id, device.product_id) == GADGET_ZERO_ID
+}
+
+/// Unloads the gadget when dropped so a failing assertion doesn't leave it plugged in
+struct GadgetGuard;
+
+impl Drop for GadgetGuard {
+    fn drop(&mut self) {
+        let _ = Command::new("modprobe").args(["-r", "g_zero"]).status();
+    }
+}
+
+/// Poll `monitor_changes` until a change matching `predicate` appears
+async fn wait_for_change<F>(monitor: &mut UsbMonitor, predicate: F) -> UsbDeviceChange
+where
+    F: Fn(&UsbDeviceChange) -> bool,
+{
+    let deadline = Instant::now() + ENUMERATION_TIMEOUT;
+
+    while Instant::now() < deadline {
+        let changes = monitor.monitor_changes().await.expect("monitor_changes failed");
+        if let Some(change) = changes.into_iter().find(|change| predicate(change)) {
+            return change;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    panic!("no matching change within {:?}", ENUMERATION_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_virtual_device_is_enumerated_and_reported() {
+    require_virtual_usb!();
+
+    let mut monitor = UsbMonitor::new().expect("failed to create USB monitor");
+
+    // The first pass reports everything already present; use it as the baseline
+    monitor.monitor_changes().await.expect("baseline scan failed");
+    assert!(!monitor.get_connected_devices().unwrap().iter().any(is_gadget));
+
+    modprobe(&["g_zero"]);
+    let guard = GadgetGuard;
+
+    let connected = wait_for_change(&mut monitor, |change| {
+        matches!(change, UsbDeviceChange::Connected(device) if is_gadget(device))
+    }).await;
+    assert_eq!(connected.get_device_info().vendor_id, GADGET_ZERO_ID.0);
+    assert!(monitor.get_connected_devices().unwrap().iter().any(is_gadget));
+
+    drop(guard);
+
+    wait_for_change(&mut monitor, |change| {
+        matches!(change, UsbDeviceChange::Disconnected(device) if is_gadget(device))
+    }).await;
+    assert!(!monitor.get_connected_devices().unwrap().iter().any(is_gadget));
+}
@@ -0,0 +1,126 @@
+//! Single-instance guard for the GUI and the CLI monitor.
+//!
+//! The lock is a file holding the owner's PID next to the configuration file. It is removed
+//! when the guard is dropped; a lock left behind by a crashed instance is detected by checking
+//! whether its PID is still running, and reclaimed.
+
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const LOCK_FILE_NAME: &str = "ironwatch.lock";
+
+/// Held for as long as this process is the running instance
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Take the lock in `dir`, reclaiming it if its owner is no longer running
+    pub fn acquire(dir: &Path) -> Result<Self> {
+        let path = dir.join(LOCK_FILE_NAME);
+
+        // Two attempts: the second follows removal of a stale lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())
+                        .with_context(|| format!("Failed to write lock file: {}", path.display()))?;
+                    debug!("Acquired instance lock {}", path.display());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let owner = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|content| content.trim().parse::<u32>().ok());
+
+                    if let Some(pid) = owner.filter(|pid| process_running(*pid)) {
+                        bail!(
+                            "IronWatch is already running (PID {}). Close it first, or remove {} if that is wrong.",
+                            pid,
+                            path.display()
+                        );
+                    }
+
+                    warn!("Reclaiming stale instance lock {}", path.display());
+                    fs::remove_file(&path)
+                        .with_context(|| format!("Failed to remove stale lock file: {}", path.display()))?;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create lock file: {}", path.display()));
+                }
+            }
+        }
+
+        bail!("Could not acquire instance lock {}", path.display())
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("Failed to release instance lock {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Whether a process with this PID exists; our own PID counts as running
+fn process_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    // Not a valid PID; `kill -0` would read one above `i32::MAX` as a negative process group
+    if pid == 0 || pid > i32::MAX as u32 {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_acquire_fails_until_released_and_stale_locks_are_reclaimed() {
+        let dir = std::env::temp_dir().join(format!("ironwatch-lock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let lock = InstanceLock::acquire(&dir).unwrap();
+        assert!(InstanceLock::acquire(&dir).is_err());
+        drop(lock);
+        assert!(!dir.join(LOCK_FILE_NAME).exists());
+
+        // A PID that cannot be running, as left behind by a crash
+        fs::write(dir.join(LOCK_FILE_NAME), "4294967295").unwrap();
+        let reclaimed = InstanceLock::acquire(&dir).unwrap();
+        let owner = fs::read_to_string(dir.join(LOCK_FILE_NAME)).unwrap();
+        drop(reclaimed);
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(owner, std::process::id().to_string());
+    }
+}
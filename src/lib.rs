@@ -0,0 +1,28 @@
+//! IronWatch's USB monitoring core as a library.
+//!
+//! The `IronWatch` binary builds its GUI and CLI on these modules. To watch devices from another
+//! program, start a [`Monitor`]; the modules below expose device records, configuration and the
+//! backend trait for anything more specialised.
+
+pub mod usb_monitor;
+pub mod usb_backend;
+pub mod audit;
+pub mod rule_expr;
+#[cfg(windows)]
+mod windows_names;
+#[cfg(all(target_os = "linux", feature = "udev"))]
+mod udev_enrichment;
+pub mod config;
+pub mod error;
+pub mod communication;
+pub mod monitoring_service;
+pub mod monitor;
+pub mod logging;
+pub mod snapshot;
+pub mod instance_lock;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+pub use config::Config;
+pub use monitor::{Monitor, MonitorHandle};
+pub use usb_monitor::{UsbDeviceChange, UsbDeviceInfo};
@@ -0,0 +1,159 @@
+use crate::config::LoggingConfig;
+use anyhow::{Context, Result};
+use log::debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Number of rotated files (`ironwatch.log.1` … `.N`) kept next to the active log
+const ROTATED_FILES_KEPT: usize = 5;
+
+/// Initialize logging to stderr and, when `logging.file_logging` is set, to a rotating file.
+///
+/// With `logging.format = "json"` every record is written as one JSON object per line.
+pub fn init_logging(log_level: &str, logging_config: Option<&LoggingConfig>) -> Result<()> {
+    let level = match log_level {
+        "error" => log::LevelFilter::Error,
+        "warn" => log::LevelFilter::Warn,
+        "info" => log::LevelFilter::Info,
+        "debug" => log::LevelFilter::Debug,
+        "trace" => log::LevelFilter::Trace,
+        _ => log::LevelFilter::Info,
+    };
+
+    let mut builder = env_logger::Builder::from_default_env();
+    builder
+        .filter_level(level)
+        .format_timestamp_secs();
+
+    if logging_config.is_some_and(|config| config.format == "json") {
+        builder.format(|buf, record| {
+            let entry = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", entry)
+        });
+    }
+
+    let file_config = logging_config.filter(|config| config.file_logging);
+    if let Some(config) = file_config {
+        let path = match &config.log_file_path {
+            Some(path) => path.clone(),
+            None => default_log_path()?,
+        };
+
+        let max_bytes = config.max_log_file_size_mb.saturating_mul(1024 * 1024);
+        let file = RotatingFileWriter::open(path, max_bytes, config.rotate_logs)?;
+
+        builder.target(env_logger::Target::Pipe(Box::new(TeeWriter {
+            console: io::stderr(),
+            file,
+        })));
+    }
+
+    builder.init();
+
+    debug!("Logging initialized at level: {}", log_level);
+    Ok(())
+}
+
+/// Default log file location when `logging.log_file_path` is not set
+fn default_log_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .context("Could not determine data directory for log file")?;
+
+    Ok(data_dir.join("ironwatch").join("ironwatch.log"))
+}
+
+/// Writes every log record to both stderr and the log file
+struct TeeWriter {
+    console: io::Stderr,
+    file: RotatingFileWriter,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A failing console must not stop file logging, and vice versa
+        let _ = self.console.write_all(buf);
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = self.console.flush();
+        self.file.flush()
+    }
+}
+
+/// Append-only log file that rolls over to `<path>.1`, `<path>.2`, … once it exceeds `max_bytes`
+struct RotatingFileWriter {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    rotate: bool,
+}
+
+impl RotatingFileWriter {
+    fn open(path: PathBuf, max_bytes: u64, rotate: bool) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory: {}", parent.display()))?;
+        }
+
+        let file = Self::open_file(&path)
+            .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self { path, file, written, max_bytes, rotate })
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Shift `<path>.N-1` → `<path>.N`, …, `<path>` → `<path>.1`, then start a fresh file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let _ = fs::remove_file(self.rotated_path(ROTATED_FILES_KEPT));
+        for index in (1..ROTATED_FILES_KEPT).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = Self::open_file(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.rotate && self.max_bytes > 0 && self.written > 0
+            && self.written + buf.len() as u64 > self.max_bytes
+        {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
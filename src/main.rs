@@ -1,22 +1,31 @@
-mod usb_monitor;
-mod config;
-mod error;
-mod communication;
-mod monitoring_service;
+// The monitoring core lives in the library so other programs can embed it
+use ironwatch::*;
 
 #[cfg(feature = "gui")]
 mod gui_simple;
 #[cfg(feature = "gui")]
 mod system_tray;
+#[cfg(feature = "gui")]
+mod rule_editor;
 
 #[cfg(feature = "cli")]
 mod cli;
 #[cfg(feature = "cli")]
 mod output;
+#[cfg(feature = "cli")]
+mod replay;
+#[cfg(feature = "cli")]
+mod benchmark;
+#[cfg(feature = "cli")]
+mod self_test;
+#[cfg(feature = "tui")]
+mod tui;
+
+#[cfg(all(test, feature = "hardware-tests"))]
+mod hardware_tests;
 
 use anyhow::{Result, Context};
-use env_logger;
-use log::{info, error, debug, warn};
+use log::{info, error, warn};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::signal;
@@ -26,19 +35,31 @@ use eframe::egui;
 
 #[cfg(feature = "cli")]
 use {
-    cli::{build_cli, parse_args, print_banner, CliConfig},
-    usb_monitor::{UsbMonitor, UsbDeviceChange},
+    cli::{build_cli, parse_args, print_banner, CliConfig, FileRotation, ListSort, StatsSort},
+    usb_monitor::{UsbMonitor, UsbDeviceChange, UsbDeviceInfo},
     config::ConfigManager,
     output::OutputManager,
-    std::sync::Arc,
     tokio::sync::Mutex,
-    tokio::signal,
 };
 
+/// Exit status of `list --fail-on-blocked` when a connected device would be blocked
+#[cfg(feature = "cli")]
+const EXIT_BLOCKED_DEVICES: i32 = 3;
+
+/// Longest wait for background tasks on exit; one stuck in a libusb call is abandoned after this
+#[cfg(any(feature = "gui", feature = "cli"))]
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    init_logging("info")?;
+    // Initialize logging (the CLI configures it itself once arguments are parsed)
+    #[cfg(feature = "gui")]
+    {
+        let logging_config = config::ConfigManager::new(None)
+            .ok()
+            .map(|manager| manager.get_config().logging.clone());
+        logging::init_logging("info", logging_config.as_ref())?;
+    }
     
     // Setup graceful shutdown
     let shutdown_flag = Arc::new(AtomicBool::new(false));
@@ -86,11 +107,16 @@ async fn launch_gui_with_shutdown(shutdown_flag: Arc<AtomicBool>) -> Result<()>
     
     info!("Starting IronWatch GUI...");
     
+    // Only one instance may poll USB and own a tray icon; released when this function returns
+    let config_manager = config::ConfigManager::new(None)
+        .context("Failed to initialize configuration manager")?;
+    let _instance_lock = acquire_instance_lock(&config_manager)?;
+    
     // Create communication hub
     let (communication_hub, communication_receiver) = CommunicationHub::new();
     
     // Start monitoring service in background
-    let monitoring_handle = start_monitoring_service_with_recovery(communication_receiver, 3)
+    let mut monitoring_handle = start_monitoring_service_with_recovery(communication_receiver, 3)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to start monitoring service: {}", e))?;
     
@@ -120,12 +146,44 @@ async fn launch_gui_with_shutdown(shutdown_flag: Arc<AtomicBool>) -> Result<()>
     info!("GUI closed, shutting down monitoring service...");
     let _ = communication_hub_shutdown.shutdown();
     
-    // Wait for monitoring service to finish
-    let _ = monitoring_handle.await;
+    // Wait for monitoring service to finish, but don't let a stuck one keep the process alive
+    join_with_timeout(&mut monitoring_handle, "Monitoring service").await;
     
     result
 }
 
+/// Wait up to `SHUTDOWN_TIMEOUT` for `task` to finish, then abort it
+#[cfg(any(feature = "gui", feature = "cli"))]
+async fn join_with_timeout<T>(task: &mut tokio::task::JoinHandle<T>, name: &str) -> Option<T> {
+    match tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut *task).await {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(e)) => {
+            error!("{} failed: {}", name, e);
+            None
+        }
+        Err(_) => {
+            warn!("{} did not stop within {}s; abandoning it", name, SHUTDOWN_TIMEOUT.as_secs());
+            task.abort();
+            None
+        }
+    }
+}
+
+/// Take the single-instance lock next to the configuration file, or in the default
+/// configuration directory when the configuration came from stdin
+fn acquire_instance_lock(config_manager: &config::ConfigManager) -> Result<instance_lock::InstanceLock> {
+    let config_path = if config_manager.is_from_stdin() {
+        config::ConfigManager::get_default_config_path()?
+    } else {
+        config_manager.get_config_path().clone()
+    };
+    let dir = config_path
+        .parent()
+        .map(std::path::Path::to_path_buf)
+        .unwrap_or_default();
+    instance_lock::InstanceLock::acquire(&dir)
+}
+
 #[cfg(feature = "gui")]
 fn load_icon() -> egui::IconData {
     // let icon_data = include_bytes!("../assets/icon.png"); // Uncomment for actual icon file
@@ -166,30 +224,105 @@ async fn launch_cli_with_shutdown(shutdown_flag: Arc<AtomicBool>) -> Result<()>
     let matches = build_cli().get_matches();
     let cli_config = parse_args(&matches)?;
 
-    // Initialize logging
-    init_logging(&cli_config.log_level)?;
-
-    // Print banner
-    print_banner();
-
     // Load configuration
-    let config_manager = ConfigManager::new(cli_config.config_file.clone())
+    let config_path = cli_config.config_file.clone()
+        .or_else(|| cli_config.config_dir.as_deref().map(ConfigManager::config_path_in));
+
+    // Answered before loading, so it works even when the file doesn't parse
+    if cli_config.print_config_path {
+        let path = ConfigManager::resolve_config_path(config_path)?;
+        println!("{}", path.display());
+        if path.exists() {
+            eprintln!("The file exists.");
+        } else {
+            eprintln!("The file does not exist yet; defaults are used until it is saved.");
+        }
+        return Ok(());
+    }
+
+    let mut config_manager = ConfigManager::new(config_path)
         .context("Failed to initialize configuration manager")?;
+
+    if cli_config.no_descriptor_strings {
+        config_manager.override_value("monitoring.descriptor_strings", "false")?;
+    }
+
+    if let Some(ref which) = cli_config.raw_descriptors {
+        config_manager.override_value("monitoring.raw_descriptors", which)?;
+    }
+
+    if let Some(level) = cli_config.usb_debug {
+        config_manager.override_value("monitoring.usb_debug", &level.to_string())?;
+    }
+
+    if let Some(max_devices) = cli_config.max_devices {
+        config_manager.override_value("monitoring.max_devices", &max_devices.to_string())?;
+    }
+
+    if let Some(ref broker) = cli_config.mqtt_broker {
+        config_manager.override_value("mqtt.broker", broker)?;
+    }
+
+    // Initialize logging (file logging comes from the configuration)
+    logging::init_logging(&cli_config.log_level, Some(&config_manager.get_config().logging))?;
+
+    // Print banner
+    if !cli_config.quiet {
+        print_banner();
+    }
     
     // Validate configuration
-    config_manager.validate()
+    config_manager.validate(cli_config.strict)
         .context("Configuration validation failed")?;
 
     // Handle subcommands with shutdown support
     match matches.subcommand() {
         Some(("monitor", _)) => {
+            let _instance_lock = acquire_instance_lock(&config_manager)?;
+            let mut cli_config = cli_config;
+            if config_manager.get_config().monitoring.auto_start && !cli_config.once {
+                cli_config.continuous = true;
+            }
+            run_monitoring_mode_with_shutdown(cli_config, config_manager, shutdown_flag).await?;
+        }
+        Some(("unblock-all", _)) => {
+            let _instance_lock = acquire_instance_lock(&config_manager)?;
+            config_manager.set_blocking_suspended(true);
+            eprintln!("⚠ Device blocking is suspended: all devices are allowed until IronWatch restarts.");
+            let mut cli_config = cli_config;
+            cli_config.continuous = true;
             run_monitoring_mode_with_shutdown(cli_config, config_manager, shutdown_flag).await?;
         }
         Some(("list", _)) => {
             run_list_mode(cli_config, config_manager).await?;
         }
+        Some(("get", _)) => {
+            run_get_mode(cli_config, config_manager).await?;
+        }
+        Some(("check", _)) => {
+            run_check_mode(cli_config, config_manager)?;
+        }
+        Some(("tag", _)) => {
+            run_tag_mode(cli_config, config_manager)?;
+        }
+        Some(("self-test", _)) => {
+            run_self_test_mode();
+        }
+        Some(("stats", _)) => {
+            run_stats_mode(cli_config, config_manager, shutdown_flag).await?;
+        }
+        Some(("replay", _)) => {
+            run_replay_mode(cli_config, config_manager, shutdown_flag).await?;
+        }
+        Some(("benchmark", _)) => {
+            run_benchmark_mode(cli_config, config_manager).await?;
+        }
+        #[cfg(feature = "tui")]
+        Some(("tui", _)) => {
+            tui::run_tui(shutdown_flag).await?;
+        }
         Some(("config", sub_matches)) => {
-            run_config_mode(sub_matches, config_manager).await?;
+            run_config_mode(sub_matches, config_manager, cli_config.strict).await?;
         }
         _ => {
             // Default behavior - show help
@@ -201,101 +334,70 @@ async fn launch_cli_with_shutdown(shutdown_flag: Arc<AtomicBool>) -> Result<()>
     Ok(())
 }
 
-/// Initialize logging based on configuration
-fn init_logging(log_level: &str) -> Result<()> {
-    let level = match log_level {
-        "error" => log::LevelFilter::Error,
-        "warn" => log::LevelFilter::Warn,
-        "info" => log::LevelFilter::Info,
-        "debug" => log::LevelFilter::Debug,
-        "trace" => log::LevelFilter::Trace,
-        _ => log::LevelFilter::Info,
+/// Build the output manager from `--sink` flags, or console plus optional `--output` file by default
+#[cfg(feature = "cli")]
+fn create_output_manager(cli_config: &CliConfig, config_manager: &ConfigManager) -> Result<OutputManager> {
+    let output_config = &config_manager.get_config().output;
+    let rotation = cli_config.rotate
+        .or_else(|| FileRotation::from_name(&output_config.rotate))
+        .unwrap_or(FileRotation::None);
+    let latest_link = cli_config.latest_link || output_config.latest_link;
+
+    let output_manager = if cli_config.sinks.is_empty() {
+        OutputManager::new(
+            cli_config.output_format.clone(),
+            cli_config.output_file.clone(),
+            output_config.color_output,
+            output_config.include_metadata,
+            rotation,
+            latest_link,
+            cli_config.file_mode,
+        )
+    } else {
+        OutputManager::from_sink_specs(
+            cli_config.output_format.clone(),
+            &cli_config.sinks,
+            output_config.color_output,
+            output_config.include_metadata,
+            rotation,
+            latest_link,
+            cli_config.file_mode,
+        )
     };
 
-    env_logger::Builder::from_default_env()
-        .filter_level(level)
-        .format_timestamp_secs()
-        .init();
-
-    debug!("Logging initialized at level: {}", log_level);
-    Ok(())
+    let mut output_manager = output_manager.context("Failed to create output manager")?;
+    output_manager.set_quiet(cli_config.quiet);
+    output_manager.set_columns(cli_config.columns.clone());
+    output_manager.set_block_message(config_manager.get_config().device_rules.block_message());
+    if output_config.include_tags {
+        output_manager.set_device_tags(config_manager.get_config().device_tags.clone());
+    }
+    Ok(output_manager)
 }
 
 #[cfg(feature = "cli")]
 async fn run_monitoring_mode_with_shutdown(cli_config: CliConfig, config_manager: ConfigManager, shutdown_flag: Arc<AtomicBool>) -> Result<()> {
-    use usb_monitor::{UsbMonitor, UsbDeviceChange};
-    use config::ConfigManager;
-    use output::OutputManager;
-    use std::sync::Arc;
-    use tokio::sync::Mutex;
-    use tokio::signal;
+    use usb_monitor::UsbMonitor;
     info!("Starting USB device monitoring mode");
 
     // Create USB monitor
-    let mut usb_monitor = UsbMonitor::new()
+    let mut usb_monitor = UsbMonitor::from_config(config_manager.get_config())
         .context("Failed to create USB monitor")?;
 
     // Set device filter if provided
     usb_monitor.set_filter(cli_config.device_filter.clone());
+    if let Some(ref classes) = cli_config.device_classes {
+        usb_monitor.set_allowed_classes(Some(classes.clone()));
+    }
+    usb_monitor.set_max_log_entries(config_manager.get_config().output.max_log_entries);
+    usb_monitor.set_startup_grace_period(cli_config.watch_new_only);
+    usb_monitor.set_snapshot_interval(cli_config.snapshot_interval);
 
     // Create output manager
-    let mut output_manager = OutputManager::new(
-        cli_config.output_format,
-        cli_config.output_file,
-        config_manager.get_config().output.color_output,
-        config_manager.get_config().output.include_metadata,
-    ).context("Failed to create output manager")?;
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
 
     if cli_config.continuous {
-        // Continuous monitoring mode
-        info!("Running in continuous monitoring mode");
-        
-        // Get initial device count for status display
-        let initial_devices = usb_monitor.get_connected_devices()
-            .context("Failed to get initial device list")?;
-        
-        output_manager.display_monitoring_status(
-            initial_devices.len(),
-            cli_config.device_filter.as_deref(),
-        )?;
-
-        // Create shared output manager for the callback
-        let output_manager_arc = Arc::new(Mutex::new(output_manager));
-        let output_manager_clone = output_manager_arc.clone();
-
-        // Start monitoring with callback
-        let monitoring_task = tokio::spawn(async move {
-            usb_monitor.start_monitoring(move |changes: Vec<UsbDeviceChange>| {
-                let output_manager = output_manager_clone.clone();
-                tokio::spawn(async move {
-                    let mut manager = output_manager.lock().await;
-                    if let Err(e) = manager.display_changes(&changes) {
-                        error!("Failed to display changes: {}", e);
-                    }
-                });
-                Ok(())
-            }).await
-        });
-
-        // Handle Ctrl+C gracefully and check shutdown flag
-        tokio::select! {
-            result = monitoring_task => {
-                match result {
-                    Ok(Ok(_)) => info!("Monitoring completed successfully"),
-                    Ok(Err(e)) => error!("Monitoring error: {}", e),
-                    Err(e) => error!("Task error: {}", e),
-                }
-            }
-            _ = signal::ctrl_c() => {
-                info!("Received interrupt signal, shutting down gracefully...");
-                shutdown_flag.store(true, Ordering::Relaxed);
-            }
-        }
-        
-        // Wait a bit for cleanup
-        while !shutdown_flag.load(Ordering::Relaxed) {
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        }
+        run_continuous_monitoring(usb_monitor, output_manager, &cli_config, config_manager.get_config(), shutdown_flag).await?;
     } else {
         // Single scan mode
         info!("Running single device scan");
@@ -312,34 +414,344 @@ async fn run_monitoring_mode_with_shutdown(cli_config: CliConfig, config_manager
     Ok(())
 }
 
+/// Watch for device changes until the shutdown flag is set or Ctrl+C, then display the session
+/// summary and close the output
+#[cfg(feature = "cli")]
+async fn run_continuous_monitoring(
+    mut usb_monitor: UsbMonitor,
+    mut output_manager: OutputManager,
+    cli_config: &CliConfig,
+    config: &Config,
+    shutdown_flag: Arc<AtomicBool>,
+) -> Result<()> {
+    info!("Running in continuous monitoring mode");
+    
+    // Get initial device count for status display
+    let initial_devices = usb_monitor.get_connected_devices()
+        .context("Failed to get initial device list")?;
+    
+    output_manager.display_monitoring_status(
+        initial_devices.len(),
+        cli_config.device_filter.as_deref(),
+    )?;
+
+    // Create shared output manager for the callback
+    let output_manager_arc = Arc::new(Mutex::new(output_manager));
+
+    #[cfg(feature = "mqtt")]
+    let mqtt_publisher = mqtt::MqttPublisher::from_config(&config.mqtt)?;
+    #[cfg(not(feature = "mqtt"))]
+    if config.mqtt.broker.is_some() {
+        warn!("mqtt.broker is set, but this build has no MQTT support (enable the mqtt feature)");
+    }
+
+    // A single writer displays changes in order; on shutdown it drains before the output is closed
+    let (change_sender, mut change_receiver) = tokio::sync::mpsc::unbounded_channel::<Vec<UsbDeviceChange>>();
+    let writer_output = output_manager_arc.clone();
+    let mut display_task = tokio::spawn(async move {
+        while let Some(changes) = change_receiver.recv().await {
+            #[cfg(feature = "mqtt")]
+            if let Some(ref publisher) = mqtt_publisher {
+                publisher.publish_changes(&changes);
+            }
+
+            if let Err(e) = writer_output.lock().await.display_changes(&changes) {
+                error!("Failed to display changes: {}", e);
+            }
+        }
+    });
+
+    // Let supervisors tell an idle monitor from a hung one
+    if let Some(interval) = cli_config.heartbeat {
+        let heartbeat_output = output_manager_arc.clone();
+        usb_monitor.set_heartbeat(interval, move |device_count| {
+            let output_manager = heartbeat_output.clone();
+            tokio::spawn(async move {
+                if let Err(e) = output_manager.lock().await.display_heartbeat(device_count) {
+                    error!("Failed to display heartbeat: {}", e);
+                }
+            });
+            Ok(())
+        });
+    }
+
+    // Start monitoring with callback; the task hands the monitor back when it stops
+    let task_shutdown_flag = shutdown_flag.clone();
+    let mut monitoring_task = tokio::spawn(async move {
+        let result = usb_monitor.start_monitoring_until(move |changes: Vec<UsbDeviceChange>| {
+            if change_sender.send(changes).is_err() {
+                error!("Change display task has stopped");
+            }
+            Ok(())
+        }, task_shutdown_flag).await;
+        (usb_monitor, result)
+    });
+
+    // The task ends on its own once the shutdown flag is set; Ctrl+C sets it and joins the task
+    let finished_monitor = tokio::select! {
+        result = &mut monitoring_task => match result {
+            Ok((monitor, Ok(_))) => {
+                info!("Monitoring completed successfully");
+                Some(monitor)
+            }
+            Ok((monitor, Err(e))) => {
+                error!("Monitoring error: {}", e);
+                Some(monitor)
+            }
+            Err(e) => {
+                error!("Task error: {}", e);
+                None
+            }
+        },
+        _ = signal::ctrl_c() => {
+            info!("Received interrupt signal, shutting down gracefully...");
+            shutdown_flag.store(true, Ordering::Relaxed);
+            join_with_timeout(&mut monitoring_task, "Monitoring task").await
+                .map(|(monitor, _)| monitor)
+        }
+    };
+
+    // The callback (and with it the sender) is gone once monitoring stops, so this finishes
+    // after every detected change has been displayed
+    join_with_timeout(&mut display_task, "Change display task").await;
+
+    // Recap the session on the console and in any output file
+    let mut output_manager = output_manager_arc.lock().await;
+    if let Some(monitor) = finished_monitor {
+        let summary = monitor.get_session_summary();
+        output_manager
+            .display_summary(&summary)
+            .context("Failed to display session summary")?;
+    }
+    output_manager.flush_and_close()
+        .context("Failed to flush output")?;
+
+    Ok(())
+}
+
 #[cfg(feature = "cli")]
 async fn run_list_mode(cli_config: CliConfig, config_manager: ConfigManager) -> Result<()> {
     use usb_monitor::UsbMonitor;
-    use config::ConfigManager;
-    use output::OutputManager;
     info!("Listing USB devices");
 
     // Create USB monitor
-    let usb_monitor = UsbMonitor::new()
+    let mut usb_monitor = UsbMonitor::from_config(config_manager.get_config())
         .context("Failed to create USB monitor")?;
+    if let Some(ref classes) = cli_config.device_classes {
+        usb_monitor.set_allowed_classes(Some(classes.clone()));
+    }
+
+    // Create output manager
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
 
-    // Get connected devices
-    let devices = usb_monitor.get_connected_devices()
+    // Device rules are evaluated during enumeration so admins can preview their effect
+    usb_monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config_manager)));
+
+    // Get connected devices, with blocked ones marked as such, and the decision for each
+    let mut listed = usb_monitor.get_connected_devices_with_policy().await
         .context("Failed to get device list")?;
 
-    // Create output manager
-    let mut output_manager = OutputManager::new(
-        cli_config.output_format,
-        cli_config.output_file,
-        config_manager.get_config().output.color_output,
-        config_manager.get_config().output.include_metadata,
-    ).context("Failed to create output manager")?;
+    // Enumeration order varies between runs; sort so two listings can be diffed
+    let by_location = |a: &UsbDeviceInfo, b: &UsbDeviceInfo| {
+        (a.bus_number, a.device_address).cmp(&(b.bus_number, b.device_address))
+    };
+    listed.sort_by(|(a, _), (b, _)| match cli_config.list_sort {
+        ListSort::Bus => by_location(a, b),
+        ListSort::Vendor => (a.vendor_id, a.product_id).cmp(&(b.vendor_id, b.product_id)).then_with(|| by_location(a, b)),
+        ListSort::Product => a.product.as_deref().map(str::to_lowercase).cmp(&b.product.as_deref().map(str::to_lowercase))
+            .then_with(|| by_location(a, b)),
+        ListSort::Class => a.device_class.cmp(&b.device_class).then_with(|| by_location(a, b)),
+    });
+    if cli_config.list_reverse {
+        listed.reverse();
+    }
+    let (devices, policies): (Vec<_>, Vec<_>) = listed.into_iter().unzip();
 
     // Display devices
-    output_manager.display_devices(&devices)
+    output_manager.display_devices_with_policy(&devices, &policies)
         .context("Failed to display devices")?;
 
-    info!("Listed {} USB devices", devices.len());
+    let blocked = policies.iter().filter(|p| p.blocked).count();
+    info!("Listed {} USB devices ({} would be blocked)", devices.len(), blocked);
+
+    if cli_config.fail_on_blocked && blocked > 0 {
+        error!("{} connected device(s) would be blocked by the active policy", blocked);
+        std::process::exit(EXIT_BLOCKED_DEVICES);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+async fn run_get_mode(cli_config: CliConfig, config_manager: ConfigManager) -> Result<()> {
+    let (vendor_id, product_id) = cli_config.device_id
+        .context("Device ID argument is required")?;
+    info!("Looking up USB device {:04x}:{:04x}", vendor_id, product_id);
+
+    let usb_monitor = UsbMonitor::from_config(config_manager.get_config())
+        .context("Failed to create USB monitor")?;
+
+    let matches: Vec<_> = usb_monitor.get_connected_devices()
+        .context("Failed to get device list")?
+        .into_iter()
+        .filter(|device| device.vendor_id == vendor_id && device.product_id == product_id)
+        .collect();
+
+    if matches.is_empty() {
+        anyhow::bail!("No connected device matches {:04x}:{:04x}", vendor_id, product_id);
+    }
+
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
+    output_manager.display_device_matches(&matches)
+        .context("Failed to display device")?;
+
+    Ok(())
+}
+
+/// Print each setup check and exit with status 1 if any failed
+#[cfg(feature = "cli")]
+fn run_self_test_mode() {
+    let checks = self_test::run_checks();
+    for check in &checks {
+        println!("[{}] {}: {}", if check.passed { " OK " } else { "FAIL" }, check.name, check.detail);
+        if let Some(remediation) = &check.remediation {
+            println!("       {}", remediation);
+        }
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed > 0 {
+        println!("{} of {} checks failed", failed, checks.len());
+        std::process::exit(1);
+    }
+    println!("All {} checks passed", checks.len());
+}
+
+/// Exit with status 1 unless the requested device is connected
+#[cfg(feature = "cli")]
+fn run_check_mode(cli_config: CliConfig, config_manager: ConfigManager) -> Result<()> {
+    let (vendor_id, product_id) = cli_config.device_id
+        .context("Device ID argument is required")?;
+
+    let usb_monitor = UsbMonitor::from_config(config_manager.get_config())
+        .context("Failed to create USB monitor")?;
+    let connected = usb_monitor.is_connected(vendor_id, product_id, cli_config.device_serial.as_deref());
+
+    if !cli_config.quiet {
+        println!("{:04x}:{:04x} is {}", vendor_id, product_id, if connected { "connected" } else { "not connected" });
+    }
+    if !connected {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Save a tag for a device, keyed by its serial-based fingerprint when the serial is known
+#[cfg(feature = "cli")]
+fn run_tag_mode(cli_config: CliConfig, mut config_manager: ConfigManager) -> Result<()> {
+    let (vendor_id, product_id) = cli_config.device_id
+        .context("Device ID argument is required")?;
+    let tag = cli_config.device_tag.unwrap_or_default();
+
+    let serial = match cli_config.device_serial {
+        Some(serial) => Some(serial),
+        None => {
+            // Tag the connected device itself rather than every device with its VID:PID
+            let usb_monitor = UsbMonitor::from_config(config_manager.get_config())
+                .context("Failed to create USB monitor")?;
+            let mut serials: Vec<String> = usb_monitor.get_connected_devices()
+                .context("Failed to get device list")?
+                .into_iter()
+                .filter(|device| device.vendor_id == vendor_id && device.product_id == product_id)
+                .filter_map(|device| device.serial_number.filter(|serial| !serial.is_empty()))
+                .collect();
+            serials.sort();
+            serials.dedup();
+
+            match serials.as_slice() {
+                [] => None,
+                [serial] => Some(serial.clone()),
+                _ => anyhow::bail!("{} connected {:04x}:{:04x} devices have serial numbers ({}); pick one with --serial",
+                    serials.len(), vendor_id, product_id, serials.join(", ")),
+            }
+        }
+    };
+
+    let fingerprint = usb_monitor::device_fingerprint(vendor_id, product_id, serial.as_deref());
+    config_manager.set_device_tag(&fingerprint, &tag);
+    config_manager.save()
+        .context("Failed to save configuration")?;
+
+    let device = match &serial {
+        Some(serial) => format!("{:04x}:{:04x} (serial {})", vendor_id, product_id, serial),
+        None => format!("{:04x}:{:04x}", vendor_id, product_id),
+    };
+    if tag.trim().is_empty() {
+        println!("Removed the tag from {}", device);
+    } else {
+        println!("Tagged {} as \"{}\"", device, tag.trim());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+async fn run_stats_mode(cli_config: CliConfig, config_manager: ConfigManager, shutdown_flag: Arc<AtomicBool>) -> Result<()> {
+    info!("Collecting device statistics for {}s", cli_config.stats_duration.as_secs());
+
+    let mut usb_monitor = UsbMonitor::from_config(config_manager.get_config())
+        .context("Failed to create USB monitor")?;
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
+    usb_monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config_manager)));
+
+    // The session ends after the requested duration or on Ctrl+C, whichever comes first
+    let duration = cli_config.stats_duration;
+    let timer_flag = shutdown_flag.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        timer_flag.store(true, Ordering::Relaxed);
+    });
+
+    usb_monitor.start_monitoring_until(|_| Ok(()), shutdown_flag).await
+        .context("Monitoring failed while collecting statistics")?;
+
+    let mut reports: Vec<_> = usb_monitor.get_statistics_report()
+        .into_iter()
+        .filter(|report| cli_config.device_id.is_none_or(|(vid, pid)| report.vendor_id == vid && report.product_id == pid))
+        .collect();
+
+    match cli_config.stats_sort {
+        StatsSort::Connections => reports.sort_by_key(|report| std::cmp::Reverse(report.statistics.total_connections)),
+        StatsSort::Duration => reports.sort_by_key(|report| std::cmp::Reverse(report.statistics.connection_duration)),
+        StatsSort::LastSeen => reports.sort_by_key(|report| std::cmp::Reverse(report.statistics.last_seen)),
+    }
+
+    output_manager.display_statistics(&reports, duration)
+        .context("Failed to display statistics")?;
+
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+async fn run_replay_mode(cli_config: CliConfig, config_manager: ConfigManager, shutdown_flag: Arc<AtomicBool>) -> Result<()> {
+    let path = cli_config.replay_file.clone()
+        .context("Recording file argument is required")?;
+
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
+    replay::run_replay(&path, cli_config.replay_speed, config_manager, &mut output_manager, &shutdown_flag).await
+}
+
+#[cfg(feature = "cli")]
+async fn run_benchmark_mode(cli_config: CliConfig, config_manager: ConfigManager) -> Result<()> {
+    info!("Benchmarking device enumeration for {}s", cli_config.benchmark_duration.as_secs());
+
+    let usb_monitor = UsbMonitor::from_config(config_manager.get_config())
+        .context("Failed to create USB monitor")?;
+    let mut output_manager = create_output_manager(&cli_config, &config_manager)?;
+
+    let report = benchmark::run(&usb_monitor, cli_config.benchmark_duration, config_manager.get_config().monitoring.descriptor_strings)?;
+
+    output_manager.display_benchmark(&report)
+        .context("Failed to display benchmark results")?;
+
     Ok(())
 }
 
@@ -347,18 +759,28 @@ async fn run_list_mode(cli_config: CliConfig, config_manager: ConfigManager) ->
 async fn run_config_mode(
     matches: &clap::ArgMatches,
     mut config_manager: ConfigManager,
+    strict: bool,
 ) -> Result<()> {
-    use config::ConfigManager;
     match matches.subcommand() {
-        Some(("show", _)) => {
+        Some(("show", sub_matches)) => {
             // Display current configuration
             let config_json = serde_json::to_string_pretty(config_manager.get_config())
                 .context("Failed to serialize configuration")?;
             
+            if sub_matches.get_flag("json") {
+                println!("{}", config_json);
+                return Ok(());
+            }
+            
             println!("Current configuration:");
             println!("{}", config_json);
             println!("\nConfiguration file: {}", config_manager.get_config_path().display());
         }
+        Some(("get", sub_matches)) => {
+            let key = sub_matches.get_one::<String>("key")
+                .context("Key argument is required")?;
+            println!("{}", config_manager.get_value(key)?);
+        }
         Some(("set", sub_matches)) => {
             // Set configuration value
             let key = sub_matches.get_one::<String>("key")
@@ -370,7 +792,7 @@ async fn run_config_mode(
                 .with_context(|| format!("Failed to set configuration value: {} = {}", key, value))?;
             
             // Validate and save
-            config_manager.validate()
+            config_manager.validate(strict)
                 .context("Configuration validation failed after update")?;
             
             config_manager.save()
@@ -386,3 +808,43 @@ async fn run_config_mode(
 
     Ok(())
 }
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use cli::OutputFormat;
+    use usb_backend::UsbBackend;
+
+    /// A system with no USB devices attached
+    struct EmptyBackend;
+
+    impl UsbBackend for EmptyBackend {
+        fn enumerate(&self) -> Result<Vec<UsbDeviceInfo>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_continuous_monitoring_returns_promptly_after_shutdown() {
+        let mut config = Config::default();
+        config.monitoring.poll_interval_ms = 10;
+        let mut usb_monitor = UsbMonitor::with_backend(Box::new(EmptyBackend));
+        usb_monitor.apply_config(&config).unwrap();
+        let mut output_manager = OutputManager::with_sinks(OutputFormat::Json, Vec::new(), false, false);
+        output_manager.set_quiet(true);
+        let cli_config = CliConfig { continuous: true, ..CliConfig::default() };
+
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+        let signal_flag = shutdown_flag.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            signal_flag.store(true, Ordering::Relaxed);
+        });
+
+        // Well under SHUTDOWN_TIMEOUT, so the monitoring task must have stopped on its own
+        let monitoring = run_continuous_monitoring(usb_monitor, output_manager, &cli_config, &config, shutdown_flag);
+        tokio::time::timeout(std::time::Duration::from_secs(2), monitoring).await
+            .expect("monitoring did not stop after shutdown was signalled")
+            .unwrap();
+    }
+}
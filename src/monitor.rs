@@ -0,0 +1,115 @@
+//! Library entry point for watching USB devices from another program.
+//!
+//! `Monitor` runs the same background service as the GUI and hands every device change to a
+//! closure, so callers don't have to wire up `CommunicationHub` channels themselves.
+
+use crate::communication::{CommunicationHub, MonitorEvent};
+use crate::config::{Config, ConfigManager};
+use crate::error::{GuiError, Result, UsbError};
+use crate::monitoring_service::MonitoringService;
+use crate::usb_backend::UsbBackend;
+use crate::usb_monitor::{UsbDeviceChange, UsbMonitor};
+use tokio::task::JoinHandle;
+
+/// Builder for a background USB monitor
+pub struct Monitor {
+    config: Config,
+    backend: Option<Box<dyn UsbBackend>>,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    /// A monitor of the system's USB devices with the default configuration
+    pub fn new() -> Self {
+        Self { config: Config::default(), backend: None }
+    }
+
+    /// Use `config` for device rules, filters and the poll interval; it is never written to disk
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Enumerate devices through `backend` instead of libusb, e.g. a scripted one in tests
+    pub fn backend(mut self, backend: Box<dyn UsbBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Start monitoring in the background and call `on_change` for every device change.
+    ///
+    /// Devices already attached are reported as connected on the first poll. Must be called
+    /// from within a Tokio runtime.
+    ///
+    /// ```no_run
+    /// # async fn run() -> ironwatch::error::Result<()> {
+    /// use ironwatch::{Monitor, UsbDeviceChange};
+    ///
+    /// let handle = Monitor::new().start(|change: UsbDeviceChange| {
+    ///     let device = change.get_device_info();
+    ///     println!("{} {:04x}:{:04x}", change.get_change_type(), device.vendor_id, device.product_id);
+    /// })?;
+    ///
+    /// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    /// handle.stop().await
+    /// # }
+    /// ```
+    pub fn start<F>(self, mut on_change: F) -> Result<MonitorHandle>
+    where
+        F: FnMut(UsbDeviceChange) + Send + 'static,
+    {
+        let usb_monitor = match self.backend {
+            Some(backend) => {
+                let mut usb_monitor = UsbMonitor::with_backend(backend);
+                usb_monitor.apply_config(&self.config)
+                    .map_err(|e| UsbError::initialization_failed(e.to_string()))?;
+                usb_monitor
+            }
+            None => UsbMonitor::from_config(&self.config)
+                .map_err(|e| UsbError::initialization_failed(e.to_string()))?,
+        };
+
+        let (hub, receiver) = CommunicationHub::new();
+        let mut service = MonitoringService::with_monitor(receiver, usb_monitor, ConfigManager::from_config(self.config));
+        let service = tokio::spawn(async move { service.run().await });
+        hub.start_monitoring()?;
+
+        let events = hub.clone();
+        let dispatcher = tokio::spawn(async move {
+            while let Some(event) = events.recv_event().await {
+                match event {
+                    MonitorEvent::DeviceChanged(change) => on_change(change),
+                    MonitorEvent::DevicesChanged(changes) => changes.into_iter().for_each(&mut on_change),
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(MonitorHandle { hub, service, dispatcher })
+    }
+}
+
+/// A running `Monitor`; dropping it leaves the service running until the runtime shuts down
+pub struct MonitorHandle {
+    hub: CommunicationHub,
+    service: JoinHandle<Result<()>>,
+    dispatcher: JoinHandle<()>,
+}
+
+impl MonitorHandle {
+    /// Stop the service and wait until every change it detected has been passed to the callback
+    pub async fn stop(self) -> Result<()> {
+        // Fails only when the service has already exited, which the join below reports
+        let _ = self.hub.shutdown();
+        let result = self.service.await
+            .map_err(|e| GuiError::communication_error(format!("Monitoring task failed: {}", e)))?;
+        // Ends once the service is gone and the remaining events are drained
+        let _ = self.dispatcher.await;
+        result
+    }
+}
@@ -1,8 +1,14 @@
-use crate::usb_monitor::{UsbMonitor, UsbDeviceChange};
+use crate::usb_monitor::UsbMonitor;
+use crate::config::{ConfigManager, DeviceRule};
+use crate::snapshot::SnapshotWriter;
 use crate::communication::{CommunicationReceiver, MonitorCommand, ShutdownCoordinator};
+#[cfg(feature = "mqtt")]
+use crate::mqtt::MqttPublisher;
 use crate::error::{Result, UsbError, IronWatchError, check_usb_permissions};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::{interval, sleep};
+use tokio::sync::RwLock;
+use tokio::time::{interval, interval_at, sleep};
 use log::{info, error, debug, warn};
 
 /// Background service that handles USB monitoring
@@ -13,6 +19,91 @@ pub struct MonitoringService {
     polling_interval: Duration,
     device_filter: Option<String>,
     is_monitoring: bool,
+    /// Periodic analytics snapshots (`monitoring.snapshot_interval_secs`)
+    snapshots: Option<(Duration, SnapshotWriter)>,
+    /// Device events mirrored to an MQTT broker (`mqtt.broker`)
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    /// Configuration shared with the USB monitor for device rules; replaced on reload
+    config_manager: Option<Arc<RwLock<ConfigManager>>>,
+    /// Whether the configuration file is reloaded on SIGHUP (or when it changes); off when embedded
+    reload_from_file: bool,
+}
+
+/// How often the configuration file is checked for changes where SIGHUP isn't available
+#[cfg(not(unix))]
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Fires when the configuration should be reloaded: on SIGHUP on Unix, and on other platforms
+/// when the configuration file's modification time changes
+struct ReloadTrigger {
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+    #[cfg(not(unix))]
+    watch_timer: tokio::time::Interval,
+    #[cfg(not(unix))]
+    modified: Option<std::time::SystemTime>,
+}
+
+impl ReloadTrigger {
+    #[cfg(unix)]
+    fn new() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+        
+        let hangup = signal(SignalKind::hangup())
+            .map_err(|e| warn!("SIGHUP configuration reload unavailable: {}", e))
+            .ok();
+        Self { hangup }
+    }
+    
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {
+            watch_timer: interval_at(tokio::time::Instant::now() + CONFIG_WATCH_INTERVAL, CONFIG_WATCH_INTERVAL),
+            modified: Self::config_modified(),
+        }
+    }
+    
+    #[cfg(not(unix))]
+    fn config_modified() -> Option<std::time::SystemTime> {
+        let path = ConfigManager::get_default_config_path().ok()?;
+        std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+    
+    /// Wait on `trigger`, or forever when reloading is off; cancel-safe like `wait`
+    async fn wait_on(trigger: &mut Option<ReloadTrigger>) {
+        match trigger {
+            Some(trigger) => trigger.wait().await,
+            None => std::future::pending().await,
+        }
+    }
+    
+    /// Wait for the next reload request; cancel-safe, so it can sit in a `select!` loop
+    #[cfg(unix)]
+    async fn wait(&mut self) {
+        match &mut self.hangup {
+            Some(hangup) => {
+                if hangup.recv().await.is_none() {
+                    // The signal stream is gone; stop polling it
+                    self.hangup = None;
+                    std::future::pending::<()>().await
+                }
+            }
+            None => std::future::pending().await,
+        }
+    }
+    
+    #[cfg(not(unix))]
+    async fn wait(&mut self) {
+        loop {
+            self.watch_timer.tick().await;
+            let modified = Self::config_modified();
+            if modified != self.modified {
+                self.modified = modified;
+                return;
+            }
+        }
+    }
 }
 
 impl MonitoringService {
@@ -25,9 +116,79 @@ impl MonitoringService {
             polling_interval: Duration::from_millis(500),
             device_filter: None,
             is_monitoring: false,
+            snapshots: Self::load_snapshot_settings(),
+            #[cfg(feature = "mqtt")]
+            mqtt: Self::load_mqtt_publisher(),
+            config_manager: None,
+            reload_from_file: true,
+        }
+    }
+    
+    /// Create a monitoring service around `monitor` and an in-memory configuration, for embedding.
+    ///
+    /// Unlike `new`, nothing is read from the configuration file and SIGHUP doesn't reload it.
+    pub fn with_monitor(communication: CommunicationReceiver, mut monitor: UsbMonitor, config_manager: ConfigManager) -> Self {
+        let config = config_manager.get_config();
+        let polling_interval = Duration::from_millis(config.monitoring.poll_interval_ms);
+        let snapshots = SnapshotWriter::from_config(&config.monitoring)
+            .unwrap_or_else(|e| {
+                warn!("Analytics snapshots disabled: {}", e);
+                None
+            });
+        #[cfg(feature = "mqtt")]
+        let mqtt = MqttPublisher::from_config(&config.mqtt)
+            .unwrap_or_else(|e| {
+                warn!("MQTT publishing disabled: {}", e);
+                None
+            });
+        
+        let config_manager = Arc::new(RwLock::new(config_manager));
+        monitor.set_config_manager(config_manager.clone());
+        
+        Self {
+            usb_monitor: Some(monitor),
+            communication,
+            shutdown_coordinator: ShutdownCoordinator::new(),
+            polling_interval,
+            device_filter: None,
+            is_monitoring: false,
+            snapshots,
+            #[cfg(feature = "mqtt")]
+            mqtt,
+            config_manager: Some(config_manager),
+            reload_from_file: false,
         }
     }
     
+    #[cfg(feature = "mqtt")]
+    fn load_mqtt_publisher() -> Option<MqttPublisher> {
+        let config_manager = ConfigManager::new(None)
+            .map_err(|e| warn!("Could not load configuration, MQTT publishing disabled: {}", e))
+            .ok()?;
+        
+        MqttPublisher::from_config(&config_manager.get_config().mqtt)
+            .unwrap_or_else(|e| {
+                warn!("MQTT publishing disabled: {}", e);
+                None
+            })
+    }
+    
+    fn load_snapshot_settings() -> Option<(Duration, SnapshotWriter)> {
+        let config_manager = match ConfigManager::new(None) {
+            Ok(config_manager) => config_manager,
+            Err(e) => {
+                warn!("Could not load configuration, analytics snapshots disabled: {}", e);
+                return None;
+            }
+        };
+        
+        SnapshotWriter::from_config(&config_manager.get_config().monitoring)
+            .unwrap_or_else(|e| {
+                warn!("Analytics snapshots disabled: {}", e);
+                None
+            })
+    }
+    
     /// Initialize the USB monitor with graceful error handling
     async fn initialize_usb_monitor(&mut self) -> Result<()> {
         // Check permissions first
@@ -44,11 +205,25 @@ impl MonitoringService {
             }
         }
         
-        // Try to create USB monitor
-        match UsbMonitor::new() {
+        let config_manager = ConfigManager::new(None)
+            .map_err(|e| warn!("Could not load configuration, device rules disabled: {}", e))
+            .ok();
+        
+        // Try to create USB monitor, with the configured backend and monitoring settings when there are any
+        let monitor = match &config_manager {
+            Some(config_manager) => UsbMonitor::from_config(config_manager.get_config()),
+            None => UsbMonitor::new(),
+        };
+        match monitor {
             Ok(mut monitor) => {
                 // Set filter if configured
                 monitor.set_filter(self.device_filter.clone());
+                if let Some(config_manager) = config_manager {
+                    self.polling_interval = Duration::from_millis(config_manager.get_config().monitoring.poll_interval_ms);
+                    let config_manager = Arc::new(RwLock::new(config_manager));
+                    monitor.set_config_manager(config_manager.clone());
+                    self.config_manager = Some(config_manager);
+                }
                 self.usb_monitor = Some(monitor);
                 info!("USB monitor initialized successfully");
                 Ok(())
@@ -66,14 +241,21 @@ impl MonitoringService {
     pub async fn run(&mut self) -> Result<()> {
         info!("Starting monitoring service");
         
-        // Try to initialize USB monitor
-        if let Err(e) = self.initialize_usb_monitor().await {
-            warn!("USB monitor initialization failed, running in degraded mode: {}", e);
-            // Continue running to handle commands, but USB functionality will be limited
+        // Try to initialize USB monitor, unless one was given
+        if self.usb_monitor.is_none() {
+            if let Err(e) = self.initialize_usb_monitor().await {
+                warn!("USB monitor initialization failed, running in degraded mode: {}", e);
+                // Continue running to handle commands, but USB functionality will be limited
+                self.communication.send_usb_unavailable(&e.to_string())?;
+            }
         }
         
         // Main service loop
         let mut poll_timer = interval(self.polling_interval);
+        let mut reload_trigger = self.reload_from_file.then(ReloadTrigger::new);
+        // The first snapshot is taken one interval in, once there is something to record
+        let snapshot_period = self.snapshots.as_ref().map_or(Duration::from_secs(3600), |(period, _)| *period);
+        let mut snapshot_timer = interval_at(tokio::time::Instant::now() + snapshot_period, snapshot_period);
         
         loop {
             tokio::select! {
@@ -105,6 +287,21 @@ impl MonitoringService {
                         // Don't break on monitoring errors, just log and continue
                     }
                 }
+                
+                // Periodic analytics snapshot, if configured
+                _ = snapshot_timer.tick(), if self.snapshots.is_some() => {
+                    self.write_snapshot();
+                }
+                
+                // SIGHUP, or a changed configuration file where there is no SIGHUP
+                _ = ReloadTrigger::wait_on(&mut reload_trigger) => {
+                    self.reload_config().await;
+                }
+            }
+            
+            // A reload or SetPollingInterval may have changed the interval
+            if poll_timer.period() != self.polling_interval {
+                poll_timer = interval(self.polling_interval);
             }
         }
         
@@ -128,12 +325,27 @@ impl MonitoringService {
             MonitorCommand::RefreshDevices => {
                 self.refresh_devices().await?;
             }
+            MonitorCommand::RefreshAnalytics => {
+                self.send_analytics()?;
+            }
             MonitorCommand::SetFilter(filter) => {
                 self.set_filter(filter).await?;
             }
             MonitorCommand::SetPollingInterval(interval) => {
                 self.set_polling_interval(interval).await?;
             }
+            MonitorCommand::AddBlacklistRule(rule) => {
+                self.add_rule(rule, true).await?;
+            }
+            MonitorCommand::AddWhitelistRule(rule) => {
+                self.add_rule(rule, false).await?;
+            }
+            MonitorCommand::SetBlockingSuspended(suspended) => {
+                if let Some(ref monitor) = self.usb_monitor {
+                    monitor.set_blocking_suspended(suspended).await;
+                }
+                self.communication.send_blocking_suspended(suspended)?;
+            }
             MonitorCommand::Shutdown => {
                 info!("Received shutdown command");
                 self.shutdown_coordinator.signal_shutdown();
@@ -152,9 +364,7 @@ impl MonitoringService {
         
         // Ensure USB monitor is initialized
         if self.usb_monitor.is_none() {
-            if let Err(e) = self.initialize_usb_monitor().await {
-                return Err(e);
-            }
+            self.initialize_usb_monitor().await?;
         }
         
         self.is_monitoring = true;
@@ -183,10 +393,11 @@ impl MonitoringService {
     /// Refresh the device list
     async fn refresh_devices(&mut self) -> Result<()> {
         if let Some(ref monitor) = self.usb_monitor {
-            match monitor.get_connected_devices() {
+            match monitor.get_connected_devices_async().await {
                 Ok(devices) => {
                     debug!("Found {} USB devices", devices.len());
                     self.communication.send_devices_updated(devices)?;
+                    self.send_analytics()?;
                 }
                 Err(e) => {
                     let error_msg = format!("Failed to get device list: {}", e);
@@ -204,6 +415,81 @@ impl MonitoringService {
         Ok(())
     }
     
+    /// Re-read the configuration file and apply its device rules and poll interval.
+    ///
+    /// A file that fails to load or validate is logged and the running configuration is kept.
+    async fn reload_config(&mut self) {
+        let reloaded = ConfigManager::new(None).and_then(|config_manager| {
+            config_manager.validate(false)?;
+            Ok(config_manager)
+        });
+        
+        let mut config_manager = match reloaded {
+            Ok(config_manager) => config_manager,
+            Err(e) => {
+                error!("Configuration reload failed, keeping the previous configuration: {:#}", e);
+                return;
+            }
+        };
+        
+        self.polling_interval = Duration::from_millis(config_manager.get_config().monitoring.poll_interval_ms);
+        if let Some(ref mut monitor) = self.usb_monitor {
+            // Backend settings such as descriptor strings are fixed by `UsbMonitor::from_config` at startup
+            if let Err(e) = monitor.apply_config(config_manager.get_config()) {
+                warn!("Could not apply the reloaded monitoring settings: {}", e);
+            }
+        }
+        let path = config_manager.get_config_path().display().to_string();
+        let rules = (
+            config_manager.get_blacklisted_devices().len(),
+            config_manager.get_whitelisted_devices().len(),
+        );
+        
+        if let Some(ref shared) = self.config_manager {
+            let mut current = shared.write().await;
+            // Unblock All lasts until restart, not until the next reload
+            config_manager.set_blocking_suspended(current.is_blocking_suspended());
+            *current = config_manager;
+        }
+        
+        info!("Configuration reloaded from {}: {} blacklist and {} whitelist rules, polling every {:?}",
+              path, rules.0, rules.1, self.polling_interval);
+    }
+    
+    /// Add a rule to the blacklist or whitelist and save it; failures are reported to the GUI.
+    ///
+    /// The rule goes into the monitor's configuration when there is one, so it applies right away.
+    async fn add_rule(&self, rule: DeviceRule, blacklist: bool) -> Result<()> {
+        let list = if blacklist { "blacklist" } else { "whitelist" };
+        let add = |config_manager: &mut ConfigManager| {
+            if blacklist {
+                config_manager.add_blacklisted_device(rule)?;
+            } else {
+                config_manager.add_whitelisted_device(rule)?;
+            }
+            config_manager.save()
+        };
+        let result = match self.config_manager {
+            Some(ref shared) => add(&mut *shared.write().await),
+            None => ConfigManager::new(None).and_then(|mut config_manager| add(&mut config_manager)),
+        };
+        
+        match result {
+            Ok(()) => info!("Added {} rule from the GUI", list),
+            Err(e) => self.communication.send_error(&format!("Failed to add {} rule: {}", list, e))?,
+        }
+        Ok(())
+    }
+    
+    /// Forward the monitor's analytics and connection timeline to the GUI
+    fn send_analytics(&self) -> Result<()> {
+        if let Some(ref monitor) = self.usb_monitor {
+            self.communication.send_analytics_updated(monitor.get_device_analytics())?;
+            self.communication.send_timeline_updated(monitor.get_connection_timeline())?;
+        }
+        Ok(())
+    }
+    
     /// Set device filter
     async fn set_filter(&mut self, filter: Option<String>) -> Result<()> {
         self.device_filter = filter.clone();
@@ -231,9 +517,11 @@ impl MonitoringService {
                 Ok(changes) => {
                     if !changes.is_empty() {
                         debug!("Detected {} device changes", changes.len());
-                        for change in changes {
-                            self.communication.send_device_change(change)?;
+                        #[cfg(feature = "mqtt")]
+                        if let Some(ref mqtt) = self.mqtt {
+                            mqtt.publish_changes(&changes);
                         }
+                        self.communication.send_devices_changed(changes)?;
                     }
                 }
                 Err(e) => {
@@ -255,6 +543,17 @@ impl MonitoringService {
         Ok(())
     }
     
+    /// Write an analytics snapshot; failures are logged and retried on the next interval
+    fn write_snapshot(&self) {
+        let (Some((_, writer)), Some(monitor)) = (&self.snapshots, &self.usb_monitor) else {
+            return;
+        };
+        
+        if let Err(e) = writer.write(monitor) {
+            error!("Failed to write analytics snapshot: {}", e);
+        }
+    }
+    
     /// Get the shutdown coordinator (for external shutdown signaling)
     pub fn shutdown_coordinator(&mut self) -> &mut ShutdownCoordinator {
         &mut self.shutdown_coordinator
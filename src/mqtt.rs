@@ -0,0 +1,110 @@
+//! Publishes device events to an MQTT broker for home-automation and IoT setups.
+//!
+//! Connects, disconnects and blocks are sent as JSON to `<topic_prefix>/<host>/device`.
+//! Publishing never waits on the network: messages are queued for a background task that
+//! drives the connection and reconnects on its own, and a full queue drops the message.
+
+use crate::config::{parse_mqtt_broker, MqttConfig};
+use crate::usb_monitor::UsbDeviceChange;
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::time::Duration;
+
+/// Messages queued for the connection before new ones are dropped
+const QUEUE_CAPACITY: usize = 100;
+
+/// Pause between reconnection attempts after the broker connection fails
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+pub struct MqttPublisher {
+    client: AsyncClient,
+    topic: String,
+    host: String,
+    connection: tokio::task::JoinHandle<()>,
+}
+
+impl MqttPublisher {
+    /// Start publishing to the configured broker, or `None` when no broker is set
+    pub fn from_config(config: &MqttConfig) -> Result<Option<Self>> {
+        let Some(ref broker) = config.broker else {
+            return Ok(None);
+        };
+        let (broker_host, port) = parse_mqtt_broker(broker).context("Invalid mqtt.broker")?;
+
+        let host = hostname();
+        let mut options = MqttOptions::new(format!("ironwatch-{}-{}", host, std::process::id()), broker_host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+
+        let (client, mut event_loop) = AsyncClient::new(options, QUEUE_CAPACITY);
+
+        // Polling the event loop again after an error reconnects
+        let broker = broker.clone();
+        let connection = tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => info!("Connected to MQTT broker {}", broker),
+                    Ok(event) => debug!("MQTT event: {:?}", event),
+                    Err(e) => {
+                        warn!("MQTT connection to {} failed, retrying in {:?}: {}", broker, RECONNECT_DELAY, e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Self {
+            client,
+            topic: format!("{}/{}/device", config.topic_prefix, host),
+            host,
+            connection,
+        }))
+    }
+
+    pub fn publish_changes(&self, changes: &[UsbDeviceChange]) {
+        for change in changes {
+            self.publish_change(change);
+        }
+    }
+
+    /// Queue one change; only connects, disconnects and blocks are published
+    pub fn publish_change(&self, change: &UsbDeviceChange) {
+        let event = match change {
+            UsbDeviceChange::Connected(_) | UsbDeviceChange::Reconnected(_) => "connected",
+            UsbDeviceChange::Disconnected(_) => "disconnected",
+            UsbDeviceChange::Blocked(..) => "blocked",
+            UsbDeviceChange::Suspended(_) => return,
+        };
+
+        let payload = serde_json::json!({
+            "event": event,
+            "host": self.host,
+            "timestamp": chrono::Utc::now(),
+            "device": change.get_device_info(),
+            "reason": change.blocked_reason(),
+        });
+
+        if let Err(e) = self.client.try_publish(&self.topic, QoS::AtLeastOnce, false, payload.to_string()) {
+            warn!("Dropped MQTT message for {}: {}", self.topic, e);
+        }
+    }
+}
+
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        let _ = self.client.try_disconnect();
+        self.connection.abort();
+    }
+}
+
+/// This machine's name for the topic, from the environment or `/etc/hostname`
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().replace(['/', '#', '+'], "_"))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
@@ -1,95 +1,891 @@
-use crate::usb_monitor::{UsbDeviceInfo, UsbDeviceChange, DeviceStatistics, DeviceAnalytics, SecurityEvent};
-use crate::cli::OutputFormat;
-use serde_json;
+use crate::usb_monitor::{UsbDeviceInfo, UsbDeviceChange, ChangeRecord, ChangeTracker, ConnectionInterval, DeviceStatistics, DeviceStatisticsReport, DeviceAnalytics, SecurityEvent, SessionSummary};
+use crate::benchmark::{BenchmarkReport, PhaseReport};
+use crate::cli::{FileMode, FileRotation, OutputFormat, SinkSpec, TableColumn};
+use crate::config::PolicyDecision;
 use std::fs::OpenOptions;
 use std::io::{Write, BufWriter};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
-use crossterm::{
-    style::Stylize,
-    terminal::{Clear, ClearType},
-    cursor::MoveTo,
-    execute,
-};
+use log::{info, warn};
+use crossterm::style::Stylize;
 use std::io::stdout;
 use chrono::Utc;
 
+/// Declaration at the top of every XML document
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+/// What `export_device_history` writes
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub enum HistoryExport<'a> {
+    /// Current devices, per-device statistics, analytics and security events
+    Full {
+        devices: &'a [UsbDeviceInfo],
+        device_stats: &'a [(String, DeviceStatistics)],
+        analytics: &'a DeviceAnalytics,
+        security_events: &'a [SecurityEvent],
+    },
+    /// Connection intervals per device, from `UsbMonitor::get_connection_timeline`
+    Timeline(&'a [ConnectionInterval]),
+}
+
+/// A destination for rendered output, each with its own format
+pub trait Sink: Send {
+    /// Format this sink expects its content in
+    fn format(&self) -> &OutputFormat;
+
+    /// Whether ANSI color codes may be written to this sink
+    fn supports_color(&self) -> bool;
+
+    /// Write already-rendered content
+    fn write(&mut self, content: &str) -> Result<()>;
+
+    /// Flush any buffered content
+    fn flush(&mut self) -> Result<()>;
+
+    /// Whether a CSV or table header is due; files get one per opened file
+    fn take_header(&mut self) -> bool {
+        true
+    }
+}
+
+/// Sink that writes to stdout
+pub struct ConsoleSink {
+    format: OutputFormat,
+}
+
+impl ConsoleSink {
+    pub fn new(format: OutputFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn format(&self) -> &OutputFormat {
+        &self.format
+    }
+
+    fn supports_color(&self) -> bool {
+        true
+    }
+
+    fn write(&mut self, content: &str) -> Result<()> {
+        print!("{}", content);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        stdout().flush().context("Failed to flush stdout")
+    }
+}
+
+/// Sink that writes to a file
+pub struct FileSink {
+    format: OutputFormat,
+    writer: BufWriter<std::fs::File>,
+    header_written: bool,
+}
+
+impl FileSink {
+    /// Open (or create) the file at `path`, keeping or emptying existing content
+    pub fn open(format: OutputFormat, path: &Path, mode: FileMode) -> Result<Self> {
+        let file = open_output_file(path, mode)?;
+
+        Ok(Self {
+            format,
+            writer: BufWriter::new(file),
+            header_written: false,
+        })
+    }
+}
+
+fn open_output_file(path: &Path, mode: FileMode) -> Result<std::fs::File> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    match mode {
+        FileMode::Append => options.append(true),
+        FileMode::Truncate => options.write(true).truncate(true),
+    };
+
+    options.open(path)
+        .with_context(|| format!("Failed to open output file: {}", path.display()))
+}
+
+impl Sink for FileSink {
+    fn format(&self) -> &OutputFormat {
+        &self.format
+    }
+
+    fn supports_color(&self) -> bool {
+        false
+    }
+
+    fn write(&mut self, content: &str) -> Result<()> {
+        self.writer.write_all(content.as_bytes())
+            .context("Failed to write to output file")
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().context("Failed to flush output file")
+    }
+
+    fn take_header(&mut self) -> bool {
+        !std::mem::replace(&mut self.header_written, true)
+    }
+}
+
+/// Sink that writes to one file per UTC day, named `<stem>-YYYY-MM-DD.<ext>` after the base path
+pub struct DailyFileSink {
+    format: OutputFormat,
+    base: PathBuf,
+    latest_link: bool,
+    mode: FileMode,
+    day: chrono::NaiveDate,
+    writer: BufWriter<std::fs::File>,
+    /// Day whose file already has a header
+    header_day: Option<chrono::NaiveDate>,
+}
+
+impl DailyFileSink {
+    /// Open today's file, optionally pointing `<stem>-latest.<ext>` at it. With
+    /// `FileMode::Truncate`, only files this sink opens are emptied, never earlier days'.
+    pub fn open(format: OutputFormat, base: &Path, latest_link: bool, mode: FileMode) -> Result<Self> {
+        let day = Utc::now().date_naive();
+        let writer = Self::open_day(base, day, latest_link, mode)?;
+
+        Ok(Self {
+            format,
+            base: base.to_path_buf(),
+            latest_link,
+            mode,
+            day,
+            writer,
+            header_day: None,
+        })
+    }
+
+    /// `base` with `-suffix` inserted before its extension
+    fn suffixed_path(base: &Path, suffix: &str) -> PathBuf {
+        let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match base.extension() {
+            Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+            None => format!("{}-{}", stem, suffix),
+        };
+        base.with_file_name(name)
+    }
+
+    fn day_path(base: &Path, day: chrono::NaiveDate) -> PathBuf {
+        Self::suffixed_path(base, &day.format("%Y-%m-%d").to_string())
+    }
+
+    fn open_day(base: &Path, day: chrono::NaiveDate, latest_link: bool, mode: FileMode) -> Result<BufWriter<std::fs::File>> {
+        let path = Self::day_path(base, day);
+        let file = open_output_file(&path, mode)?;
+
+        if latest_link {
+            Self::update_latest_link(base, &path);
+        }
+
+        Ok(BufWriter::new(file))
+    }
+
+    /// Point the `-latest` symlink at `target`; failing to do so never stops output
+    fn update_latest_link(base: &Path, target: &Path) {
+        let link = Self::suffixed_path(base, "latest");
+        let _ = std::fs::remove_file(&link);
+
+        #[cfg(unix)]
+        let result = std::os::unix::fs::symlink(target.file_name().unwrap_or_default(), &link);
+        #[cfg(not(unix))]
+        let result: std::io::Result<()> = Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "symlinks are only supported on Unix"));
+
+        if let Err(e) = result {
+            warn!("Could not update {}: {}", link.display(), e);
+        }
+    }
+}
+
+impl Sink for DailyFileSink {
+    fn format(&self) -> &OutputFormat {
+        &self.format
+    }
+
+    fn supports_color(&self) -> bool {
+        false
+    }
+
+    fn write(&mut self, content: &str) -> Result<()> {
+        // Roll over lazily, on the first write of a new day
+        let today = Utc::now().date_naive();
+        if today != self.day {
+            self.writer.flush().context("Failed to flush output file")?;
+            self.writer = Self::open_day(&self.base, today, self.latest_link, self.mode)?;
+            self.day = today;
+            info!("Output rolled over to {}", Self::day_path(&self.base, today).display());
+        }
+
+        self.writer.write_all(content.as_bytes())
+            .context("Failed to write to output file")
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().context("Failed to flush output file")
+    }
+
+    /// A header per day's file, including one the next write will roll over to
+    fn take_header(&mut self) -> bool {
+        let today = Utc::now().date_naive();
+        self.header_day.replace(today) != Some(today)
+    }
+}
+
+/// Open a file sink with the given rotation
+fn open_file_sink(format: OutputFormat, path: &Path, rotation: FileRotation, latest_link: bool, mode: FileMode) -> Result<Box<dyn Sink>> {
+    // Like saving the configuration, a missing directory is created rather than an error
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty() && !parent.exists()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create output directory: {}", parent.display()))?;
+    }
+
+    Ok(match rotation {
+        FileRotation::None => Box::new(FileSink::open(format, path, mode)?),
+        FileRotation::Daily => Box::new(DailyFileSink::open(format, path, latest_link, mode)?),
+    })
+}
+
 pub struct OutputManager {
     format: OutputFormat,
-    output_file: Option<BufWriter<std::fs::File>>,
+    sinks: Vec<Box<dyn Sink>>,
     use_colors: bool,
     include_metadata: bool,
+    quiet: bool,
+    block_message: String,
+    columns: Vec<TableColumn>,
+    /// Filter that selected the listed devices, recorded in device list metadata
+    filter: Option<String>,
+    /// Each device's previous change, for `prev_status` and `since_ms` in JSON output
+    tracker: ChangeTracker,
+    /// Tags added to structured device listings; empty unless `output.include_tags` is set
+    device_tags: HashMap<String, String>,
 }
 
 impl OutputManager {
-    /// Create a new output manager
+    /// Create a new output manager writing to the console and optionally one file
     pub fn new(
         format: OutputFormat,
         output_file_path: Option<PathBuf>,
         use_colors: bool,
         include_metadata: bool,
+        rotation: FileRotation,
+        latest_link: bool,
+        mode: FileMode,
     ) -> Result<Self> {
-        let output_file = match output_file_path {
-            Some(path) => {
-                let file = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&path)
-                    .with_context(|| format!("Failed to open output file: {}", path.display()))?;
-                Some(BufWriter::new(file))
-            }
-            None => None,
-        };
+        let mut sinks: Vec<Box<dyn Sink>> = vec![Box::new(ConsoleSink::new(format.clone()))];
 
-        Ok(Self {
+        if let Some(path) = output_file_path {
+            sinks.push(open_file_sink(format.clone(), &path, rotation, latest_link, mode)?);
+        }
+
+        Ok(Self::with_sinks(format, sinks, use_colors, include_metadata))
+    }
+
+    /// Create an output manager from `--sink` specifications
+    pub fn from_sink_specs(
+        format: OutputFormat,
+        specs: &[SinkSpec],
+        use_colors: bool,
+        include_metadata: bool,
+        rotation: FileRotation,
+        latest_link: bool,
+        mode: FileMode,
+    ) -> Result<Self> {
+        let mut sinks: Vec<Box<dyn Sink>> = Vec::with_capacity(specs.len());
+
+        for spec in specs {
+            match &spec.path {
+                Some(path) => sinks.push(open_file_sink(spec.format.clone(), path, rotation, latest_link, mode)?),
+                None => sinks.push(Box::new(ConsoleSink::new(spec.format.clone()))),
+            }
+        }
+
+        Ok(Self::with_sinks(format, sinks, use_colors, include_metadata))
+    }
+
+    /// Create an output manager from an explicit set of sinks
+    pub fn with_sinks(
+        format: OutputFormat,
+        sinks: Vec<Box<dyn Sink>>,
+        use_colors: bool,
+        include_metadata: bool,
+    ) -> Self {
+        Self {
             format,
-            output_file,
+            sinks,
             use_colors,
             include_metadata,
-        })
+            quiet: false,
+            block_message: crate::config::DEFAULT_BLOCK_MESSAGE.to_string(),
+            columns: TableColumn::ALL.to_vec(),
+            filter: None,
+            tracker: ChangeTracker::default(),
+            device_tags: HashMap::new(),
+        }
+    }
+
+    /// Suppress status and informational lines so only device data is written
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Select and order the columns of table output
+    pub fn set_columns(&mut self, columns: Vec<TableColumn>) {
+        self.columns = columns;
+    }
+
+    /// Add each device's tag to JSON, NDJSON, XML and TOML device listings
+    pub fn set_device_tags(&mut self, tags: HashMap<String, String>) {
+        self.device_tags = tags;
+    }
+
+    /// Describe the filter applied to listed devices, so exports record what they contain
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub fn set_filter_description(&mut self, filter: Option<String>) {
+        self.filter = filter;
+    }
+
+    /// Set the warning shown whenever a device is blocked
+    pub fn set_block_message(&mut self, message: &str) {
+        self.block_message = message.to_string();
     }
 
     /// Display a list of USB devices
     pub fn display_devices(&mut self, devices: &[UsbDeviceInfo]) -> Result<()> {
-        match self.format {
-            OutputFormat::Json => self.output_json_devices(devices),
-            OutputFormat::Table => self.output_table_devices(devices),
-            OutputFormat::Csv => self.output_csv_devices(devices),
-        }
+        self.display_device_list(devices, None)
+    }
+
+    /// Display a list of USB devices alongside the allow/deny decision for each
+    pub fn display_devices_with_policy(&mut self, devices: &[UsbDeviceInfo], policies: &[PolicyDecision]) -> Result<()> {
+        self.display_device_list(devices, Some(policies))
+    }
+
+    /// Display devices looked up by ID: a single match as one JSON object, several as an array
+    pub fn display_device_matches(&mut self, devices: &[UsbDeviceInfo]) -> Result<()> {
+        let include_metadata = self.include_metadata;
+        let columns = self.columns.clone();
+        let tags = self.device_tags.clone();
+
+        self.emit_with_header(|format, use_colors, header| match format {
+            OutputFormat::Json => {
+                let output = match Self::devices_to_json(devices, None, &tags, true)?.as_slice() {
+                    [device] => serde_json::to_string_pretty(device),
+                    devices => serde_json::to_string_pretty(devices),
+                }.context("Failed to serialize devices to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Ndjson => Self::render_ndjson_devices(devices, None, &tags),
+            OutputFormat::Table => Ok(Self::render_table_devices(devices, None, &columns, use_colors, include_metadata, None, header)),
+            OutputFormat::Csv => Ok(Self::render_csv_devices(devices, None, header)),
+            OutputFormat::Xml => Self::render_xml_devices(devices, None, &tags, include_metadata, None),
+            OutputFormat::Toml => Self::render_toml_devices(devices, None, &tags, include_metadata, None),
+        })?;
+
+        self.flush()
+    }
+
+    fn display_device_list(&mut self, devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>) -> Result<()> {
+        let include_metadata = self.include_metadata;
+        let filter = self.filter.clone();
+        let columns = self.columns.clone();
+        let tags = self.device_tags.clone();
+
+        self.emit_with_header(|format, use_colors, header| match format {
+            OutputFormat::Json => Self::render_json_devices(devices, policies, &tags, include_metadata, filter.as_deref()),
+            OutputFormat::Ndjson => Self::render_ndjson_devices(devices, policies, &tags),
+            OutputFormat::Table => Ok(Self::render_table_devices(devices, policies, &columns, use_colors, include_metadata, filter.as_deref(), header)),
+            OutputFormat::Csv => Ok(Self::render_csv_devices(devices, policies, header)),
+            OutputFormat::Xml => Self::render_xml_devices(devices, policies, &tags, include_metadata, filter.as_deref()),
+            OutputFormat::Toml => Self::render_toml_devices(devices, policies, &tags, include_metadata, filter.as_deref()),
+        })?;
+
+        self.flush()
     }
 
     /// Display USB device changes
     pub fn display_changes(&mut self, changes: &[UsbDeviceChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        
+        let records: Vec<ChangeRecord> = changes.iter()
+            .map(|change| self.tracker.record(change.clone()))
+            .collect();
+        
+        // Render the whole batch once per sink so a hub's worth of changes is a single write
+        let columns = self.columns.clone();
+        self.emit(|format, use_colors| {
+            match format {
+                OutputFormat::Xml => return Ok(Self::render_xml_changes(&records)),
+                OutputFormat::Toml => return Self::render_toml_changes(&records),
+                _ => {}
+            }
+            
+            let mut output = String::new();
+            for record in &records {
+                output.push_str(&match format {
+                    OutputFormat::Table => Self::render_table_change(&record.change, &columns, use_colors),
+                    OutputFormat::Csv => Self::render_csv_change(&record.change),
+                    _ => Self::render_json_change(record)?,
+                });
+            }
+            Ok(output)
+        })?;
+        
         for change in changes {
-            match self.format {
-                OutputFormat::Json => self.output_json_change(change)?,
-                OutputFormat::Table => self.output_table_change(change)?,
-                OutputFormat::Csv => self.output_csv_change(change)?,
+            if let UsbDeviceChange::Blocked(device, _) = change {
+                let warning = format!("{} ({:04x}:{:04x}) blocked. {}",
+                    device.display_name(), device.vendor_id, device.product_id, self.block_message);
+                self.display_warning(&warning)?;
             }
         }
         
-        if let Some(ref mut file) = self.output_file {
-            file.flush().context("Failed to flush output file")?;
+        self.flush()
+    }
+
+    /// Emit an idle heartbeat record; only JSON and NDJSON sinks receive it
+    pub fn display_heartbeat(&mut self, device_count: usize) -> Result<()> {
+        self.emit(|format, _| match format {
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                let json = serde_json::json!({
+                    "event": "heartbeat",
+                    "ts": Utc::now(),
+                    "devices": device_count,
+                });
+                let output = serde_json::to_string(&json)
+                    .context("Failed to serialize heartbeat to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Table | OutputFormat::Csv | OutputFormat::Xml | OutputFormat::Toml => Ok(String::new()),
+        })?;
+
+        self.flush()
+    }
+
+    /// Display the end-of-session summary in each sink's format
+    pub fn display_summary(&mut self, summary: &SessionSummary) -> Result<()> {
+        self.emit(|format, use_colors| match format {
+            OutputFormat::Json => {
+                let json = serde_json::json!({ "session_summary": summary });
+                let output = serde_json::to_string_pretty(&json)
+                    .context("Failed to serialize session summary to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Ndjson => {
+                let json = serde_json::json!({ "event": "session_summary", "summary": summary });
+                let output = serde_json::to_string(&json)
+                    .context("Failed to serialize session summary to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Table => Ok(Self::render_table_summary(summary, use_colors)),
+            OutputFormat::Csv => Ok(Self::render_csv_summary(summary)),
+            OutputFormat::Xml => {
+                let value = serde_json::to_value(summary)
+                    .context("Failed to serialize session summary")?;
+                Ok(Self::render_xml_document("session_summary", &value))
+            }
+            OutputFormat::Toml => {
+                let value = serde_json::to_value(summary)
+                    .context("Failed to serialize session summary")?;
+                Self::render_toml_document(serde_json::json!({ "session_summary": value }))
+            }
+        })?;
+
+        self.flush()
+    }
+
+    /// Display per-device statistics gathered over a session of `session` length
+    pub fn display_statistics(&mut self, reports: &[DeviceStatisticsReport], session: std::time::Duration) -> Result<()> {
+        self.emit(|format, use_colors| match format {
+            OutputFormat::Json => {
+                let json = serde_json::json!({
+                    "session_seconds": session.as_secs(),
+                    "session_only": true,
+                    "devices": reports.iter().map(Self::statistics_json).collect::<Vec<_>>(),
+                });
+                let output = serde_json::to_string_pretty(&json)
+                    .context("Failed to serialize statistics to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Ndjson => {
+                let mut output = String::new();
+                for report in reports {
+                    let line = serde_json::to_string(&Self::statistics_json(report))
+                        .context("Failed to serialize statistics to JSON")?;
+                    output.push_str(&line);
+                    output.push('\n');
+                }
+                Ok(output)
+            }
+            OutputFormat::Table => Ok(Self::render_table_statistics(reports, session, use_colors)),
+            OutputFormat::Csv => Ok(Self::render_csv_statistics(reports)),
+            OutputFormat::Xml => {
+                let value = serde_json::json!({
+                    "session_seconds": session.as_secs(),
+                    "session_only": true,
+                    "devices": reports.iter().map(Self::statistics_json).collect::<Vec<_>>(),
+                });
+                Ok(Self::render_xml_document("statistics", &value))
+            }
+            OutputFormat::Toml => {
+                let value = serde_json::json!({
+                    "session_seconds": session.as_secs(),
+                    "session_only": true,
+                    "devices": reports.iter().map(Self::statistics_json).collect::<Vec<_>>(),
+                });
+                Self::render_toml_document(value)
+            }
+        })?;
+
+        self.flush()
+    }
+
+    fn statistics_json(report: &DeviceStatisticsReport) -> serde_json::Value {
+        let stats = &report.statistics;
+        serde_json::json!({
+            "device_key": report.device_key,
+            "vendor_id": report.vendor_id,
+            "product_id": report.product_id,
+            "name": report.name,
+            "connections": stats.total_connections,
+            "disconnections": stats.total_disconnections,
+            "blocked": stats.total_blocked,
+            "connected_seconds": stats.connection_duration.as_secs(),
+            "first_seen": stats.first_seen,
+            "last_seen": stats.last_seen,
+        })
+    }
+
+    /// Render per-device statistics for humans
+    fn render_table_statistics(reports: &[DeviceStatisticsReport], session: std::time::Duration, use_colors: bool) -> String {
+        let mut output = String::new();
+
+        let title = format!("Device Statistics ({} device(s))", reports.len());
+        if use_colors {
+            output.push_str(&format!("\n{}\n", title.bold().blue()));
+        } else {
+            output.push_str(&format!("\n{}\n", title));
         }
-        
+        output.push_str(&format!("{:<9} {:<25} {:>5} {:>5} {:>5} {:>10}  {:<19}  {:<19}\n",
+            "VID:PID", "Name", "Conn", "Disc", "Blk", "Connected", "First Seen", "Last Seen"));
+        output.push_str(&format!("{}\n", "-".repeat(106)));
+
+        for report in reports {
+            let stats = &report.statistics;
+            let connected = stats.connection_duration.as_secs();
+            output.push_str(&format!("{:04x}:{:04x} {:<25} {:>5} {:>5} {:>5} {:>10}  {:<19}  {:<19}\n",
+                report.vendor_id,
+                report.product_id,
+                Self::truncate_string(report.name.as_deref().unwrap_or("Unknown"), 25),
+                stats.total_connections,
+                stats.total_disconnections,
+                stats.total_blocked,
+                format!("{}h{:02}m{:02}s", connected / 3600, connected / 60 % 60, connected % 60),
+                stats.first_seen.format("%Y-%m-%d %H:%M:%S"),
+                stats.last_seen.format("%Y-%m-%d %H:%M:%S"),
+            ));
+        }
+
+        output.push_str(&format!("\nThese numbers cover this {}s session only; IronWatch does not persist history between runs.\n",
+            session.as_secs()));
+        output
+    }
+
+    /// Render per-device statistics as CSV
+    fn render_csv_statistics(reports: &[DeviceStatisticsReport]) -> String {
+        let mut output = String::from("DeviceKey,VendorId,ProductId,Name,Connections,Disconnections,Blocked,ConnectedSeconds,FirstSeen,LastSeen\n");
+
+        for report in reports {
+            let stats = &report.statistics;
+            output.push_str(&format!("{},{:04x},{:04x},{},{},{},{},{},{},{}\n",
+                Self::escape_csv_field(&report.device_key),
+                report.vendor_id,
+                report.product_id,
+                Self::escape_csv_field(report.name.as_deref().unwrap_or("")),
+                stats.total_connections,
+                stats.total_disconnections,
+                stats.total_blocked,
+                stats.connection_duration.as_secs(),
+                stats.first_seen.to_rfc3339(),
+                stats.last_seen.to_rfc3339(),
+            ));
+        }
+
+        output
+    }
+
+    /// Display the results of the `benchmark` subcommand
+    pub fn display_benchmark(&mut self, report: &BenchmarkReport) -> Result<()> {
+        self.emit(|format, use_colors| match format {
+            OutputFormat::Json => {
+                let output = serde_json::to_string_pretty(report)
+                    .context("Failed to serialize benchmark to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Ndjson => {
+                let output = serde_json::to_string(report)
+                    .context("Failed to serialize benchmark to JSON")?;
+                Ok(format!("{}\n", output))
+            }
+            OutputFormat::Table => Ok(Self::render_table_benchmark(report, use_colors)),
+            OutputFormat::Csv => {
+                let mut output = String::from("Phase,Iterations,ScansPerSecond,P50Ms,P99Ms,DescriptorReadMs,DescriptorReadShare\n");
+                for (phase, timings) in [("cold", &report.cold), ("warm", &report.warm)] {
+                    output.push_str(&format!("{},{},{:.2},{:.3},{:.3},{:.3},{:.4}\n",
+                        phase,
+                        timings.iterations,
+                        timings.scans_per_second,
+                        timings.p50_ms,
+                        timings.p99_ms,
+                        timings.descriptor_read_ms,
+                        timings.descriptor_read_share,
+                    ));
+                }
+                Ok(output)
+            }
+            OutputFormat::Xml => {
+                let value = serde_json::to_value(report)
+                    .context("Failed to serialize benchmark")?;
+                Ok(Self::render_xml_document("benchmark", &value))
+            }
+            OutputFormat::Toml => {
+                let value = serde_json::to_value(report)
+                    .context("Failed to serialize benchmark")?;
+                Self::render_toml_document(serde_json::json!({ "benchmark": value }))
+            }
+        })?;
+
+        self.flush()
+    }
+
+    /// Render benchmark results for humans
+    fn render_table_benchmark(report: &BenchmarkReport, use_colors: bool) -> String {
+        let mut output = String::new();
+
+        let title = format!("Enumeration Benchmark ({} device(s), {:.0}s)", report.devices, report.duration_seconds);
+        if use_colors {
+            output.push_str(&format!("\n{}\n", title.bold().blue()));
+        } else {
+            output.push_str(&format!("\n{}\n", title));
+        }
+        output.push_str(&format!("{:<6} {:>10} {:>10} {:>10} {:>10} {:>14} {:>8}\n",
+            "Phase", "Scans", "Scans/s", "p50 (ms)", "p99 (ms)", "open() (ms)", "open()%"));
+        output.push_str(&format!("{}\n", "-".repeat(74)));
+
+        let row = |phase: &str, timings: &PhaseReport| format!("{:<6} {:>10} {:>10.1} {:>10.3} {:>10.3} {:>14.1} {:>7.1}%\n",
+            phase,
+            timings.iterations,
+            timings.scans_per_second,
+            timings.p50_ms,
+            timings.p99_ms,
+            timings.descriptor_read_ms,
+            timings.descriptor_read_share * 100.0,
+        );
+        output.push_str(&row("cold", &report.cold));
+        output.push_str(&row("warm", &report.warm));
+
+        if !report.descriptor_strings {
+            output.push_str("\nDescriptor strings are disabled, so no devices were opened.\n");
+        }
+        output
+    }
+
+    /// Render the session summary for humans
+    fn render_table_summary(summary: &SessionSummary, use_colors: bool) -> String {
+        let mut output = String::new();
+        let duration = summary.ended_at.signed_duration_since(summary.started_at);
+
+        let title = "Session Summary";
+        if use_colors {
+            output.push_str(&format!("\n{}\n", title.bold().blue()));
+        } else {
+            output.push_str(&format!("\n{}\n", title));
+        }
+        output.push_str(&format!("{}\n", "-".repeat(40)));
+        output.push_str(&format!("Duration:       {}h {}m {}s\n",
+            duration.num_hours(), duration.num_minutes() % 60, duration.num_seconds() % 60));
+        output.push_str(&format!("Connections:    {}\n", summary.total_connections));
+        output.push_str(&format!("Disconnections: {}\n", summary.total_disconnections));
+        output.push_str(&format!("Blocked:        {}\n", summary.total_blocked));
+        output.push_str(&format!("Unique devices: {}\n", summary.unique_devices));
+        if let Some(latency) = summary.avg_detection_latency_ms {
+            output.push_str(&format!("Avg detection:  {:.1} ms after poll cycle start\n", latency));
+        }
+
+        if !summary.top_devices.is_empty() {
+            output.push_str("\nMost frequently connecting devices:\n");
+            for (i, device) in summary.top_devices.iter().enumerate() {
+                output.push_str(&format!("  {}. {:<25} {:<20} {} connection(s)\n",
+                    i + 1,
+                    Self::truncate_string(device.name.as_deref().unwrap_or("Unknown"), 25),
+                    device.device_key,
+                    device.connections
+                ));
+            }
+        }
+
+        output
+    }
+
+    /// Render the session summary as CSV
+    fn render_csv_summary(summary: &SessionSummary) -> String {
+        let mut output = String::new();
+
+        output.push_str("Started,Ended,Connections,Disconnections,Blocked,UniqueDevices,AvgDetectionLatencyMs\n");
+        output.push_str(&format!("{},{},{},{},{},{},{}\n",
+            summary.started_at.to_rfc3339(),
+            summary.ended_at.to_rfc3339(),
+            summary.total_connections,
+            summary.total_disconnections,
+            summary.total_blocked,
+            summary.unique_devices,
+            summary.avg_detection_latency_ms.map(|ms| format!("{:.1}", ms)).unwrap_or_default()
+        ));
+
+        output.push_str("Rank,DeviceKey,Name,Connections\n");
+        for (i, device) in summary.top_devices.iter().enumerate() {
+            output.push_str(&format!("{},{},{},{}\n",
+                i + 1,
+                Self::escape_csv_field(&device.device_key),
+                Self::escape_csv_field(device.name.as_deref().unwrap_or("")),
+                device.connections
+            ));
+        }
+
+        output
+    }
+
+    /// Render content once per sink in that sink's format and write it
+    fn emit<F>(&mut self, render: F) -> Result<()>
+    where
+        F: Fn(&OutputFormat, bool) -> Result<String>,
+    {
+        for sink in self.sinks.iter_mut() {
+            let use_colors = self.use_colors && sink.supports_color();
+            let content = render(sink.format(), use_colors)?;
+            sink.write(&content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Like `emit`, also telling the renderer whether this sink still needs a CSV or table header
+    fn emit_with_header<F>(&mut self, render: F) -> Result<()>
+    where
+        F: Fn(&OutputFormat, bool, bool) -> Result<String>,
+    {
+        for sink in self.sinks.iter_mut() {
+            let use_colors = self.use_colors && sink.supports_color();
+            let header = sink.take_header();
+            let content = render(sink.format(), use_colors, header)?;
+            sink.write(&content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush all sinks
+    pub fn flush(&mut self) -> Result<()> {
+        for sink in self.sinks.iter_mut() {
+            sink.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush and close every sink, e.g. on shutdown; anything displayed afterwards is discarded
+    pub fn flush_and_close(&mut self) -> Result<()> {
+        self.flush()?;
+        self.sinks.clear();
         Ok(())
     }
 
     /// Export device history to a file
-    pub fn export_device_history(
-        &mut self,
-        devices: &[UsbDeviceInfo],
-        device_stats: &[(String, DeviceStatistics)],
-        analytics: &DeviceAnalytics,
-        security_events: &[SecurityEvent],
-        export_path: &PathBuf,
-    ) -> Result<()> {
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub fn export_device_history(&mut self, export: HistoryExport<'_>, export_path: &PathBuf) -> Result<()> {
         let timestamp = Utc::now();
         
+        let (devices, device_stats, analytics, security_events) = match export {
+            HistoryExport::Full { devices, device_stats, analytics, security_events } => (devices, device_stats, analytics, security_events),
+            HistoryExport::Timeline(intervals) => {
+                let content = Self::render_timeline(&self.format, intervals, timestamp)?;
+                return std::fs::write(export_path, content)
+                    .with_context(|| format!("Failed to write export file: {}", export_path.display()));
+            }
+        };
+        
         match self.format {
-            OutputFormat::Json => self.export_json_history(devices, device_stats, analytics, security_events, export_path, timestamp),
+            OutputFormat::Json | OutputFormat::Ndjson => self.export_json_history(devices, device_stats, analytics, security_events, export_path, timestamp),
             OutputFormat::Table => self.export_table_history(devices, device_stats, analytics, security_events, export_path, timestamp),
             OutputFormat::Csv => self.export_csv_history(devices, device_stats, analytics, security_events, export_path, timestamp),
+            OutputFormat::Xml => {
+                let export_data = crate::snapshot::history_json(devices, device_stats, analytics, security_events, timestamp);
+                std::fs::write(export_path, Self::render_xml_document("ironwatch_history", &export_data))
+                    .with_context(|| format!("Failed to write export file: {}", export_path.display()))
+            }
+            OutputFormat::Toml => {
+                let export_data = crate::snapshot::history_json(devices, device_stats, analytics, security_events, timestamp);
+                std::fs::write(export_path, Self::render_toml_document(export_data)?)
+                    .with_context(|| format!("Failed to write export file: {}", export_path.display()))
+            }
+        }
+    }
+
+    /// Render connection intervals for a timeline export
+    fn render_timeline(format: &OutputFormat, intervals: &[ConnectionInterval], timestamp: chrono::DateTime<Utc>) -> Result<String> {
+        let document = crate::snapshot::timeline_json(intervals, timestamp);
+        
+        match format {
+            OutputFormat::Json => serde_json::to_string_pretty(&document)
+                .context("Failed to serialize timeline to JSON"),
+            OutputFormat::Ndjson => {
+                let mut output = String::new();
+                for interval in intervals {
+                    let line = serde_json::to_string(interval)
+                        .context("Failed to serialize timeline to JSON")?;
+                    output.push_str(&line);
+                    output.push('\n');
+                }
+                Ok(output)
+            }
+            OutputFormat::Csv => {
+                let mut output = String::from("DeviceKey,ConnectedAt,DisconnectedAt\n");
+                for interval in intervals {
+                    output.push_str(&format!("{},{},{}\n",
+                        Self::escape_csv_field(&interval.device_key),
+                        interval.connected_at.to_rfc3339(),
+                        interval.disconnected_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+                    ));
+                }
+                Ok(output)
+            }
+            OutputFormat::Table => {
+                let mut output = String::from("IronWatch Connection Timeline\n");
+                output.push_str("=============================\n\n");
+                output.push_str(&format!("Export Time: {}\n\n", timestamp.format("%Y-%m-%d %H:%M:%S UTC")));
+                output.push_str(&format!("{:<32} {:<20} {:<20}\n", "Device", "Connected", "Disconnected"));
+                output.push_str(&format!("{} {} {}\n", "-".repeat(32), "-".repeat(20), "-".repeat(20)));
+                for interval in intervals {
+                    output.push_str(&format!("{:<32} {:<20} {:<20}\n",
+                        Self::truncate_string(&interval.device_key, 32),
+                        interval.connected_at.format("%Y-%m-%d %H:%M:%S"),
+                        interval.disconnected_at
+                            .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+                            .unwrap_or_else(|| "still connected".to_string()),
+                    ));
+                }
+                Ok(output)
+            }
+            OutputFormat::Xml => Ok(Self::render_xml_document("timeline", &document)),
+            OutputFormat::Toml => Self::render_toml_document(document),
         }
     }
 
@@ -103,31 +899,7 @@ impl OutputManager {
         export_path: &PathBuf,
         timestamp: chrono::DateTime<Utc>,
     ) -> Result<()> {
-        let export_data = serde_json::json!({
-            "export_timestamp": timestamp,
-            "export_format": "json",
-            "summary": {
-                "total_devices": devices.len(),
-                "total_connections": analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>(),
-                "unique_devices": analytics.unique_devices,
-                "blocked_devices": analytics.blocked_devices,
-                "security_violations": analytics.security_violations,
-                "device_classes": analytics.device_class_distribution.len(),
-                "vendors": analytics.vendor_distribution.len(),
-            },
-            "current_devices": devices,
-            "device_statistics": device_stats,
-            "analytics": {
-                "device_class_distribution": analytics.device_class_distribution,
-                "vendor_distribution": analytics.vendor_distribution,
-                "connection_frequency": analytics.connection_frequency,
-            },
-            "security": {
-                "total_blocked": analytics.blocked_devices,
-                "total_violations": analytics.security_violations,
-                "security_events": security_events,
-            }
-        });
+        let export_data = crate::snapshot::history_json(devices, device_stats, analytics, security_events, timestamp);
 
         let json_string = serde_json::to_string_pretty(&export_data)
             .context("Failed to serialize export data to JSON")?;
@@ -151,30 +923,32 @@ impl OutputManager {
         let mut csv_content = String::new();
         
         // Header
-        csv_content.push_str("Export Timestamp,Device Count,Total Connections,Unique Devices,Blocked Devices,Security Violations\n");
-        csv_content.push_str(&format!("{},{},{},{},{},{}\n", 
+        csv_content.push_str("Export Timestamp,Device Count,Total Connections,Unique Devices,Unique Fingerprints,Blocked Devices,Security Violations\n");
+        csv_content.push_str(&format!("{},{},{},{},{},{},{}\n", 
             timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
             devices.len(),
             analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>(),
             analytics.unique_devices,
+            analytics.unique_fingerprints,
             analytics.blocked_devices,
             analytics.security_violations
         ));
         
         csv_content.push_str("\nCurrent Devices\n");
-        csv_content.push_str("Bus,Vendor ID,Product ID,Manufacturer,Product,Serial,Class,Status,Timestamp\n");
+        csv_content.push_str("Bus,Vendor ID,Product ID,Manufacturer,Product,Serial,Class,Status,Timestamp,Fingerprint\n");
         
         for device in devices {
-            csv_content.push_str(&format!("{},{:04x},{:04x},{},{},{},{:02x},{:?},{}\n",
+            csv_content.push_str(&format!("{},{:04x},{:04x},{},{},{},{:02x},{:?},{},{}\n",
                 device.bus_number,
                 device.vendor_id,
                 device.product_id,
-                device.manufacturer.as_deref().unwrap_or("Unknown"),
-                device.product.as_deref().unwrap_or("Unknown"),
-                device.serial_number.as_deref().unwrap_or("Unknown"),
+                Self::escape_csv_field(device.manufacturer.as_deref().unwrap_or("Unknown")),
+                Self::escape_csv_field(device.product.as_deref().unwrap_or("Unknown")),
+                Self::escape_csv_field(device.serial_number.as_deref().unwrap_or("Unknown")),
                 device.device_class,
                 device.connection_status,
-                device.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+                device.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+                device.fingerprint
             ));
         }
         
@@ -183,7 +957,7 @@ impl OutputManager {
         
         for (key, stats) in device_stats {
             csv_content.push_str(&format!("{},{},{},{},{},{},{},{}\n",
-                key,
+                Self::escape_csv_field(key),
                 stats.total_connections,
                 stats.total_disconnections,
                 stats.total_blocked,
@@ -195,17 +969,18 @@ impl OutputManager {
         }
         
         csv_content.push_str("\nSecurity Events\n");
-        csv_content.push_str("Timestamp,Event Type,Device Product,Vendor ID,Product ID,Reason,Action\n");
+        csv_content.push_str("Timestamp,Event Type,Device Product,Vendor ID,Product ID,Reason,Action,Rule ID\n");
         
         for event in security_events {
-            csv_content.push_str(&format!("{},{:?},{},{:04x},{:04x},{},{:?}\n",
+            csv_content.push_str(&format!("{},{:?},{},{:04x},{:04x},{},{:?},{}\n",
                 event.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
                 event.event_type,
-                event.device_info.product.as_deref().unwrap_or("Unknown"),
+                Self::escape_csv_field(event.device_info.product.as_deref().unwrap_or("Unknown")),
                 event.device_info.vendor_id,
                 event.device_info.product_id,
-                event.reason,
-                event.action_taken
+                Self::escape_csv_field(&event.reason),
+                event.action_taken,
+                event.rule_id.as_deref().unwrap_or("")
             ));
         }
         
@@ -259,11 +1034,12 @@ impl OutputManager {
         table_content.push_str(&format!("Total Connections (24h): {}\n", 
             analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>()));
         table_content.push_str(&format!("Unique Devices: {}\n", analytics.unique_devices));
+        table_content.push_str(&format!("Unique Fingerprints: {}\n", analytics.unique_fingerprints));
         table_content.push_str(&format!("Blocked Devices: {}\n", analytics.blocked_devices));
         table_content.push_str(&format!("Security Violations: {}\n", analytics.security_violations));
         table_content.push_str(&format!("Device Classes: {}\n", analytics.device_class_distribution.len()));
         table_content.push_str(&format!("Vendors: {}\n", analytics.vendor_distribution.len()));
-        table_content.push_str("\n");
+        table_content.push('\n');
         
         // Current Devices
         table_content.push_str("CURRENT DEVICES\n");
@@ -289,7 +1065,7 @@ impl OutputManager {
         table_content.push_str("---------------------------- ------------ --------------- -------- -------------------- -------------------- ---------\n");
         
         for (key, stats) in device_stats {
-            let key_short = if key.len() > 28 { format!("{}...", &key[..25]) } else { key.clone() };
+            let key_short = Self::truncate_string(key, 28);
             table_content.push_str(&format!("{:<28} {:>12} {:>15} {:>8} {:>19} {:>19} {:>9}s\n",
                 key_short,
                 stats.total_connections,
@@ -328,7 +1104,7 @@ impl OutputManager {
             for (class_id, count) in &analytics.device_class_distribution {
                 table_content.push_str(&format!("  0x{:02x}: {}\n", class_id, count));
             }
-            table_content.push_str("\n");
+            table_content.push('\n');
         }
         
         if !analytics.vendor_distribution.is_empty() {
@@ -336,7 +1112,7 @@ impl OutputManager {
             for (vendor_id, count) in &analytics.vendor_distribution {
                 table_content.push_str(&format!("  0x{:04x}: {}\n", vendor_id, count));
             }
-            table_content.push_str("\n");
+            table_content.push('\n');
         }
         
         std::fs::write(export_path, table_content)
@@ -345,14 +1121,19 @@ impl OutputManager {
         Ok(())
     }
 
-    /// Output devices in JSON format
-    fn output_json_devices(&mut self, devices: &[UsbDeviceInfo]) -> Result<()> {
-        let json = if self.include_metadata {
-            serde_json::json!({
+    /// Render devices in JSON format
+    fn render_json_devices(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, tags: &HashMap<String, String>, include_metadata: bool, filter: Option<&str>) -> Result<String> {
+        let devices = Self::devices_to_json(devices, policies, tags, true)?;
+        let json = if include_metadata {
+            let mut json = serde_json::json!({
                 "timestamp": Utc::now(),
                 "device_count": devices.len(),
                 "devices": devices
-            })
+            });
+            if let Some(filter) = filter {
+                json["filter"] = serde_json::json!(filter);
+            }
+            json
         } else {
             serde_json::json!(devices)
         };
@@ -360,81 +1141,185 @@ impl OutputManager {
         let output = serde_json::to_string_pretty(&json)
             .context("Failed to serialize devices to JSON")?;
         
-        self.write_output(&output)?;
-        Ok(())
+        Ok(format!("{}\n", output))
+    }
+
+    /// Render devices as newline-delimited JSON, one device per line
+    fn render_ndjson_devices(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, tags: &HashMap<String, String>) -> Result<String> {
+        let mut output = String::new();
+
+        for device in Self::devices_to_json(devices, policies, tags, true)? {
+            let line = serde_json::to_string(&device)
+                .context("Failed to serialize device to JSON")?;
+            output.push_str(&line);
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+
+    /// Convert devices to JSON values, attaching a `policy` object when decisions are given, a
+    /// `tag` for tagged devices and, with `include_raw`, any `raw_descriptor` that was read
+    fn devices_to_json(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, tags: &HashMap<String, String>, include_raw: bool) -> Result<Vec<serde_json::Value>> {
+        devices.iter().enumerate().map(|(i, device)| {
+            let mut value = serde_json::to_value(device)
+                .context("Failed to serialize device to JSON")?;
+
+            if let Some(policy) = policies.and_then(|p| p.get(i)) {
+                value["policy"] = serde_json::json!({
+                    "status": policy.status(),
+                    "reason": policy.reason,
+                });
+            }
+
+            if let Some(tag) = crate::config::device_tag(tags, device) {
+                value["tag"] = serde_json::json!(tag);
+            }
+
+            if let Some(raw) = device.raw_descriptor.as_ref().filter(|_| include_raw) {
+                value["raw_descriptor"] = serde_json::json!(raw);
+            }
+
+            Ok(value)
+        }).collect()
     }
 
-    /// Output devices in table format
-    fn output_table_devices(&mut self, devices: &[UsbDeviceInfo]) -> Result<()> {
+    /// Format a policy decision for the table's Policy column
+    fn format_policy(policy: &PolicyDecision) -> String {
+        match &policy.reason {
+            Some(reason) => format!("{} ({})", policy.status(), reason),
+            None => policy.status().to_string(),
+        }
+    }
+
+    /// Render devices in table format
+    fn render_table_devices(
+        devices: &[UsbDeviceInfo],
+        policies: Option<&[PolicyDecision]>,
+        columns: &[TableColumn],
+        use_colors: bool,
+        include_metadata: bool,
+        filter: Option<&str>,
+        with_header: bool,
+    ) -> String {
         if devices.is_empty() {
-            self.write_output("No USB devices found.\n")?;
-            return Ok(());
+            return "No USB devices found.\n".to_string();
         }
 
+        let mut output = String::new();
+
         // Header
-        let header = if self.use_colors {
-            format!(
-                "{:<4} {:<8} {:<8} {:<25} {:<25} {:<15} {:<20}",
-                "Bus".bold().blue(),
-                "VID:PID".bold().blue(),
-                "Address".bold().blue(),
-                "Manufacturer".bold().blue(),
-                "Product".bold().blue(),
-                "Class".bold().blue(),
-                "Timestamp".bold().blue()
-            )
-        } else {
-            format!(
-                "{:<4} {:<8} {:<8} {:<25} {:<25} {:<15} {:<20}",
-                "Bus", "VID:PID", "Address", "Manufacturer", "Product", "Class", "Timestamp"
-            )
-        };
+        let mut header = columns.iter()
+            .map(|column| {
+                let title = format!("{:<width$}", Self::column_title(*column), width = Self::column_width(*column));
+                if use_colors { title.bold().blue().to_string() } else { title }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if policies.is_some() {
+            if use_colors {
+                header.push_str(&format!(" {}", "Policy".bold().blue()));
+            } else {
+                header.push_str(" Policy");
+            }
+        }
         
-        self.write_output(&format!("{}\n", header))?;
-        self.write_output(&format!("{}\n", "-".repeat(110)))?;
+        let width: usize = columns.iter().map(|column| Self::column_width(*column) + 1).sum();
+        if with_header {
+            output.push_str(&format!("{}\n", header));
+            output.push_str(&format!("{}\n", "-".repeat(if policies.is_some() { width + 40 } else { width })));
+        }
 
         // Device rows
-        for device in devices {
-            let manufacturer = device.manufacturer.as_deref().unwrap_or("Unknown");
-            let product = device.product.as_deref().unwrap_or("Unknown");
-            let timestamp = device.timestamp.format("%H:%M:%S").to_string();
-            
-            let row = format!(
-                "{:<4} {:04X}:{:04X} {:<8} {:<25} {:<25} {:<15} {:<20}",
-                device.bus_number,
-                device.vendor_id,
-                device.product_id,
-                device.device_address,
-                Self::truncate_string(manufacturer, 25),
-                Self::truncate_string(product, 25),
-                format!("{:02X}", device.device_class),
-                timestamp
-            );
+        for (i, device) in devices.iter().enumerate() {
+            let mut row = Self::render_table_cells(device, columns, "%H:%M:%S");
+
+            if let Some(policy) = policies.and_then(|p| p.get(i)) {
+                let text = Self::format_policy(policy);
+                if use_colors {
+                    let colored = if policy.blocked { text.red().bold() } else { text.green() };
+                    row.push_str(&format!(" {}", colored));
+                } else {
+                    row.push_str(&format!(" {}", text));
+                }
+            }
             
-            self.write_output(&format!("{}\n", row))?;
+            output.push_str(&format!("{}\n", row));
         }
 
-        if self.include_metadata {
-            self.write_output(&format!("\nTotal devices: {}\n", devices.len()))?;
+        if include_metadata {
+            output.push_str(&format!("\nTotal devices: {}\n", devices.len()));
+            if let Some(filter) = filter {
+                output.push_str(&format!("Filter: {}\n", filter));
+            }
         }
 
-        Ok(())
+        output
+    }
+
+    fn column_title(column: TableColumn) -> &'static str {
+        match column {
+            TableColumn::Bus => "Bus",
+            TableColumn::VidPid => "VID:PID",
+            TableColumn::Address => "Address",
+            TableColumn::Manufacturer => "Manufacturer",
+            TableColumn::Product => "Product",
+            TableColumn::Class => "Class",
+            TableColumn::Timestamp => "Timestamp",
+        }
+    }
+
+    fn column_width(column: TableColumn) -> usize {
+        match column {
+            TableColumn::Bus => 4,
+            TableColumn::VidPid | TableColumn::Address => 8,
+            TableColumn::Manufacturer | TableColumn::Product => 25,
+            TableColumn::Class => 15,
+            TableColumn::Timestamp => 20,
+        }
+    }
+
+    /// A device's selected table cells, padded to their column widths
+    fn render_table_cells(device: &UsbDeviceInfo, columns: &[TableColumn], timestamp_format: &str) -> String {
+        columns.iter()
+            .map(|column| {
+                let value = match column {
+                    TableColumn::Bus => device.bus_number.to_string(),
+                    TableColumn::VidPid => format!("{:04X}:{:04X}", device.vendor_id, device.product_id),
+                    TableColumn::Address => device.device_address.to_string(),
+                    TableColumn::Manufacturer => Self::truncate_string(device.manufacturer.as_deref().unwrap_or("Unknown"), 25),
+                    TableColumn::Product => Self::truncate_string(device.product.as_deref().unwrap_or("Unknown"), 25),
+                    TableColumn::Class => format!("{:02X}", device.device_class),
+                    TableColumn::Timestamp => device.timestamp.format(timestamp_format).to_string(),
+                };
+                format!("{:<width$}", value, width = Self::column_width(*column))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
-    /// Output devices in CSV format
-    fn output_csv_devices(&mut self, devices: &[UsbDeviceInfo]) -> Result<()> {
+    /// Render devices in CSV format
+    fn render_csv_devices(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, with_header: bool) -> String {
+        let mut output = String::new();
+
         // CSV Header
-        let header = "Bus,VendorID,ProductID,Address,Manufacturer,Product,SerialNumber,DeviceClass,Timestamp";
-        self.write_output(&format!("{}\n", header))?;
+        if with_header {
+            let mut header = "Bus,VendorID,ProductID,Address,Manufacturer,Product,SerialNumber,DeviceClass,Timestamp,Fingerprint".to_string();
+            if policies.is_some() {
+                header.push_str(",Policy,PolicyReason");
+            }
+            output.push_str(&format!("{}\n", header));
+        }
 
         // Device rows
-        for device in devices {
+        for (i, device) in devices.iter().enumerate() {
             let manufacturer = device.manufacturer.as_deref().unwrap_or("");
             let product = device.product.as_deref().unwrap_or("");
             let serial = device.serial_number.as_deref().unwrap_or("");
             
-            let row = format!(
-                "{},{:04X},{:04X},{},{},{},{},{:02X},{}",
+            let mut row = format!(
+                "{},{:04X},{:04X},{},{},{},{},{:02X},{},{}",
                 device.bus_number,
                 device.vendor_id,
                 device.product_id,
@@ -443,51 +1328,215 @@ impl OutputManager {
                 Self::escape_csv_field(product),
                 Self::escape_csv_field(serial),
                 device.device_class,
-                device.timestamp.to_rfc3339()
+                device.timestamp.to_rfc3339(),
+                device.fingerprint
             );
+
+            if let Some(policy) = policies.and_then(|p| p.get(i)) {
+                row.push_str(&format!(",{},{}",
+                    policy.status(),
+                    Self::escape_csv_field(policy.reason.as_deref().unwrap_or(""))
+                ));
+            }
             
-            self.write_output(&format!("{}\n", row))?;
+            output.push_str(&format!("{}\n", row));
         }
 
-        Ok(())
+        output
     }
 
-    /// Output a device change in JSON format
-    fn output_json_change(&mut self, change: &UsbDeviceChange) -> Result<()> {
-        let json = serde_json::json!({
+    fn change_to_json(record: &ChangeRecord) -> serde_json::Value {
+        let change = &record.change;
+        let mut json = serde_json::json!({
             "change_type": change.get_change_type(),
             "device": change.get_device_info(),
-            "timestamp": Utc::now()
+            "timestamp": Utc::now(),
+            "prev_status": record.prev_status,
+            "since_ms": record.since_ms,
         });
+        if let Some(reason) = change.blocked_reason() {
+            json["reason"] = serde_json::Value::from(reason);
+        }
+        json
+    }
 
-        let output = serde_json::to_string(&json)
+    /// Render a device change as a single line of JSON
+    fn render_json_change(record: &ChangeRecord) -> Result<String> {
+        let output = serde_json::to_string(&Self::change_to_json(record))
             .context("Failed to serialize change to JSON")?;
         
-        self.write_output(&format!("{}\n", output))?;
-        Ok(())
+        Ok(format!("{}\n", output))
+    }
+
+    /// Render devices as a TOML document with one `[[devices]]` table per device, fields as in JSON
+    fn render_toml_devices(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, tags: &HashMap<String, String>, include_metadata: bool, filter: Option<&str>) -> Result<String> {
+        let mut document = serde_json::json!({ "devices": Self::devices_to_json(devices, policies, tags, false)? });
+        if include_metadata {
+            document["timestamp"] = serde_json::json!(Utc::now());
+            document["device_count"] = serde_json::json!(devices.len());
+            if let Some(filter) = filter {
+                document["filter"] = serde_json::json!(filter);
+            }
+        }
+        Self::render_toml_document(document)
+    }
+
+    /// Render a batch of changes as `[[changes]]` tables; appended batches stay one valid document
+    fn render_toml_changes(records: &[ChangeRecord]) -> Result<String> {
+        let changes: Vec<serde_json::Value> = records.iter().map(Self::change_to_json).collect();
+        Self::render_toml_document(serde_json::json!({ "changes": changes }))
+    }
+
+    /// Serialize a JSON object as TOML, leaving out null fields since TOML has no null
+    fn render_toml_document(document: serde_json::Value) -> Result<String> {
+        toml::to_string(&Self::without_nulls(document))
+            .context("Failed to serialize to TOML")
+    }
+
+    fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, Self::without_nulls(value)))
+                .collect(),
+            serde_json::Value::Array(items) => items.into_iter()
+                .filter(|value| !value.is_null())
+                .map(Self::without_nulls)
+                .collect(),
+            other => other,
+        }
     }
 
-    /// Output a device change in table format
-    fn output_table_change(&mut self, change: &UsbDeviceChange) -> Result<()> {
+    /// Render devices as an XML document with one `<device>` per device, fields as in JSON
+    fn render_xml_devices(devices: &[UsbDeviceInfo], policies: Option<&[PolicyDecision]>, tags: &HashMap<String, String>, include_metadata: bool, filter: Option<&str>) -> Result<String> {
+        let mut output = String::from(XML_DECLARATION);
+        if include_metadata {
+            let filter = filter.map(|filter| format!(" filter=\"{}\"", Self::escape_xml(filter))).unwrap_or_default();
+            output.push_str(&format!("<devices timestamp=\"{}\" device_count=\"{}\"{}>\n", Utc::now().to_rfc3339(), devices.len(), filter));
+        } else {
+            output.push_str("<devices>\n");
+        }
+
+        for device in Self::devices_to_json(devices, policies, tags, false)? {
+            Self::write_xml_element(&mut output, "device", &device, 1);
+        }
+
+        output.push_str("</devices>\n");
+        Ok(output)
+    }
+
+    /// Render a batch of changes as one `<changes>` document
+    fn render_xml_changes(records: &[ChangeRecord]) -> String {
+        let mut output = String::from(XML_DECLARATION);
+        output.push_str("<changes>\n");
+        for record in records {
+            Self::write_xml_element(&mut output, "change", &Self::change_to_json(record), 1);
+        }
+        output.push_str("</changes>\n");
+        output
+    }
+
+    fn render_xml_document(root: &str, value: &serde_json::Value) -> String {
+        let mut output = String::from(XML_DECLARATION);
+        Self::write_xml_element(&mut output, root, value, 0);
+        output
+    }
+
+    /// Write a JSON value as an XML element: objects become child elements, array items repeat
+    /// a singular child element, and keys that aren't valid names become `<entry key="...">`
+    fn write_xml_element(output: &mut String, name: &str, value: &serde_json::Value, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let (open, close) = if Self::is_xml_name(name) {
+            (name.to_string(), name)
+        } else {
+            (format!("entry key=\"{}\"", Self::escape_xml(name)), "entry")
+        };
+
+        match value {
+            serde_json::Value::Null => output.push_str(&format!("{}<{}/>\n", indent, open)),
+            serde_json::Value::Array(items) if items.is_empty() => output.push_str(&format!("{}<{}/>\n", indent, open)),
+            serde_json::Value::Object(fields) if fields.is_empty() => output.push_str(&format!("{}<{}/>\n", indent, open)),
+            serde_json::Value::Array(items) => {
+                let item_name = match name {
+                    "devices" | "current_devices" => "device",
+                    "changes" => "change",
+                    "intervals" => "interval",
+                    "security_events" => "security_event",
+                    _ => "item",
+                };
+                output.push_str(&format!("{}<{}>\n", indent, open));
+                for item in items {
+                    Self::write_xml_element(output, item_name, item, depth + 1);
+                }
+                output.push_str(&format!("{}</{}>\n", indent, close));
+            }
+            serde_json::Value::Object(fields) => {
+                output.push_str(&format!("{}<{}>\n", indent, open));
+                for (key, field) in fields {
+                    Self::write_xml_element(output, key, field, depth + 1);
+                }
+                output.push_str(&format!("{}</{}>\n", indent, close));
+            }
+            serde_json::Value::String(text) => {
+                output.push_str(&format!("{}<{}>{}</{}>\n", indent, open, Self::escape_xml(text), close));
+            }
+            scalar => output.push_str(&format!("{}<{}>{}</{}>\n", indent, open, scalar, close)),
+        }
+    }
+
+    /// Whether a key can be used as an element name as-is
+    fn is_xml_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            && !name.to_ascii_lowercase().starts_with("xml")
+    }
+
+    /// Escape text and attribute content; control characters XML can't carry become U+FFFD
+    fn escape_xml(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                '\t' | '\n' | '\r' => escaped.push(c),
+                c if (c as u32) < 0x20 => escaped.push('\u{FFFD}'),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    /// Render a device change in table format
+    fn render_table_change(change: &UsbDeviceChange, columns: &[TableColumn], use_colors: bool) -> String {
         let device = change.get_device_info();
         let change_type = change.get_change_type();
         let manufacturer = device.manufacturer.as_deref().unwrap_or("Unknown");
         let product = device.product.as_deref().unwrap_or("Unknown");
         let timestamp = device.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
 
-        let change_indicator = if self.use_colors {
+        let change_indicator = if use_colors {
             match change_type {
                 "CONNECTED" => format!("[{}]", "CONNECTED".green().bold()),
                 "DISCONNECTED" => format!("[{}]", "DISCONNECTED".red().bold()),
                 "RECONNECTED" => format!("[{}]", "RECONNECTED".yellow().bold()),
+                "SUSPENDED" => format!("[{}]", "SUSPENDED".blue().bold()),
                 _ => format!("[{}]", change_type),
             }
         } else {
             format!("[{}]", change_type)
         };
 
-        let output = format!(
-            "{} {} {:04X}:{:04X} {} - {} (Bus {}, Address {})",
+        // A custom column selection replaces the default one-line description
+        if columns != TableColumn::ALL {
+            return format!("{} {}\n", change_indicator, Self::render_table_cells(device, columns, "%Y-%m-%d %H:%M:%S").trim_end());
+        }
+
+        format!(
+            "{} {} {:04X}:{:04X} {} - {} (Bus {}, Address {})\n",
             timestamp,
             change_indicator,
             device.vendor_id,
@@ -496,90 +1545,103 @@ impl OutputManager {
             product,
             device.bus_number,
             device.device_address
-        );
-
-        self.write_output(&format!("{}\n", output))?;
-        Ok(())
+        )
     }
 
-    /// Output a device change in CSV format
-    fn output_csv_change(&mut self, change: &UsbDeviceChange) -> Result<()> {
+    /// Render a device change in CSV format
+    fn render_csv_change(change: &UsbDeviceChange) -> String {
         let device = change.get_device_info();
         let change_type = change.get_change_type();
         let manufacturer = device.manufacturer.as_deref().unwrap_or("");
         let product = device.product.as_deref().unwrap_or("");
 
-        let row = format!(
-            "{},{},{:04X},{:04X},{},{},{}",
+        let mut row = format!(
+            "{},{},{:04X},{:04X},{},{},{},{}",
             device.timestamp.to_rfc3339(),
             change_type,
             device.vendor_id,
             device.product_id,
             Self::escape_csv_field(manufacturer),
             Self::escape_csv_field(product),
-            device.bus_number
+            device.bus_number,
+            device.fingerprint
         );
+        // Blocked rows carry one extra field: why
+        if let Some(reason) = change.blocked_reason() {
+            row.push(',');
+            row.push_str(&Self::escape_csv_field(reason));
+        }
+        row.push('\n');
+        row
+    }
 
-        self.write_output(&format!("{}\n", row))?;
-        Ok(())
+    /// Write human-readable text to every table-format sink.
+    /// Structured sinks (JSON, NDJSON, CSV) are skipped so their streams stay parseable.
+    fn write_text<F>(&mut self, render: F) -> Result<()>
+    where
+        F: Fn(bool) -> String,
+    {
+        self.emit(|format, use_colors| match format {
+            OutputFormat::Table => Ok(render(use_colors)),
+            _ => Ok(String::new()),
+        })
     }
 
-    /// Write output to console and/or file
-    fn write_output(&mut self, content: &str) -> Result<()> {
-        // Write to console
-        print!("{}", content);
-        
-        // Write to file if configured
-        if let Some(ref mut file) = self.output_file {
-            file.write_all(content.as_bytes())
-                .context("Failed to write to output file")?;
+    /// Write an error, warning or info line like `write_text`, and to stderr as well when any
+    /// sink is structured, so the message isn't lost while the structured streams stay parseable
+    fn write_message<F>(&mut self, render: F) -> Result<()>
+    where
+        F: Fn(bool) -> String,
+    {
+        if self.sinks.iter().any(|sink| *sink.format() != OutputFormat::Table) {
+            eprint!("{}", render(self.use_colors));
         }
-        
-        Ok(())
+        self.write_text(render)
     }
 
     /// Display monitoring status
     pub fn display_monitoring_status(&mut self, device_count: usize, filter: Option<&str>) -> Result<()> {
-        let status = if self.use_colors {
-            format!(
-                "{} {} USB devices{}",
-                "Monitoring".green().bold(),
-                device_count.to_string().cyan().bold(),
-                match filter {
-                    Some(f) => format!(" (filtered: {})", f.yellow()),
-                    None => String::new(),
-                }
-            )
-        } else {
-            format!(
-                "Monitoring {} USB devices{}",
-                device_count,
-                match filter {
-                    Some(f) => format!(" (filtered: {})", f),
-                    None => String::new(),
-                }
-            )
-        };
+        if self.quiet {
+            return Ok(());
+        }
 
-        self.write_output(&format!("{}\n", status))?;
-        self.write_output("Press Ctrl+C to stop monitoring...\n\n")?;
-        Ok(())
-    }
+        self.write_text(|use_colors| {
+            let status = if use_colors {
+                format!(
+                    "{} {} USB devices{}",
+                    "Monitoring".green().bold(),
+                    device_count.to_string().cyan().bold(),
+                    match filter {
+                        Some(f) => format!(" (filtered: {})", f.yellow()),
+                        None => String::new(),
+                    }
+                )
+            } else {
+                format!(
+                    "Monitoring {} USB devices{}",
+                    device_count,
+                    match filter {
+                        Some(f) => format!(" (filtered: {})", f),
+                        None => String::new(),
+                    }
+                )
+            };
 
-    /// Clear the screen (for continuous monitoring)
-    pub fn clear_screen(&self) -> Result<()> {
-        execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0))
-            .context("Failed to clear screen")?;
-        Ok(())
+            format!("{}\nPress Ctrl+C to stop monitoring...\n\n", status)
+        })?;
+
+        self.flush()
     }
 
-    /// Truncate string to specified length
+    /// Truncate string to at most `max_len` characters, never splitting a UTF-8 character
     fn truncate_string(s: &str, max_len: usize) -> String {
-        if s.len() <= max_len {
-            s.to_string()
-        } else {
-            format!("{}...", &s[..max_len.saturating_sub(3)])
+        if s.chars().count() <= max_len {
+            return s.to_string();
         }
+
+        let keep = max_len.saturating_sub(3);
+        let end = s.char_indices().nth(keep).map(|(idx, _)| idx).unwrap_or(s.len());
+        format!("{}...", &s[..end])
     }
 
     /// Escape CSV field if it contains special characters
@@ -592,38 +1654,436 @@ impl OutputManager {
     }
 
     /// Display error message
+    #[allow(dead_code)]
     pub fn display_error(&mut self, error: &str) -> Result<()> {
-        let message = if self.use_colors {
-            format!("{}: {}", "Error".red().bold(), error)
-        } else {
-            format!("Error: {}", error)
-        };
-        
-        self.write_output(&format!("{}\n", message))?;
-        Ok(())
+        self.write_message(|use_colors| {
+            if use_colors {
+                format!("{}: {}\n", "Error".red().bold(), error)
+            } else {
+                format!("Error: {}\n", error)
+            }
+        })?;
+
+        self.flush()
     }
 
     /// Display warning message
     pub fn display_warning(&mut self, warning: &str) -> Result<()> {
-        let message = if self.use_colors {
-            format!("{}: {}", "Warning".yellow().bold(), warning)
-        } else {
-            format!("Warning: {}", warning)
-        };
-        
-        self.write_output(&format!("{}\n", message))?;
-        Ok(())
+        self.write_message(|use_colors| {
+            if use_colors {
+                format!("{}: {}\n", "Warning".yellow().bold(), warning)
+            } else {
+                format!("Warning: {}\n", warning)
+            }
+        })?;
+
+        self.flush()
     }
 
     /// Display info message
+    #[allow(dead_code)]
     pub fn display_info(&mut self, info: &str) -> Result<()> {
-        let message = if self.use_colors {
-            format!("{}: {}", "Info".blue().bold(), info)
-        } else {
-            format!("Info: {}", info)
+        if self.quiet {
+            return Ok(());
+        }
+
+        self.write_message(|use_colors| {
+            if use_colors {
+                format!("{}: {}\n", "Info".blue().bold(), info)
+            } else {
+                format!("Info: {}\n", info)
+            }
+        })?;
+
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usb_monitor::{ConnectionStatus, SecurityAction, SecurityEventType};
+    use std::collections::HashMap;
+
+    /// Collects everything written to it, for checking what a sink received
+    struct MemorySink {
+        format: OutputFormat,
+        content: std::sync::Arc<std::sync::Mutex<String>>,
+    }
+
+    impl Sink for MemorySink {
+        fn format(&self) -> &OutputFormat {
+            &self.format
+        }
+
+        fn supports_color(&self) -> bool {
+            false
+        }
+
+        fn write(&mut self, content: &str) -> Result<()> {
+            self.content.lock().unwrap().push_str(content);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn memory_sink(format: OutputFormat) -> (Box<dyn Sink>, std::sync::Arc<std::sync::Mutex<String>>) {
+        let content = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        (Box::new(MemorySink { format, content: content.clone() }), content)
+    }
+
+    fn sample_device() -> UsbDeviceInfo {
+        UsbDeviceInfo {
+            bus_number: 1,
+            device_address: 4,
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            device_version: 0x0100,
+            manufacturer: Some("Acme, Inc.".to_string()),
+            product: Some("Flash \"Drive\"".to_string()),
+            serial_number: Some("SN,001".to_string()),
+            device_class: 0x08,
+            device_subclass: 0,
+            device_protocol: 0,
+            max_packet_size: 64,
+            num_configurations: 1,
+            timestamp: Utc::now(),
+            connection_status: ConnectionStatus::Blocked,
+            fingerprint: String::new(),
+            raw_descriptor: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_history_escapes_free_text_fields() {
+        let device = sample_device();
+        let reason = "Blocked by rule \"mass storage\", vendor 1234";
+        let events = vec![SecurityEvent {
+            timestamp: Utc::now(),
+            event_type: SecurityEventType::DeviceBlocked,
+            device_info: device.clone(),
+            reason: reason.to_string(),
+            action_taken: SecurityAction::Blocked,
+            rule_id: Some("0123456789abcdef".to_string()),
+        }];
+        let analytics = DeviceAnalytics {
+            device_class_distribution: HashMap::new(),
+            vendor_distribution: HashMap::new(),
+            connection_frequency: Vec::new(),
+            total_devices_seen: 1,
+            unique_devices: 1,
+            unique_fingerprints: 1,
+            ignored_devices: 0,
+            blocked_devices: 1,
+            security_violations: 1,
         };
-        
-        self.write_output(&format!("{}\n", message))?;
-        Ok(())
+
+        let export_path = std::env::temp_dir()
+            .join(format!("ironwatch-csv-escape-{}.csv", std::process::id()));
+        let mut manager = OutputManager::with_sinks(OutputFormat::Csv, Vec::new(), false, false);
+        manager
+            .export_device_history(HistoryExport::Full {
+                devices: &[device],
+                device_stats: &[],
+                analytics: &analytics,
+                security_events: &events,
+            }, &export_path)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::remove_file(&export_path).ok();
+
+        let section = content
+            .split("\nSecurity Events\n")
+            .nth(1)
+            .and_then(|rest| rest.split("\n\n").next())
+            .unwrap();
+        let mut reader = csv::Reader::from_reader(section.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].len(), headers.len());
+        assert_eq!(&records[0][2], "Flash \"Drive\"");
+        assert_eq!(&records[0][5], reason);
+        assert_eq!(&records[0][6], "Blocked");
+        assert_eq!(&records[0][7], "0123456789abcdef");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_timeline_export_writes_one_row_per_interval() {
+        let connected_at = Utc::now();
+        let intervals = vec![
+            ConnectionInterval { device_key: "1234:5678".to_string(), connected_at, disconnected_at: Some(connected_at + chrono::Duration::seconds(30)) },
+            ConnectionInterval { device_key: "1234:5678".to_string(), connected_at: connected_at + chrono::Duration::seconds(60), disconnected_at: None },
+        ];
+
+        let export_path = std::env::temp_dir()
+            .join(format!("ironwatch-timeline-{}.csv", std::process::id()));
+        let mut manager = OutputManager::with_sinks(OutputFormat::Csv, Vec::new(), false, false);
+        manager.export_device_history(HistoryExport::Timeline(&intervals), &export_path).unwrap();
+
+        let content = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::remove_file(&export_path).ok();
+
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(&records[0][0], "1234:5678");
+        assert!(!records[0][2].is_empty());
+        assert_eq!(&records[1][2], "");
+    }
+
+    #[test]
+    fn test_each_sink_gets_its_own_format_and_messages_stay_out_of_structured_ones() {
+        let (table_sink, table) = memory_sink(OutputFormat::Table);
+        let (json_sink, json) = memory_sink(OutputFormat::Json);
+        let mut manager = OutputManager::with_sinks(OutputFormat::Table, vec![table_sink, json_sink], false, false);
+
+        manager.display_devices(&[sample_device()]).unwrap();
+        manager.display_error("libusb went away").unwrap();
+
+        let table = table.lock().unwrap();
+        assert!(table.contains("Flash \"Drive\""));
+        assert!(table.contains("Error: libusb went away"));
+        let devices: Vec<UsbDeviceInfo> = serde_json::from_str(&json.lock().unwrap()).unwrap();
+        assert_eq!(devices[0].product_id, 0x5678);
+    }
+
+    #[test]
+    fn test_list_output_shows_each_devices_policy_decision() {
+        let (table_sink, table) = memory_sink(OutputFormat::Table);
+        let (json_sink, json) = memory_sink(OutputFormat::Json);
+        let mut manager = OutputManager::with_sinks(OutputFormat::Table, vec![table_sink, json_sink], false, false);
+
+        let mut allowed = sample_device();
+        allowed.device_address = 5;
+        let policies = [
+            PolicyDecision { blocked: true, reason: Some("Vendor not allowed".to_string()) },
+            PolicyDecision { blocked: false, reason: None },
+        ];
+        manager.display_devices_with_policy(&[sample_device(), allowed], &policies).unwrap();
+
+        assert!(table.lock().unwrap().contains("BLOCKED (Vendor not allowed)"));
+        let devices: serde_json::Value = serde_json::from_str(&json.lock().unwrap()).unwrap();
+        assert_eq!(devices[0]["policy"]["status"], "BLOCKED");
+        assert_eq!(devices[0]["policy"]["reason"], "Vendor not allowed");
+        assert_eq!(devices[1]["policy"]["status"], "ALLOWED");
+    }
+
+    #[test]
+    fn test_flush_and_close_writes_all_buffered_changes() {
+        let path = std::env::temp_dir()
+            .join(format!("ironwatch-flush-close-{}.ndjson", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let sinks: Vec<Box<dyn Sink>> = vec![Box::new(FileSink::open(OutputFormat::Ndjson, &path, FileMode::Append).unwrap())];
+        let mut manager = OutputManager::with_sinks(OutputFormat::Ndjson, sinks, false, false);
+
+        // Well past the BufWriter's capacity, and in several batches
+        let changes: Vec<UsbDeviceChange> = (0..200)
+            .map(|_| UsbDeviceChange::Connected(sample_device()))
+            .collect();
+        for batch in changes.chunks(50) {
+            manager.display_changes(batch).unwrap();
+        }
+
+        // Shutdown: nothing after this may be lost or written
+        manager.flush_and_close().unwrap();
+        manager.display_changes(&changes[..1]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 200);
+        assert!(lines.iter().all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok()));
+    }
+
+    #[test]
+    fn test_daily_file_sink_rolls_over_on_first_write_of_a_new_day() {
+        let dir = std::env::temp_dir().join(format!("ironwatch-daily-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("events.ndjson");
+
+        let mut sink = DailyFileSink::open(OutputFormat::Ndjson, &base, true, FileMode::Append).unwrap();
+        let today = sink.day;
+
+        // Pretend the sink was opened yesterday; nothing moves until the next write
+        let yesterday = today.pred_opt().unwrap();
+        sink.day = yesterday;
+        sink.write("{}\n").unwrap();
+        sink.flush().unwrap();
+
+        let today_path = dir.join(format!("events-{}.ndjson", today.format("%Y-%m-%d")));
+        assert_eq!(std::fs::read_to_string(&today_path).unwrap(), "{}\n");
+        assert_eq!(sink.day, today);
+        #[cfg(unix)]
+        assert_eq!(std::fs::read_to_string(dir.join("events-latest.ndjson")).unwrap(), "{}\n");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_output_file_directories_are_created() {
+        let root = std::env::temp_dir().join(format!("ironwatch-output-dirs-{}", std::process::id()));
+        let path = root.join("some/new/dir/out.json");
+
+        let mut manager = OutputManager::new(OutputFormat::Json, Some(path.clone()), false, false, FileRotation::None, false, FileMode::Append).unwrap();
+        manager.flush_and_close().unwrap();
+        let created = path.is_file();
+        std::fs::remove_dir_all(&root).ok();
+
+        assert!(created);
+    }
+
+    #[test]
+    fn test_csv_header_is_written_once_per_open_and_truncate_overwrites() {
+        let path = std::env::temp_dir().join(format!("ironwatch-file-mode-{}.csv", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let write_lists = |mode: FileMode, lists: usize| {
+            let sinks: Vec<Box<dyn Sink>> = vec![Box::new(FileSink::open(OutputFormat::Csv, &path, mode).unwrap())];
+            let mut manager = OutputManager::with_sinks(OutputFormat::Csv, sinks, false, false);
+            for _ in 0..lists {
+                manager.display_devices(&[sample_device()]).unwrap();
+            }
+            manager.flush_and_close().unwrap();
+            std::fs::read_to_string(&path).unwrap()
+        };
+
+        let content = write_lists(FileMode::Append, 2);
+        assert_eq!(content.matches("Bus,VendorID").count(), 1);
+        assert_eq!(content.lines().count(), 3);
+
+        assert_eq!(write_lists(FileMode::Append, 1).lines().count(), 5);
+        let content = write_lists(FileMode::Truncate, 1);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(content.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_blocked_changes_carry_their_reason() {
+        let reason = "Untrusted vendor, mass storage (rule 0123456789abcdef)";
+        let blocked = UsbDeviceChange::Blocked(sample_device(), reason.to_string());
+        let connected = UsbDeviceChange::Connected(sample_device());
+
+        let mut tracker = ChangeTracker::default();
+        let json: serde_json::Value = serde_json::from_str(&OutputManager::render_json_change(&tracker.record(blocked.clone())).unwrap()).unwrap();
+        assert_eq!(json["reason"], reason);
+        let json: serde_json::Value = serde_json::from_str(&OutputManager::render_json_change(&tracker.record(connected.clone())).unwrap()).unwrap();
+        assert!(json.get("reason").is_none());
+
+        let csv = OutputManager::render_csv_change(&blocked) + &OutputManager::render_csv_change(&connected);
+        let records: Vec<csv::StringRecord> = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(csv.as_bytes())
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(&records[0][8], reason);
+        assert_eq!(records[1].len(), 8);
+    }
+
+    #[test]
+    fn test_json_changes_carry_previous_status_and_elapsed_time() {
+        let mut tracker = ChangeTracker::default();
+        let mut device = sample_device();
+        device.connection_status = ConnectionStatus::Disconnected;
+        let disconnected = tracker.record(UsbDeviceChange::Disconnected(device.clone()));
+
+        device.connection_status = ConnectionStatus::Reconnected;
+        device.timestamp += chrono::Duration::seconds(120);
+        let reconnected = tracker.record(UsbDeviceChange::Reconnected(device));
+
+        let json: serde_json::Value = serde_json::from_str(&OutputManager::render_json_change(&disconnected).unwrap()).unwrap();
+        assert!(json["prev_status"].is_null());
+        assert!(json["since_ms"].is_null());
+        let json: serde_json::Value = serde_json::from_str(&OutputManager::render_json_change(&reconnected).unwrap()).unwrap();
+        assert_eq!(json["prev_status"], "Disconnected");
+        assert_eq!(json["since_ms"], 120_000);
+    }
+
+    #[test]
+    fn test_xml_output_escapes_text_and_mirrors_json_fields() {
+        let xml = OutputManager::render_xml_devices(&[sample_device()], None, &HashMap::new(), false, None).unwrap();
+        assert!(xml.starts_with(XML_DECLARATION));
+        assert!(xml.contains("<devices>\n  <device>\n"));
+        assert!(xml.contains("<manufacturer>Acme, Inc.</manufacturer>"));
+        assert!(xml.contains("<product>Flash &quot;Drive&quot;</product>"));
+        assert!(xml.contains("<vendor_id>4660</vendor_id>"));
+        assert!(xml.trim_end().ends_with("</devices>"));
+
+        let blocked = UsbDeviceChange::Blocked(sample_device(), "Vendor <untrusted> & \u{1}odd".to_string());
+        let xml = OutputManager::render_xml_changes(&[ChangeTracker::default().record(blocked)]);
+        assert!(xml.contains("<change_type>BLOCKED</change_type>"));
+        assert!(xml.contains("<reason>Vendor &lt;untrusted&gt; &amp; \u{FFFD}odd</reason>"));
+
+        let mut distribution = serde_json::Map::new();
+        distribution.insert("8".to_string(), serde_json::json!(2));
+        let xml = OutputManager::render_xml_document("vendor_distribution", &serde_json::Value::Object(distribution));
+        assert!(xml.contains("<entry key=\"8\">2</entry>"));
+    }
+
+    #[test]
+    fn test_toml_devices_and_changes_round_trip() {
+        #[derive(serde::Deserialize)]
+        struct Document {
+            device_count: usize,
+            devices: Vec<UsbDeviceInfo>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Changes {
+            changes: Vec<UsbDeviceChange>,
+        }
+
+        let mut device = sample_device();
+        device.serial_number = None;
+        device.update_fingerprint();
+
+        let toml_output = OutputManager::render_toml_devices(&[device.clone(), sample_device()], None, &HashMap::new(), true, None).unwrap();
+        assert!(toml_output.contains("[[devices]]"));
+        let document: Document = toml::from_str(&toml_output).unwrap();
+        assert_eq!(document.device_count, 2);
+        assert_eq!(document.devices[0].serial_number, None);
+        assert_eq!(document.devices[0].fingerprint, device.fingerprint);
+        assert_eq!(document.devices[1].product.as_deref(), Some("Flash \"Drive\""));
+        assert_eq!(document.devices[1].connection_status, ConnectionStatus::Blocked);
+
+        let blocked = UsbDeviceChange::Blocked(device, "Vendor not allowed".to_string());
+        let toml_output = OutputManager::render_toml_changes(&[ChangeTracker::default().record(blocked)]).unwrap();
+        let changes: Changes = toml::from_str(&toml_output).unwrap();
+        assert!(matches!(&changes.changes[..], [UsbDeviceChange::Blocked(device, reason)]
+            if device.product_id == 0x5678 && reason == "Vendor not allowed"));
+    }
+
+    #[test]
+    fn test_raw_descriptor_is_only_in_json_device_output() {
+        let mut device = sample_device();
+        device.raw_descriptor = Some("120100020000004034127856000101020301".to_string());
+        let devices = [device.clone()];
+        let tags = HashMap::new();
+
+        let json_output = OutputManager::render_json_devices(&devices, None, &tags, false, None).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json_output).unwrap();
+        assert_eq!(json[0]["raw_descriptor"], "120100020000004034127856000101020301");
+        let ndjson_output = OutputManager::render_ndjson_devices(&devices, None, &tags).unwrap();
+        assert!(ndjson_output.contains("\"raw_descriptor\""));
+
+        assert!(!OutputManager::render_xml_devices(&devices, None, &tags, false, None).unwrap().contains("raw_descriptor"));
+        assert!(!OutputManager::render_toml_devices(&devices, None, &tags, false, None).unwrap().contains("raw_descriptor"));
+        assert!(!serde_json::to_string(&device).unwrap().contains("raw_descriptor"));
+    }
+
+    #[test]
+    fn test_truncate_string_is_char_boundary_safe() {
+        // "Größenwahnsinn": byte 3 falls inside the two-byte "ö"
+        assert_eq!(OutputManager::truncate_string("Größenwahnsinn", 6), "Grö...");
+        assert_eq!(OutputManager::truncate_string("Größenwahnsinn", 5), "Gr...");
+        assert_eq!(OutputManager::truncate_string("Größenwahnsinn", 14), "Größenwahnsinn");
+        assert_eq!(OutputManager::truncate_string("Größenwahnsinn", 2), "...");
+    }
+}
@@ -0,0 +1,154 @@
+//! Replay of recorded NDJSON change streams through the live pipeline.
+//!
+//! Records written by `monitor --format ndjson` are turned back into `UsbDeviceChange`s,
+//! re-checked against the current device rules and displayed through `OutputManager`.
+
+use crate::config::ConfigManager;
+use crate::output::OutputManager;
+use crate::usb_backend::UsbBackend;
+use crate::usb_monitor::{UsbDeviceChange, UsbDeviceInfo, UsbMonitor};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A change read back from a recording, with the time it was originally emitted
+#[derive(Debug, Clone)]
+pub struct RecordedChange {
+    pub timestamp: DateTime<Utc>,
+    pub change: UsbDeviceChange,
+}
+
+/// Backend with no hardware behind it; replayed changes are the only input
+struct NoDevices;
+
+impl UsbBackend for NoDevices {
+    fn enumerate(&self) -> Result<Vec<UsbDeviceInfo>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Parse change records from NDJSON, skipping heartbeats, summaries and other non-change lines
+pub fn parse_recording<R: BufRead>(reader: R) -> Result<Vec<RecordedChange>> {
+    let mut changes = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.context("Failed to read recording")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: serde_json::Value = serde_json::from_str(&line)
+            .with_context(|| format!("Line {} is not valid JSON", index + 1))?;
+
+        let Some(change_type) = record.get("change_type").and_then(|value| value.as_str()) else {
+            debug!("Skipping non-change record on line {}", index + 1);
+            continue;
+        };
+
+        if !UsbDeviceChange::CHANGE_TYPES.contains(&change_type) {
+            warn!("Skipping unknown change type '{}' on line {}", change_type, index + 1);
+            continue;
+        }
+
+        let change: UsbDeviceChange = serde_json::from_value(record.clone())
+            .with_context(|| format!("Line {} has an invalid device record", index + 1))?;
+
+        let timestamp = record.get("timestamp")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or(change.get_device_info().timestamp);
+
+        changes.push(RecordedChange { timestamp, change });
+    }
+
+    Ok(changes)
+}
+
+/// Feed a recording through rule evaluation and output, honouring its timing scaled by `speed`.
+///
+/// A `speed` of 0 replays without any delay.
+pub async fn run_replay(
+    path: &Path,
+    speed: f64,
+    config_manager: ConfigManager,
+    output_manager: &mut OutputManager,
+    shutdown_flag: &AtomicBool,
+) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open recording: {}", path.display()))?;
+    let recording = parse_recording(std::io::BufReader::new(file))?;
+    info!("Replaying {} recorded changes from {}", recording.len(), path.display());
+
+    let mut usb_monitor = UsbMonitor::with_backend(Box::new(NoDevices));
+    usb_monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config_manager)));
+
+    let mut previous: Option<DateTime<Utc>> = None;
+    for recorded in recording {
+        if shutdown_flag.load(Ordering::Relaxed) {
+            info!("Replay interrupted");
+            break;
+        }
+
+        if let (Some(previous), true) = (previous, speed > 0.0) {
+            let gap = (recorded.timestamp - previous).to_std().unwrap_or(Duration::ZERO);
+            tokio::time::sleep(gap.div_f64(speed)).await;
+        }
+        previous = Some(recorded.timestamp);
+
+        let change = usb_monitor.apply_recorded_change(recorded.change).await;
+        output_manager.display_changes(&[change])
+            .context("Failed to display replayed change")?;
+    }
+
+    output_manager.display_summary(&usb_monitor.get_session_summary())
+        .context("Failed to display session summary")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeviceRule;
+
+    const RECORDING: &str = r#"{"change_type":"CONNECTED","device":{"bus_number":1,"device_address":4,"vendor_id":57005,"product_id":1,"device_version":256,"manufacturer":"Acme","product":"Widget","serial_number":null,"device_class":8,"device_subclass":0,"device_protocol":0,"max_packet_size":64,"num_configurations":1,"timestamp":"2024-01-01T00:00:00Z","connection_status":"Connected"},"timestamp":"2024-01-01T00:00:00Z"}
+{"event":"heartbeat","ts":"2024-01-01T00:00:30Z","devices":1}
+{"change_type":"DISCONNECTED","device":{"bus_number":1,"device_address":4,"vendor_id":57005,"product_id":1,"device_version":256,"manufacturer":"Acme","product":"Widget","serial_number":null,"device_class":8,"device_subclass":0,"device_protocol":0,"max_packet_size":64,"num_configurations":1,"timestamp":"2024-01-01T00:01:00Z","connection_status":"Disconnected"},"timestamp":"2024-01-01T00:01:00Z"}
+"#;
+
+    #[test]
+    fn test_parse_recording_skips_non_change_records() {
+        let recording = parse_recording(RECORDING.as_bytes()).unwrap();
+
+        assert_eq!(recording.len(), 2);
+        assert!(matches!(recording[0].change, UsbDeviceChange::Connected(_)));
+        assert!(matches!(recording[1].change, UsbDeviceChange::Disconnected(_)));
+        assert_eq!((recording[1].timestamp - recording[0].timestamp).num_seconds(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_arrival_is_checked_against_current_rules() {
+        let config_path = std::env::temp_dir()
+            .join(format!("ironwatch-replay-test-{}-missing.json", std::process::id()));
+        let mut config = ConfigManager::new(Some(config_path)).unwrap();
+        config.add_blacklisted_device(DeviceRule {
+            vendor_id: Some(0xdead),
+            reason: "Untrusted vendor".to_string(),
+            ..DeviceRule::new()
+        }).unwrap();
+
+        let mut usb_monitor = UsbMonitor::with_backend(Box::new(NoDevices));
+        usb_monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config)));
+
+        let recording = parse_recording(RECORDING.as_bytes()).unwrap();
+        let change = usb_monitor.apply_recorded_change(recording[0].change.clone()).await;
+
+        assert!(matches!(change, UsbDeviceChange::Blocked(..)));
+        assert_eq!(usb_monitor.get_session_summary().total_blocked, 1);
+    }
+}
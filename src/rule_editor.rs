@@ -0,0 +1,214 @@
+//! Form for creating a blacklist or whitelist rule in the GUI.
+//!
+//! Input is validated as it is typed: IDs must be hex of the right width, the reason is
+//! required, and a rule needs at least one matching criterion, since an empty rule matches
+//! every device. Save stays disabled until the form is valid; Enter saves and Escape cancels.
+
+use crate::config::DeviceRule;
+
+use eframe::egui::{self, Color32, Key, RichText};
+
+/// Which list a new rule goes into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleList {
+    Blacklist,
+    Whitelist,
+}
+
+/// What the user did with the editor this frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleEditorAction {
+    Save(RuleList, Box<DeviceRule>),
+    Cancel,
+}
+
+/// Validation problems, one slot per field
+#[derive(Debug, Default, PartialEq)]
+struct RuleErrors {
+    vendor_id: Option<String>,
+    product_id: Option<String>,
+    device_class: Option<String>,
+    reason: Option<String>,
+    /// Set when no matching criterion is filled in
+    no_criteria: Option<String>,
+}
+
+impl RuleErrors {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleEditor {
+    list: RuleList,
+    vendor_id: String,
+    product_id: String,
+    device_class: String,
+    manufacturer: String,
+    product_name: String,
+    serial_number: String,
+    reason: String,
+}
+
+impl RuleEditor {
+    pub fn new(list: RuleList) -> Self {
+        Self {
+            list,
+            vendor_id: String::new(),
+            product_id: String::new(),
+            device_class: String::new(),
+            manufacturer: String::new(),
+            product_name: String::new(),
+            serial_number: String::new(),
+            reason: String::new(),
+        }
+    }
+
+    /// Draw the form; returns an action once the user saves a valid rule or cancels
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<RuleEditorAction> {
+        ui.horizontal(|ui| {
+            ui.label("List:");
+            ui.radio_value(&mut self.list, RuleList::Blacklist, "Blacklist (block)");
+            ui.radio_value(&mut self.list, RuleList::Whitelist, "Whitelist (allow)");
+        });
+        ui.add_space(8.0);
+
+        let errors = self.validate();
+
+        egui::Grid::new("rule_editor_grid")
+            .num_columns(2)
+            .spacing([10.0, 6.0])
+            .show(ui, |ui| {
+                Self::field(ui, "Vendor ID", &mut self.vendor_id, "e.g. 0951", errors.vendor_id.as_deref());
+                Self::field(ui, "Product ID", &mut self.product_id, "e.g. 1666", errors.product_id.as_deref());
+                Self::field(ui, "Device class", &mut self.device_class, "e.g. 08", errors.device_class.as_deref());
+                Self::field(ui, "Manufacturer", &mut self.manufacturer, "", None);
+                Self::field(ui, "Product name", &mut self.product_name, "", None);
+                Self::field(ui, "Serial number", &mut self.serial_number, "", None);
+                Self::field(ui, "Reason *", &mut self.reason, "Why this rule exists", errors.reason.as_deref());
+            });
+
+        if let Some(warning) = &errors.no_criteria {
+            ui.add_space(6.0);
+            ui.colored_label(Color32::from_rgb(255, 165, 0), format!("⚠ {}", warning));
+        }
+
+        ui.add_space(10.0);
+
+        let valid = errors.is_empty();
+        let (enter, escape) = ui.input(|input| (input.key_pressed(Key::Enter), input.key_pressed(Key::Escape)));
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            let save = ui.add_enabled(valid, egui::Button::new("💾 Save Rule"));
+            if save.clicked() || (valid && enter) {
+                action = self.build().map(|rule| RuleEditorAction::Save(self.list, Box::new(rule)));
+            }
+            if ui.button("Cancel").clicked() || escape {
+                action = Some(RuleEditorAction::Cancel);
+            }
+        });
+
+        action
+    }
+
+    fn field(ui: &mut egui::Ui, label: &str, value: &mut String, hint: &str, error: Option<&str>) {
+        ui.label(label);
+        ui.vertical(|ui| {
+            ui.add(egui::TextEdit::singleline(value).hint_text(hint).desired_width(220.0));
+            if let Some(error) = error {
+                ui.label(RichText::new(error).small().color(Color32::RED));
+            }
+        });
+        ui.end_row();
+    }
+
+    fn validate(&self) -> RuleErrors {
+        let mut errors = RuleErrors {
+            vendor_id: hex_error(&self.vendor_id, 4, "Vendor ID"),
+            product_id: hex_error(&self.product_id, 4, "Product ID"),
+            device_class: hex_error(&self.device_class, 2, "Device class"),
+            ..RuleErrors::default()
+        };
+
+        if self.reason.trim().is_empty() {
+            errors.reason = Some("A reason is required".to_string());
+        }
+
+        let criteria = [
+            &self.vendor_id,
+            &self.product_id,
+            &self.device_class,
+            &self.manufacturer,
+            &self.product_name,
+            &self.serial_number,
+        ];
+        if criteria.iter().all(|value| value.trim().is_empty()) {
+            errors.no_criteria = Some(match self.list {
+                RuleList::Blacklist => "This rule has no criteria: this would block all devices".to_string(),
+                RuleList::Whitelist => "This rule has no criteria: this would allow all devices".to_string(),
+            });
+        }
+
+        errors
+    }
+
+    /// The rule described by the form, or `None` while it is invalid
+    fn build(&self) -> Option<DeviceRule> {
+        if !self.validate().is_empty() {
+            return None;
+        }
+
+        let hex = |value: &str| u16::from_str_radix(value.trim(), 16).ok();
+        let text = |value: &str| Some(value.trim()).filter(|value| !value.is_empty()).map(str::to_string);
+
+        let mut rule = DeviceRule::new();
+        rule.vendor_id = hex(&self.vendor_id);
+        rule.product_id = hex(&self.product_id);
+        rule.device_class = hex(&self.device_class).map(|class| class as u8);
+        rule.manufacturer = text(&self.manufacturer);
+        rule.product_name = text(&self.product_name);
+        rule.serial_number = text(&self.serial_number);
+        rule.reason = self.reason.trim().to_string();
+        Some(rule)
+    }
+}
+
+/// Empty is fine; otherwise exactly `digits` hex digits
+fn hex_error(value: &str, digits: usize, name: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || (value.len() == digits && value.chars().all(|c| c.is_ascii_hexdigit())) {
+        None
+    } else {
+        Some(format!("{} must be {} hex digits", name, digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_need_valid_ids_a_criterion_and_a_reason() {
+        let mut editor = RuleEditor::new(RuleList::Blacklist);
+        let errors = editor.validate();
+        assert!(errors.no_criteria.as_deref().unwrap().contains("block all devices"));
+        assert!(errors.reason.is_some());
+        assert!(editor.build().is_none());
+
+        editor.vendor_id = "951".to_string();
+        editor.reason = "Unapproved storage".to_string();
+        assert!(editor.validate().vendor_id.is_some());
+        editor.vendor_id = "09zz".to_string();
+        assert!(editor.validate().vendor_id.is_some());
+
+        editor.vendor_id = "0951".to_string();
+        editor.manufacturer = "  Kingston ".to_string();
+        let rule = editor.build().unwrap();
+        assert_eq!(rule.vendor_id, Some(0x0951));
+        assert_eq!(rule.product_id, None);
+        assert_eq!(rule.manufacturer.as_deref(), Some("Kingston"));
+        assert_eq!(rule.reason, "Unapproved storage");
+    }
+}
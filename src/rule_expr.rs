@@ -0,0 +1,355 @@
+//! Boolean expressions over device fields for rules the structured fields can't express.
+//!
+//! ```text
+//! expr       := and ( "||" and )*
+//! and        := unary ( "&&" unary )*
+//! unary      := "!" unary | "(" expr ")" | comparison
+//! comparison := field ( "==" | "!=" ) literal
+//! literal    := 0x1f | 31 | "text"
+//! ```
+//!
+//! Numeric fields are `vendor_id`, `product_id`, `class`, `subclass`, `protocol`, `bus` and
+//! `address`; string fields are `manufacturer`, `product` and `serial`, compared without regard
+//! to case. A missing string equals nothing. Evaluation only reads the device.
+
+use crate::usb_monitor::UsbDeviceInfo;
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    VendorId,
+    ProductId,
+    Class,
+    Subclass,
+    Protocol,
+    Bus,
+    Address,
+    Manufacturer,
+    Product,
+    Serial,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vendor_id" | "vid" => Some(Field::VendorId),
+            "product_id" | "pid" => Some(Field::ProductId),
+            "class" | "device_class" => Some(Field::Class),
+            "subclass" => Some(Field::Subclass),
+            "protocol" => Some(Field::Protocol),
+            "bus" => Some(Field::Bus),
+            "address" => Some(Field::Address),
+            "manufacturer" => Some(Field::Manufacturer),
+            "product" => Some(Field::Product),
+            "serial" | "serial_number" => Some(Field::Serial),
+            _ => None,
+        }
+    }
+
+    fn is_string(&self) -> bool {
+        matches!(self, Field::Manufacturer | Field::Product | Field::Serial)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Literal),
+    Eq,
+    NotEq,
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Compare { field: Field, equal: bool, value: Literal },
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn matches(&self, device: &UsbDeviceInfo) -> bool {
+        match self {
+            Node::Compare { field, equal, value } => field_equals(device, *field, value) == *equal,
+            Node::Not(inner) => !inner.matches(device),
+            Node::And(left, right) => left.matches(device) && right.matches(device),
+            Node::Or(left, right) => left.matches(device) || right.matches(device),
+        }
+    }
+}
+
+/// A parsed rule expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleExpr {
+    root: Node,
+}
+
+impl RuleExpr {
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let root = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            bail!("Unexpected {:?} in rule expression '{}'", token, source);
+        }
+        Ok(Self { root })
+    }
+
+    /// Whether the device satisfies the expression
+    pub fn matches(&self, device: &UsbDeviceInfo) -> bool {
+        self.root.matches(device)
+    }
+}
+
+fn field_equals(device: &UsbDeviceInfo, field: Field, value: &Literal) -> bool {
+    let text = match field {
+        Field::Manufacturer => &device.manufacturer,
+        Field::Product => &device.product,
+        Field::Serial => &device.serial_number,
+        _ => {
+            let number = match field {
+                Field::VendorId => device.vendor_id as u64,
+                Field::ProductId => device.product_id as u64,
+                Field::Class => device.device_class as u64,
+                Field::Subclass => device.device_subclass as u64,
+                Field::Protocol => device.device_protocol as u64,
+                Field::Bus => device.bus_number as u64,
+                _ => device.device_address as u64,
+            };
+            return *value == Literal::Number(number);
+        }
+    };
+
+    match (text, value) {
+        (Some(text), Literal::Text(expected)) => text.eq_ignore_ascii_case(expected),
+        _ => false,
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' | '!' | '&' | '|' => {
+                let pair = chars.peek().map(|(_, next)| *next);
+                match (c, pair) {
+                    ('=', Some('=')) => { chars.next(); Token::Eq }
+                    ('!', Some('=')) => { chars.next(); Token::NotEq }
+                    ('&', Some('&')) => { chars.next(); Token::And }
+                    ('|', Some('|')) => { chars.next(); Token::Or }
+                    ('!', _) => Token::Not,
+                    _ => bail!("Unexpected '{}' at position {} in rule expression", c, start),
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => text.push(escaped),
+                            None => bail!("Unterminated string in rule expression"),
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => bail!("Unterminated string in rule expression"),
+                    }
+                }
+                Token::Literal(Literal::Text(text))
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some((_, next)) = chars.peek().filter(|(_, next)| next.is_ascii_alphanumeric() || *next == '_') {
+                    word.push(*next);
+                    chars.next();
+                }
+
+                if c.is_ascii_digit() {
+                    let number = match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+                        Some(hex) => u64::from_str_radix(hex, 16),
+                        None => word.parse(),
+                    };
+                    match number {
+                        Ok(number) => Token::Literal(Literal::Number(number)),
+                        Err(_) => bail!("Invalid number '{}' in rule expression", word),
+                    }
+                } else {
+                    Token::Ident(word)
+                }
+            }
+            _ => bail!("Unexpected '{}' at position {} in rule expression", c, start),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Node::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Node::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        match self.advance() {
+            Some(Token::Not) => Ok(Node::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    bail!("Missing ')' in rule expression");
+                }
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                let field = Field::from_name(&name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown field '{}' in rule expression", name))?;
+                let equal = match self.advance() {
+                    Some(Token::Eq) => true,
+                    Some(Token::NotEq) => false,
+                    _ => bail!("Expected '==' or '!=' after '{}' in rule expression", name),
+                };
+                let value = match self.advance() {
+                    Some(Token::Literal(value)) => value,
+                    _ => bail!("Expected a value after '{}' in rule expression", name),
+                };
+                if field.is_string() != matches!(value, Literal::Text(_)) {
+                    let expected = if field.is_string() { "a quoted string" } else { "a number" };
+                    bail!("Field '{}' must be compared with {}", name, expected);
+                }
+                Ok(Node::Compare { field, equal, value })
+            }
+            Some(token) => bail!("Unexpected {:?} in rule expression", token),
+            None => bail!("Rule expression ended unexpectedly"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usb_monitor::ConnectionStatus;
+
+    fn storage(manufacturer: Option<&str>, serial: Option<&str>) -> UsbDeviceInfo {
+        UsbDeviceInfo {
+            bus_number: 1,
+            device_address: 4,
+            vendor_id: 0x0951,
+            product_id: 0x1666,
+            device_version: 0x0100,
+            manufacturer: manufacturer.map(str::to_string),
+            product: Some("DataTraveler".to_string()),
+            serial_number: serial.map(str::to_string),
+            device_class: 0x08,
+            device_subclass: 0x06,
+            device_protocol: 0x50,
+            max_packet_size: 64,
+            num_configurations: 1,
+            timestamp: chrono::Utc::now(),
+            connection_status: ConnectionStatus::Connected,
+            fingerprint: String::new(),
+            raw_descriptor: None,
+        }
+    }
+
+    fn matches(source: &str, device: &UsbDeviceInfo) -> bool {
+        RuleExpr::parse(source).unwrap().matches(device)
+    }
+
+    #[test]
+    fn test_comparisons_and_hex_literals() {
+        let device = storage(Some("Kingston"), Some("ABC123"));
+
+        assert!(matches("class == 0x08", &device));
+        assert!(matches("class == 8", &device));
+        assert!(matches("vid == 0x0951 && pid == 0x1666", &device));
+        assert!(!matches("class != 0x08", &device));
+        assert!(matches("manufacturer == \"kingston\"", &device));
+        assert!(!matches("manufacturer != \"Kingston\"", &device));
+        assert!(matches("!(serial == \"XYZ\")", &device));
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let device = storage(Some("Kingston"), None);
+
+        // Parsed as `class == 3 || (class == 8 && product == "DataTraveler")`
+        assert!(matches("class == 3 || class == 8 && product == \"DataTraveler\"", &device));
+        assert!(!matches("(class == 3 || class == 8) && product == \"Other\"", &device));
+    }
+
+    #[test]
+    fn test_missing_strings_equal_nothing() {
+        let device = storage(None, None);
+
+        assert!(!matches("manufacturer == \"Kingston\"", &device));
+        assert!(matches("manufacturer != \"Kingston\"", &device));
+        assert!(matches("class == 0x08 && manufacturer != \"Kingston\"", &device));
+    }
+
+    #[test]
+    fn test_invalid_expressions_are_rejected() {
+        for source in [
+            "",
+            "class ==",
+            "class = 8",
+            "colour == 1",
+            "class == \"mass storage\"",
+            "manufacturer == 0x10",
+            "(class == 8",
+            "class == 8 &&",
+            "class == 8 class == 9",
+            "manufacturer == \"unterminated",
+            "class == 0xZZ",
+        ] {
+            assert!(RuleExpr::parse(source).is_err(), "'{}' should not parse", source);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Setup checks behind the `self-test` subcommand.
+//!
+//! Each check reports what it found, and failures carry the same remediation hints the GUI
+//! shows, so new users can fix permission problems before running `monitor`.
+
+use crate::error::{check_usb_permissions, get_user_friendly_message, IronWatchError, UsbError};
+use crate::usb_backend::{RusbBackend, UsbBackend};
+
+/// Outcome of one self-test check
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was found, or what went wrong
+    pub detail: String,
+    /// How to fix a failed check
+    pub remediation: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: true, detail: detail.into(), remediation: None }
+    }
+
+    fn fail(name: &'static str, error: &IronWatchError) -> Self {
+        let (detail, remediation) = get_user_friendly_message(error);
+        Self { name, passed: false, detail, remediation }
+    }
+}
+
+/// Run every check, in the order a new user would have to fix them
+pub fn run_checks() -> Vec<CheckResult> {
+    let mut checks = vec![check_libusb(), check_permissions()];
+    #[cfg(target_os = "linux")]
+    checks.push(check_plugdev());
+    checks.push(check_enumeration());
+    checks
+}
+
+fn check_libusb() -> CheckResult {
+    let version = rusb::version();
+    match rusb::Context::new() {
+        Ok(_) => CheckResult::pass("libusb", format!(
+            "libusb {}.{}.{} is available", version.major(), version.minor(), version.micro(),
+        )),
+        Err(e) => CheckResult::fail("libusb", &IronWatchError::UsbError(UsbError::InitializationFailed(e.to_string()))),
+    }
+}
+
+fn check_permissions() -> CheckResult {
+    match check_usb_permissions() {
+        Ok(()) => CheckResult::pass("USB permissions", "The USB device list can be read"),
+        Err(e) => CheckResult::fail("USB permissions", &e),
+    }
+}
+
+fn check_enumeration() -> CheckResult {
+    let result = RusbBackend::new().and_then(|backend| backend.enumerate());
+    match result {
+        Ok(devices) => CheckResult::pass("Enumeration", format!("Found {} USB devices", devices.len())),
+        Err(e) => {
+            let error = e.chain()
+                .find_map(|cause| cause.downcast_ref::<rusb::Error>())
+                .map(|usb_error| match usb_error {
+                    // `From` reports this as device 0000:0000, which is no help here
+                    rusb::Error::Access => IronWatchError::PermissionError(usb_error.to_string()),
+                    _ => IronWatchError::from(*usb_error),
+                })
+                .unwrap_or_else(|| IronWatchError::UsbError(UsbError::EnumerationFailed(e.to_string())));
+            CheckResult::fail("Enumeration", &error)
+        }
+    }
+}
+
+/// Root needs no group; systems without a `plugdev` group grant access through udev rules instead
+#[cfg(target_os = "linux")]
+fn check_plugdev() -> CheckResult {
+    const NAME: &str = "plugdev group";
+
+    let status = std::fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let ids = |field: &str| -> Vec<u32> {
+        status.lines()
+            .find_map(|line| line.strip_prefix(field))
+            .map(|rest| rest.split_whitespace().filter_map(|id| id.parse().ok()).collect())
+            .unwrap_or_default()
+    };
+
+    // Uid: real, effective, saved, filesystem
+    if ids("Uid:").get(1) == Some(&0) {
+        return CheckResult::pass(NAME, "Running as root");
+    }
+
+    let group_file = std::fs::read_to_string("/etc/group").unwrap_or_default();
+    match group_id(&group_file, "plugdev") {
+        None => CheckResult::pass(NAME, "This system has no plugdev group; USB access is granted by udev rules"),
+        Some(gid) if ids("Groups:").contains(&gid) => CheckResult::pass(NAME, "The current user is in plugdev"),
+        Some(_) => CheckResult::fail(NAME, &IronWatchError::PermissionError(
+            "The current user is not in the plugdev group".to_string(),
+        )),
+    }
+}
+
+/// Id of the group `name` in the contents of an `/etc/group` file
+#[cfg(target_os = "linux")]
+fn group_id(group_file: &str, name: &str) -> Option<u32> {
+    group_file.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        if fields.next()? != name {
+            return None;
+        }
+        fields.nth(1)?.parse().ok()
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_id_reads_the_gid_field() {
+        let group_file = "root:x:0:\nplugdevs:x:45:\nplugdev:x:46:alice,bob\n";
+        assert_eq!(group_id(group_file, "plugdev"), Some(46));
+        assert_eq!(group_id(group_file, "dialout"), None);
+    }
+}
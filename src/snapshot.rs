@@ -0,0 +1,169 @@
+//! Periodic analytics snapshots for long-running sessions.
+//!
+//! Each snapshot is the same JSON document as a history export, written to a timestamped
+//! file so trends can be reconstructed later; only the most recent files are kept.
+
+use crate::config::MonitoringConfig;
+use crate::usb_monitor::{ConnectionInterval, DeviceAnalytics, DeviceStatistics, SecurityEvent, UsbDeviceInfo, UsbMonitor};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SNAPSHOT_PREFIX: &str = "ironwatch_snapshot_";
+
+/// The JSON document written by history exports and snapshots
+pub fn history_json(
+    devices: &[UsbDeviceInfo],
+    device_stats: &[(String, DeviceStatistics)],
+    analytics: &DeviceAnalytics,
+    security_events: &[SecurityEvent],
+    timestamp: DateTime<Utc>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "export_timestamp": timestamp,
+        "export_format": "json",
+        "summary": {
+            "total_devices": devices.len(),
+            "total_connections": analytics.connection_frequency.iter().map(|(_, count)| count).sum::<u32>(),
+            "unique_devices": analytics.unique_devices,
+            "unique_fingerprints": analytics.unique_fingerprints,
+            "blocked_devices": analytics.blocked_devices,
+            "security_violations": analytics.security_violations,
+            "device_classes": analytics.device_class_distribution.len(),
+            "vendors": analytics.vendor_distribution.len(),
+        },
+        "current_devices": devices,
+        "device_statistics": device_stats,
+        "analytics": {
+            "device_class_distribution": analytics.device_class_distribution,
+            "vendor_distribution": analytics.vendor_distribution,
+            "connection_frequency": analytics.connection_frequency,
+        },
+        "security": {
+            "total_blocked": analytics.blocked_devices,
+            "total_violations": analytics.security_violations,
+            "security_events": security_events,
+        }
+    })
+}
+
+/// The JSON document written by timeline exports: one interval per connection, for Gantt-style charts
+pub fn timeline_json(intervals: &[ConnectionInterval], timestamp: DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "export_timestamp": timestamp,
+        "export_format": "timeline",
+        "intervals": intervals,
+    })
+}
+
+/// Writes timestamped snapshots into a directory, pruning all but the newest `keep`
+pub struct SnapshotWriter {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl SnapshotWriter {
+    pub fn new(dir: PathBuf, keep: usize) -> Self {
+        Self { dir, keep: keep.max(1) }
+    }
+
+    /// The snapshot interval and writer configured by `monitoring.snapshot_*`, if enabled
+    pub fn from_config(config: &MonitoringConfig) -> Result<Option<(Duration, Self)>> {
+        if config.snapshot_interval_secs == 0 {
+            return Ok(None);
+        }
+
+        let dir = match &config.snapshot_dir {
+            Some(dir) => dir.clone(),
+            None => dirs::data_local_dir()
+                .context("Could not determine data directory for snapshots")?
+                .join("ironwatch")
+                .join("snapshots"),
+        };
+
+        Ok(Some((Duration::from_secs(config.snapshot_interval_secs), Self::new(dir, config.snapshot_keep))))
+    }
+
+    /// Write a snapshot of the monitor's current state and prune old ones
+    pub fn write(&self, monitor: &UsbMonitor) -> Result<PathBuf> {
+        let devices = monitor.get_connected_devices()?;
+        let device_stats: Vec<(String, DeviceStatistics)> = monitor.get_statistics_report()
+            .into_iter()
+            .map(|report| (report.device_key, report.statistics))
+            .collect();
+        let security_events: Vec<SecurityEvent> = monitor.get_security_events().iter().cloned().collect();
+
+        let timestamp = Utc::now();
+        let document = history_json(&devices, &device_stats, &monitor.get_device_analytics(), &security_events, timestamp);
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create snapshot directory: {}", self.dir.display()))?;
+
+        let path = self.dir.join(format!("{}{}.json", SNAPSHOT_PREFIX, timestamp.format("%Y%m%dT%H%M%SZ")));
+        let content = serde_json::to_string_pretty(&document)
+            .context("Failed to serialize snapshot")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write snapshot: {}", path.display()))?;
+        info!("Wrote analytics snapshot to {}", path.display());
+
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// Remove all but the newest `keep` snapshots; names sort chronologically
+    fn prune(&self) -> Result<()> {
+        let mut snapshots: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read snapshot directory: {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(".json"))
+            })
+            .collect();
+
+        snapshots.sort();
+        let excess = snapshots.len().saturating_sub(self.keep);
+        for path in snapshots.into_iter().take(excess) {
+            debug!("Pruning old snapshot {}", path.display());
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove old snapshot: {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_keeps_only_the_newest_snapshots() {
+        let dir = std::env::temp_dir().join(format!("ironwatch-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for hour in 10..15 {
+            fs::write(dir.join(format!("{}20240101T{}0000Z.json", SNAPSHOT_PREFIX, hour)), "{}").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "unrelated").unwrap();
+
+        SnapshotWriter::new(dir.clone(), 2).prune().unwrap();
+
+        let mut remaining: Vec<String> = fs::read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(remaining, vec![
+            format!("{}20240101T130000Z.json", SNAPSHOT_PREFIX),
+            format!("{}20240101T140000Z.json", SNAPSHOT_PREFIX),
+            "notes.txt".to_string(),
+        ]);
+    }
+}
@@ -1,6 +1,6 @@
 use tray_icon::{
     menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
-    TrayIcon, TrayIconBuilder, TrayIconEvent,
+    MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
 use winit::{
     event::{Event, WindowEvent},
@@ -13,14 +13,25 @@ use anyhow::Result;
 pub enum TrayMessage {
     Show,
     Hide,
+    /// Left-click on the icon: show the window if hidden, hide it otherwise
+    ToggleWindow,
     Quit,
     ToggleMonitoring,
     ShowSettings,
     ShowAbout,
 }
 
+/// How insistently a desktop notification asks for attention
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationUrgency {
+    Normal,
+    /// Stays on screen until dismissed, where the desktop supports it
+    Critical,
+}
+
 pub struct SystemTray {
     _tray_icon: TrayIcon,
+    monitoring_item: MenuItem,
     event_receiver: mpsc::Receiver<TrayMessage>,
 }
 
@@ -32,7 +43,7 @@ impl SystemTray {
         let show_item = MenuItem::new("Show IronWatch", true, None);
         let hide_item = MenuItem::new("Hide IronWatch", true, None);
         let separator1 = PredefinedMenuItem::separator();
-        let monitoring_item = MenuItem::new("Toggle Monitoring", true, None);
+        let monitoring_item = MenuItem::new("Start Monitoring", true, None);
         let separator2 = PredefinedMenuItem::separator();
         let settings_item = MenuItem::new("Settings", true, None);
         let about_item = MenuItem::new("About", true, None);
@@ -94,17 +105,15 @@ impl SystemTray {
         // Handle tray icon events
         let sender_clone = sender.clone();
         TrayIconEvent::set_event_handler(Some(move |event| {
-            match event {
-                TrayIconEvent::Click { .. } => {
-                    let _ = sender_clone.send(TrayMessage::Show);
-                }
-                _ => {}
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                let _ = sender_clone.send(TrayMessage::ToggleWindow);
             }
         }));
         
         Ok((
             Self {
                 _tray_icon: tray_icon,
+                monitoring_item,
                 event_receiver: receiver,
             },
             sender,
@@ -139,9 +148,11 @@ impl SystemTray {
             .expect("Failed to create tray icon")
     }
     
-    pub fn update_icon(&self, monitoring: bool) -> Result<()> {
-        // Update icon based on monitoring state
-        let icon = if monitoring {
+    /// Update the icon for the monitoring state; `alert` shows the blocked-device icon instead
+    pub fn update_icon(&self, monitoring: bool, alert: bool) -> Result<()> {
+        let icon = if alert {
+            Self::create_alert_icon()
+        } else if monitoring {
             Self::create_monitoring_icon()
         } else {
             Self::create_tray_icon()
@@ -151,6 +162,12 @@ impl SystemTray {
         Ok(())
     }
     
+    /// Label the monitoring menu item with the action it will take
+    pub fn update_monitoring_label(&self, running: bool) {
+        let label = if running { "Stop Monitoring" } else { "Start Monitoring" };
+        self.monitoring_item.set_text(label);
+    }
+    
     fn create_monitoring_icon() -> tray_icon::Icon {
         // Create a pulsing/active icon when monitoring
         let size = 16;
@@ -175,28 +192,51 @@ impl SystemTray {
             .expect("Failed to create monitoring icon")
     }
     
-    pub fn show_notification(&self, title: &str, message: &str) -> Result<()> {
-        #[cfg(target_os = "windows")]
-        {
-            use notify_rust::Notification;
-            Notification::new()
-                .summary(title)
-                .body(message)
-                .icon("usb")
-                .timeout(3000)
-                .show()?;
+    fn create_alert_icon() -> tray_icon::Icon {
+        // Solid red with a white cross while a blocked device hasn't been acknowledged
+        let size = 16;
+        let mut rgba = Vec::with_capacity(size * size * 4);
+        
+        for y in 0..size {
+            for x in 0..size {
+                let is_border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+                let is_cross = (3..size - 3).contains(&x) && (x == y || x + y == size - 1);
+                
+                if is_border {
+                    rgba.extend_from_slice(&[160, 0, 0, 255]); // Dark red border
+                } else if is_cross {
+                    rgba.extend_from_slice(&[255, 255, 255, 255]); // White cross
+                } else {
+                    rgba.extend_from_slice(&[220, 20, 20, 255]); // Solid red
+                }
+            }
         }
         
-        #[cfg(not(target_os = "windows"))]
-        {
-            use notify_rust::Notification;
-            Notification::new()
-                .summary(title)
-                .body(message)
-                .timeout(3000)
-                .show()?;
+        tray_icon::Icon::from_rgba(rgba, size as u32, size as u32)
+            .expect("Failed to create alert icon")
+    }
+    
+    pub fn show_notification(&self, title: &str, message: &str, urgency: NotificationUrgency) -> Result<()> {
+        use notify_rust::{Notification, Timeout};
+        
+        let mut notification = Notification::new();
+        notification.summary(title).body(message);
+        
+        match urgency {
+            NotificationUrgency::Normal => notification.timeout(3000),
+            NotificationUrgency::Critical => notification.timeout(Timeout::Never),
+        };
+        
+        #[cfg(target_os = "windows")]
+        notification.icon("usb");
+        
+        // Only freedesktop notification servers know about urgency
+        #[cfg(all(unix, not(target_os = "macos")))]
+        if urgency == NotificationUrgency::Critical {
+            notification.urgency(notify_rust::Urgency::Critical);
         }
         
+        notification.show()?;
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,360 @@
+use crate::communication::{CommunicationHub, MonitorEvent, MonitoringStatus};
+use crate::monitoring_service::start_monitoring_service_with_recovery;
+use crate::usb_monitor::{UsbDeviceChange, UsbDeviceInfo};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use log::{info, warn};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use std::io::{stdout, Stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum number of entries kept in the event log pane
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// How long to wait for a key press before redrawing
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pane {
+    Devices,
+    Events,
+}
+
+/// Terminal UI state, fed by `MonitorEvent`s from the monitoring service
+struct TuiApp {
+    devices: Vec<UsbDeviceInfo>,
+    events: Vec<(DateTime<Utc>, String, Color)>,
+    status: MonitoringStatus,
+    focus: Pane,
+    device_state: TableState,
+    event_state: ListState,
+}
+
+impl TuiApp {
+    fn new() -> Self {
+        Self {
+            devices: Vec::new(),
+            events: Vec::new(),
+            status: MonitoringStatus::Stopped,
+            focus: Pane::Devices,
+            device_state: TableState::default(),
+            event_state: ListState::default(),
+        }
+    }
+
+    /// Apply an event from the monitoring service; returns true if the device list should be refreshed
+    fn handle_event(&mut self, event: MonitorEvent) -> bool {
+        match event {
+            MonitorEvent::DevicesLoaded(devices) | MonitorEvent::DevicesUpdated(devices) => {
+                self.devices = devices;
+                self.clamp_selection();
+                false
+            }
+            MonitorEvent::DeviceChanged(change) => {
+                self.log_change(&change);
+                true
+            }
+            MonitorEvent::DevicesChanged(changes) => {
+                for change in &changes {
+                    self.log_change(change);
+                }
+                true
+            }
+            MonitorEvent::MonitoringStarted => {
+                self.status = MonitoringStatus::Running;
+                self.log("Monitoring started".to_string(), Color::Green);
+                false
+            }
+            MonitorEvent::MonitoringStopped => {
+                self.status = MonitoringStatus::Stopped;
+                self.log("Monitoring stopped".to_string(), Color::Yellow);
+                false
+            }
+            MonitorEvent::MonitoringError(error) => {
+                self.status = MonitoringStatus::Error(error.clone());
+                self.log(format!("Error: {}", error), Color::Red);
+                false
+            }
+            MonitorEvent::PermissionError(error) => {
+                self.log(format!("Permission error: {}", error), Color::Red);
+                false
+            }
+            MonitorEvent::UsbUnavailable(error) => {
+                self.log(format!("USB unavailable: {}", error), Color::Red);
+                false
+            }
+            MonitorEvent::BlockingSuspended(suspended) => {
+                let message = if suspended { "Device blocking suspended until restart" } else { "Device blocking resumed" };
+                self.log(message.to_string(), Color::Yellow);
+                false
+            }
+            MonitorEvent::AnalyticsUpdated(_) | MonitorEvent::TimelineUpdated(_) => false,
+        }
+    }
+
+    fn log_change(&mut self, change: &UsbDeviceChange) {
+        let device = change.get_device_info();
+        let color = match change {
+            UsbDeviceChange::Connected(_) => Color::Green,
+            UsbDeviceChange::Disconnected(_) => Color::Red,
+            UsbDeviceChange::Reconnected(_) => Color::Yellow,
+            UsbDeviceChange::Blocked(..) => Color::Magenta,
+            UsbDeviceChange::Suspended(_) => Color::Blue,
+            _ => Color::White,
+        };
+
+        self.log(
+            format!(
+                "{:<12} {:04x}:{:04x} {}",
+                change.get_change_type(),
+                device.vendor_id,
+                device.product_id,
+                device.display_name(),
+            ),
+            color,
+        );
+    }
+
+    fn log(&mut self, message: String, color: Color) {
+        self.events.push((Utc::now(), message, color));
+
+        if self.events.len() > MAX_LOG_ENTRIES {
+            self.events.drain(..self.events.len() - MAX_LOG_ENTRIES);
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        match self.device_state.selected() {
+            Some(_) if self.devices.is_empty() => self.device_state.select(None),
+            Some(i) if i >= self.devices.len() => self.device_state.select(Some(self.devices.len() - 1)),
+            _ => {}
+        }
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        let (len, selected) = match self.focus {
+            Pane::Devices => (self.devices.len(), self.device_state.selected()),
+            Pane::Events => (self.events.len(), self.event_state.selected()),
+        };
+
+        if len == 0 {
+            return;
+        }
+
+        let current = selected.unwrap_or(if delta < 0 { len } else { 0 }) as isize;
+        let next = Some((current + delta).clamp(0, len as isize - 1) as usize);
+
+        match self.focus {
+            Pane::Devices => self.device_state.select(next),
+            Pane::Events => self.event_state.select(next),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Percentage(60),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ])
+            .split(frame.size());
+
+        self.render_status(frame, chunks[0]);
+        self.render_devices(frame, chunks[1]);
+        self.render_events(frame, chunks[2]);
+
+        let help = Paragraph::new("q: quit  ↑/↓: scroll  Tab: switch pane  r: refresh")
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(help, chunks[3]);
+    }
+
+    fn render_status(&self, frame: &mut Frame, area: Rect) {
+        let (label, color) = match &self.status {
+            MonitoringStatus::Running => ("MONITORING".to_string(), Color::Green),
+            MonitoringStatus::Error(error) => (format!("ERROR: {}", error), Color::Red),
+            other => (format!("{:?}", other).to_uppercase(), Color::Yellow),
+        };
+
+        let line = Line::from(vec![
+            Span::styled("IronWatch ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(label, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("  {} devices", self.devices.len())),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    fn render_devices(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(["Bus", "Addr", "VID:PID", "Manufacturer", "Product", "Class", "Status"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let rows = self.devices.iter().map(|device| {
+            Row::new(vec![
+                format!("{:03}", device.bus_number),
+                format!("{:03}", device.device_address),
+                format!("{:04x}:{:04x}", device.vendor_id, device.product_id),
+                device.manufacturer.clone().unwrap_or_else(|| "Unknown".to_string()),
+                device.product.clone().unwrap_or_else(|| "Unknown".to_string()),
+                format!("{:02x}", device.device_class),
+                format!("{:?}", device.connection_status),
+            ])
+        });
+
+        let widths = [
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Length(10),
+            Constraint::Percentage(25),
+            Constraint::Percentage(35),
+            Constraint::Length(6),
+            Constraint::Length(13),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(self.pane_block(" Devices ", Pane::Devices))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(table, area, &mut self.device_state);
+    }
+
+    fn render_events(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self.events.iter().map(|(timestamp, message, color)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    timestamp.with_timezone(&Local).format("%H:%M:%S ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(message.clone(), Style::default().fg(*color)),
+            ]))
+        }).collect();
+
+        // Follow the tail of the log until the user selects an entry
+        if self.event_state.selected().is_none() {
+            let visible = area.height.saturating_sub(2) as usize;
+            *self.event_state.offset_mut() = items.len().saturating_sub(visible);
+        }
+
+        let list = List::new(items)
+            .block(self.pane_block(" Events ", Pane::Events))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(list, area, &mut self.event_state);
+    }
+
+    fn pane_block(&self, title: &'static str, pane: Pane) -> Block<'static> {
+        let border = if self.focus == pane { Color::Cyan } else { Color::DarkGray };
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border))
+    }
+}
+
+/// Run the terminal UI until `q` is pressed or shutdown is requested
+pub async fn run_tui(shutdown_flag: Arc<AtomicBool>) -> Result<()> {
+    info!("Starting IronWatch TUI");
+
+    let (communication_hub, communication_receiver) = CommunicationHub::new();
+    let monitoring_handle = start_monitoring_service_with_recovery(communication_receiver, 3)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start monitoring service: {}", e))?;
+
+    let _ = communication_hub.start_monitoring();
+
+    let mut terminal = setup_terminal()?;
+    let result = event_loop(&mut terminal, &communication_hub, &shutdown_flag).await;
+    restore_terminal(&mut terminal)?;
+
+    let _ = communication_hub.shutdown();
+    if let Err(e) = monitoring_handle.await {
+        warn!("Monitoring service task failed: {}", e);
+    }
+
+    result
+}
+
+async fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    communication_hub: &CommunicationHub,
+    shutdown_flag: &AtomicBool,
+) -> Result<()> {
+    let mut app = TuiApp::new();
+
+    while !shutdown_flag.load(Ordering::Relaxed) {
+        let mut needs_refresh = false;
+        while let Some(event) = communication_hub.try_recv_event() {
+            needs_refresh |= app.handle_event(event);
+        }
+        if needs_refresh {
+            let _ = communication_hub.refresh_devices();
+        }
+
+        terminal.draw(|frame| app.render(frame))
+            .context("Failed to draw terminal UI")?;
+
+        // Raw mode swallows Ctrl+C, so it is handled as a key press here
+        let input = tokio::task::block_in_place(|| -> std::io::Result<Option<Event>> {
+            if event::poll(INPUT_POLL_INTERVAL)? {
+                event::read().map(Some)
+            } else {
+                Ok(None)
+            }
+        }).context("Failed to read terminal input")?;
+
+        if let Some(Event::Key(key)) = input {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Up => app.scroll(-1),
+                KeyCode::Down => app.scroll(1),
+                KeyCode::PageUp => app.scroll(-10),
+                KeyCode::PageDown => app.scroll(10),
+                KeyCode::Tab => {
+                    app.focus = match app.focus {
+                        Pane::Devices => Pane::Events,
+                        Pane::Events => Pane::Devices,
+                    };
+                }
+                KeyCode::Char('r') => {
+                    let _ = communication_hub.refresh_devices();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode().context("Failed to enable raw terminal mode")?;
+    execute!(stdout(), EnterAlternateScreen).context("Failed to enter alternate screen")?;
+    Terminal::new(CrosstermBackend::new(stdout())).context("Failed to create terminal")
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode().context("Failed to disable raw terminal mode")?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen).context("Failed to leave alternate screen")?;
+    terminal.show_cursor().context("Failed to restore cursor")
+}
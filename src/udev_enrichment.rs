@@ -0,0 +1,71 @@
+//! Linux-only enrichment of missing descriptor strings from udev properties.
+//!
+//! Enabled with `monitoring.udev_enrichment`. Devices are matched to their udev entry by
+//! bus number and device address; only fields the descriptors left empty are filled.
+
+use crate::usb_monitor::UsbDeviceInfo;
+use log::debug;
+
+/// Fill missing manufacturer, product and serial number from udev
+pub fn enrich(devices: &mut [UsbDeviceInfo]) {
+    if devices.iter().all(is_complete) {
+        return;
+    }
+
+    let entries = match scan_usb_devices() {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("udev enumeration failed: {}", e);
+            return;
+        }
+    };
+
+    for entry in entries {
+        let (Some(bus), Some(address)) = (number_property(&entry, "BUSNUM"), number_property(&entry, "DEVNUM")) else {
+            continue;
+        };
+
+        let Some(device) = devices.iter_mut().find(|d| d.bus_number == bus && d.device_address == address) else {
+            continue;
+        };
+
+        if device.manufacturer.is_none() {
+            device.manufacturer = text_property(&entry, "ID_VENDOR");
+        }
+        if device.product.is_none() {
+            device.product = text_property(&entry, "ID_MODEL");
+        }
+        if device.serial_number.is_none() {
+            // ID_SERIAL is "<vendor>_<model>_<serial>"; ID_SERIAL_SHORT holds just the serial
+            device.serial_number = string_property(&entry, "ID_SERIAL_SHORT");
+            device.update_fingerprint();
+        }
+    }
+}
+
+fn is_complete(device: &UsbDeviceInfo) -> bool {
+    device.manufacturer.is_some() && device.product.is_some() && device.serial_number.is_some()
+}
+
+fn scan_usb_devices() -> std::io::Result<Vec<udev::Device>> {
+    let mut enumerator = udev::Enumerator::new()?;
+    enumerator.match_subsystem("usb")?;
+    enumerator.match_property("DEVTYPE", "usb_device")?;
+    Ok(enumerator.scan_devices()?.collect())
+}
+
+fn string_property(entry: &udev::Device, key: &str) -> Option<String> {
+    entry.property_value(key)
+        .and_then(|value| value.to_str())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// udev replaces spaces with underscores in ID_VENDOR/ID_MODEL
+fn text_property(entry: &udev::Device, key: &str) -> Option<String> {
+    string_property(entry, key).map(|value| value.replace('_', " "))
+}
+
+fn number_property(entry: &udev::Device, key: &str) -> Option<u8> {
+    string_property(entry, key).and_then(|value| value.parse().ok())
+}
@@ -0,0 +1,650 @@
+use crate::config::RawDescriptors;
+use crate::usb_monitor::{device_fingerprint, ConnectionStatus, UsbDeviceInfo};
+use rusb::constants::{LIBUSB_DT_CONFIG, LIBUSB_DT_DEVICE, LIBUSB_REQUEST_GET_DESCRIPTOR};
+use rusb::{Context, Device, DeviceDescriptor, DeviceHandle, Direction, LogLevel, Recipient, RequestType, UsbContext};
+use anyhow::{Result, Context as AnyhowContext};
+use log::{debug, warn};
+use chrono::Utc;
+use usb_ids::FromId;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Most devices opened at once to read their string descriptors
+const DESCRIPTOR_READERS: usize = 4;
+
+/// Longest wait for one GET_DESCRIPTOR control transfer
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Size of a device descriptor, and of the header of a configuration descriptor
+const DEVICE_DESCRIPTOR_LENGTH: usize = 18;
+const CONFIG_HEADER_LENGTH: usize = 9;
+
+/// Manufacturer, product and serial number strings as read from a device
+pub type DescriptorStrings = (Option<String>, Option<String>, Option<String>);
+
+/// What one device read produced: its strings and, when asked for, its raw descriptors
+type DescriptorRead = (DescriptorStrings, Option<Vec<u8>>);
+
+/// Which descriptors still have to be read from a device, because the cache didn't have them
+#[derive(Debug, Clone, Copy)]
+struct Unread {
+    strings: bool,
+    raw: bool,
+}
+
+/// Descriptor strings of attached devices, so unchanged devices aren't reopened on every poll.
+///
+/// Entries are keyed by `vid:pid:bus:address` rather than the monitor's device key, since the
+/// serial-based keys need the very strings being cached. A device that re-enumerates gets a new
+/// address and is read again; entries for devices that are gone are dropped on each scan.
+#[derive(Debug, Default)]
+pub struct DescriptorCache {
+    entries: HashMap<String, DescriptorStrings>,
+    /// Raw descriptors in hex, or `None` for devices that couldn't be read
+    raw: HashMap<String, Option<String>>,
+    /// Devices that didn't answer in time; they aren't opened again until they re-enumerate
+    stalled: HashSet<String>,
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that required opening the device
+    pub misses: u64,
+    /// Wall-clock time spent opening devices and reading their strings on misses
+    pub read_time: Duration,
+}
+
+impl DescriptorCache {
+    fn key(vendor_id: u16, product_id: u16, bus_number: u8, device_address: u8) -> String {
+        format!("{}:{}:{}:{}", vendor_id, product_id, bus_number, device_address)
+    }
+
+    fn get(&mut self, key: &str) -> Option<DescriptorStrings> {
+        let cached = self.entries.get(key).cloned();
+        if cached.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        cached
+    }
+
+    /// Forget all cached strings so the next scan opens every device again
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.raw.clear();
+        self.stalled.clear();
+    }
+
+    fn retain_present(&mut self, present: &HashSet<String>) {
+        self.entries.retain(|key, _| present.contains(key));
+        self.raw.retain(|key, _| present.contains(key));
+        self.stalled.retain(|key| present.contains(key));
+    }
+}
+
+/// Source of USB device information, abstracted so monitoring logic can run without hardware
+pub trait UsbBackend: Send + Sync {
+    /// Enumerate all currently attached devices
+    fn enumerate(&self) -> Result<Vec<UsbDeviceInfo>>;
+
+    /// Enumerate, reusing descriptor strings from `cache` for devices that are still attached
+    fn enumerate_cached(&self, _cache: &mut DescriptorCache) -> Result<Vec<UsbDeviceInfo>> {
+        self.enumerate()
+    }
+
+    /// Best-effort attempt to stop a blocked device from working.
+    ///
+    /// Returns a description of what was done, or why nothing could be.
+    fn quarantine(&self, _device: &UsbDeviceInfo) -> std::result::Result<String, String> {
+        Err("quarantine is not supported by this backend".to_string())
+    }
+}
+
+/// Backend that talks to real hardware through libusb
+pub struct RusbBackend {
+    context: Context,
+    /// Fill missing descriptor strings from udev (Linux with the `udev` feature only)
+    #[cfg_attr(not(all(target_os = "linux", feature = "udev")), allow(dead_code))]
+    udev_enrichment: bool,
+    /// Open devices to read string descriptors (`monitoring.descriptor_strings`)
+    descriptor_strings: bool,
+    /// Longest wait for one device's strings (`monitoring.enumeration_timeout_ms`); `None` waits indefinitely
+    enumeration_timeout: Option<Duration>,
+    /// Descriptors read as raw bytes for JSON export (`monitoring.raw_descriptors`)
+    raw_descriptors: RawDescriptors,
+}
+
+impl RusbBackend {
+    /// Create a new libusb-backed backend
+    pub fn new() -> Result<Self> {
+        let context = Context::new()
+            .context("Failed to create USB context")?;
+        
+        Ok(Self {
+            context,
+            udev_enrichment: false,
+            descriptor_strings: true,
+            enumeration_timeout: Some(Duration::from_millis(crate::config::DEFAULT_ENUMERATION_TIMEOUT_MS)),
+            raw_descriptors: RawDescriptors::Off,
+        })
+    }
+
+    /// Choose whether devices are opened to read their string descriptors
+    pub fn with_descriptor_strings(mut self, enabled: bool) -> Self {
+        self.descriptor_strings = enabled;
+        self.warn_if_raw_descriptors_unread();
+        self
+    }
+
+    /// Set libusb's own log level (0 none to 4 debug; higher values are treated as debug)
+    pub fn with_log_level(mut self, level: u8) -> Self {
+        let level = match level {
+            0 => LogLevel::None,
+            1 => LogLevel::Error,
+            2 => LogLevel::Warning,
+            3 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        };
+        self.context.set_log_level(level);
+        self
+    }
+
+    /// Give up on a device whose strings take longer than `timeout_ms` to read (0 waits indefinitely)
+    pub fn with_enumeration_timeout(mut self, timeout_ms: u64) -> Self {
+        self.enumeration_timeout = (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms));
+        self
+    }
+
+    /// Choose which descriptors are read from each device as raw bytes
+    pub fn with_raw_descriptors(mut self, raw_descriptors: RawDescriptors) -> Self {
+        self.raw_descriptors = raw_descriptors;
+        self.warn_if_raw_descriptors_unread();
+        self
+    }
+
+    /// Raw descriptors are read while a device is open for its strings, so they need both settings
+    fn warn_if_raw_descriptors_unread(&self) {
+        if self.raw_descriptors != RawDescriptors::Off && !self.descriptor_strings {
+            warn!("monitoring.raw_descriptors is set, but monitoring.descriptor_strings is off, so devices aren't opened and no raw descriptors are read");
+        }
+    }
+
+    /// Enable filling missing manufacturer/product/serial from udev properties
+    pub fn with_udev_enrichment(mut self, enabled: bool) -> Self {
+        #[cfg(not(all(target_os = "linux", feature = "udev")))]
+        if enabled {
+            warn!("monitoring.udev_enrichment is set, but this build has no udev support (enable the udev feature on Linux)");
+        }
+        self.udev_enrichment = enabled;
+        self
+    }
+
+    /// Get detailed information about a USB device.
+    ///
+    /// Strings and raw descriptors come from `cache` or the USB ID database where possible; the
+    /// returned `Unread` says what still has to be read from the device, see `read_descriptors`.
+    fn get_device_info(&self, device: &Device<Context>, cache: &mut DescriptorCache) -> Result<(UsbDeviceInfo, Unread)> {
+        let descriptor = device.device_descriptor()
+            .context("Failed to get device descriptor")?;
+        
+        let bus_number = device.bus_number();
+        let device_address = device.address();
+        
+        let cache_key = DescriptorCache::key(descriptor.vendor_id(), descriptor.product_id(), bus_number, device_address);
+        
+        // Read the power state first: opening the device later resumes it
+        let connection_status = if is_runtime_suspended(device) {
+            ConnectionStatus::Suspended
+        } else {
+            ConnectionStatus::Connected
+        };
+        
+        let stalled = cache.stalled.contains(&cache_key);
+
+        // Strings read on an earlier scan are reused; the rest are read in parallel afterwards
+        let ((manufacturer, product, serial_number), strings_known) = if !self.descriptor_strings || stalled {
+            (Self::lookup_usb_ids(descriptor.vendor_id(), descriptor.product_id()), true)
+        } else if let Some(cached) = cache.get(&cache_key) {
+            (cached, true)
+        } else {
+            ((None, None, None), false)
+        };
+
+        // Raw descriptors are read along with the strings, while the device is open anyway
+        let (raw_descriptor, raw_known) = if self.raw_descriptors == RawDescriptors::Off || !self.descriptor_strings || stalled {
+            (None, true)
+        } else if let Some(cached) = cache.raw.get(&cache_key) {
+            (cached.clone(), true)
+        } else {
+            (None, false)
+        };
+
+        let fingerprint = device_fingerprint(descriptor.vendor_id(), descriptor.product_id(), serial_number.as_deref());
+        
+        let info = UsbDeviceInfo {
+            bus_number,
+            device_address,
+            vendor_id: descriptor.vendor_id(),
+            product_id: descriptor.product_id(),
+            device_version: {
+                let version = descriptor.device_version();
+                (version.major() as u16) << 8 | (version.minor() as u16)
+            },
+            manufacturer,
+            product,
+            serial_number,
+            device_class: descriptor.class_code(),
+            device_subclass: descriptor.sub_class_code(),
+            device_protocol: descriptor.protocol_code(),
+            max_packet_size: descriptor.max_packet_size(),
+            num_configurations: descriptor.num_configurations(),
+            timestamp: Utc::now(),
+            connection_status,
+            fingerprint,
+            raw_descriptor,
+        };
+        Ok((info, Unread { strings: !strings_known, raw: !raw_known }))
+    }
+
+    /// Open the `unread` devices a few at a time and fill in their strings and raw descriptors,
+    /// caching what was read.
+    ///
+    /// A device that doesn't answer within the enumeration timeout gets names from the USB ID
+    /// database instead, and is left alone until it re-enumerates.
+    fn read_descriptors(&self, infos: &mut [UsbDeviceInfo], unread: Vec<(usize, Device<Context>, Unread)>, cache: &mut DescriptorCache) {
+        if unread.is_empty() {
+            return;
+        }
+
+        let read_start = Instant::now();
+        let configurations = self.raw_descriptors == RawDescriptors::All;
+        let jobs = unread.iter()
+            .map(|(_, device, wanted)| {
+                let device = device.clone();
+                let wanted = *wanted;
+                Box::new(move || Self::read_device_descriptors(&device, wanted, configurations)) as Job<DescriptorRead>
+            })
+            .collect();
+        let results = run_bounded(jobs, DESCRIPTOR_READERS, self.enumeration_timeout);
+        cache.read_time += read_start.elapsed();
+
+        for ((position, _, wanted), result) in unread.into_iter().zip(results) {
+            let info = &mut infos[position];
+            let cache_key = DescriptorCache::key(info.vendor_id, info.product_id, info.bus_number, info.device_address);
+
+            let Some((strings, raw)) = result else {
+                warn!("USB device {:04x}:{:04x} on bus {} address {} did not answer within {:?}; using database names until it re-enumerates",
+                      info.vendor_id, info.product_id, info.bus_number, info.device_address, self.enumeration_timeout.unwrap_or_default());
+                cache.stalled.insert(cache_key);
+                if wanted.strings {
+                    (info.manufacturer, info.product, info.serial_number) = Self::lookup_usb_ids(info.vendor_id, info.product_id);
+                    info.update_fingerprint();
+                }
+                continue;
+            };
+
+            if wanted.raw {
+                info.raw_descriptor = raw.map(|bytes| hex_string(&bytes));
+                cache.raw.insert(cache_key.clone(), info.raw_descriptor.clone());
+            }
+
+            if wanted.strings {
+                // Only a read that produced something counts; empty reads are retried next scan
+                if strings != (None, None, None) {
+                    cache.entries.insert(cache_key, strings.clone());
+                }
+                (info.manufacturer, info.product, info.serial_number) = strings;
+                info.update_fingerprint();
+            }
+        }
+    }
+
+    /// Open a device once and read what `wanted` asks for; empty if it can't be opened
+    fn read_device_descriptors(device: &Device<Context>, wanted: Unread, configurations: bool) -> DescriptorRead {
+        let handle = match device.open() {
+            Ok(handle) => handle,
+            Err(e) => {
+                debug!("Could not open device {}:{} for its descriptors: {}",
+                       device.bus_number(), device.address(), e);
+                return ((None, None, None), None);
+            }
+        };
+
+        let strings = match device.device_descriptor() {
+            Ok(descriptor) if wanted.strings => Self::get_string_descriptors(&handle, &descriptor),
+            _ => (None, None, None),
+        };
+        let raw = if wanted.raw {
+            Self::read_raw_descriptors(&handle, configurations)
+        } else {
+            None
+        };
+        (strings, raw)
+    }
+
+    /// Read the device descriptor, and with `configurations` every configuration descriptor,
+    /// exactly as the device sends them; `None` if the device descriptor can't be read
+    fn read_raw_descriptors(handle: &DeviceHandle<Context>, configurations: bool) -> Option<Vec<u8>> {
+        let mut bytes = Self::read_descriptor(handle, LIBUSB_DT_DEVICE, 0, DEVICE_DESCRIPTOR_LENGTH)?;
+        if configurations {
+            let count = bytes.get(DEVICE_DESCRIPTOR_LENGTH - 1).copied().unwrap_or(0);
+            for index in 0..count {
+                // The header's wTotalLength covers the interface and endpoint descriptors that follow
+                let total_length = match Self::read_descriptor(handle, LIBUSB_DT_CONFIG, index, CONFIG_HEADER_LENGTH) {
+                    Some(header) if header.len() >= 4 => u16::from_le_bytes([header[2], header[3]]),
+                    _ => break,
+                };
+                match Self::read_descriptor(handle, LIBUSB_DT_CONFIG, index, total_length as usize) {
+                    Some(configuration) => bytes.extend(configuration),
+                    None => break,
+                }
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Up to `length` bytes of descriptor `index` of `descriptor_type`, via a standard GET_DESCRIPTOR request
+    fn read_descriptor(handle: &DeviceHandle<Context>, descriptor_type: u8, index: u8, length: usize) -> Option<Vec<u8>> {
+        let request_type = rusb::request_type(Direction::In, RequestType::Standard, Recipient::Device);
+        let mut buffer = vec![0; length];
+        match handle.read_control(request_type, LIBUSB_REQUEST_GET_DESCRIPTOR, u16::from_be_bytes([descriptor_type, index]), 0, &mut buffer, CONTROL_TIMEOUT) {
+            Ok(read) => {
+                buffer.truncate(read);
+                Some(buffer)
+            }
+            Err(e) => {
+                debug!("GET_DESCRIPTOR {:#04x} index {} failed: {}", descriptor_type, index, e);
+                None
+            }
+        }
+    }
+
+    /// Vendor and product names from the USB ID database; there is no serial without opening the device
+    fn lookup_usb_ids(vendor_id: u16, product_id: u16) -> DescriptorStrings {
+        let manufacturer = usb_ids::Vendor::from_id(vendor_id).map(|vendor| vendor.name().to_string());
+        let product = usb_ids::Device::from_vid_pid(vendor_id, product_id).map(|device| device.name().to_string());
+        (manufacturer, product, None)
+    }
+
+    /// Extract string descriptors from device
+    fn get_string_descriptors(
+        handle: &DeviceHandle<Context>,
+        descriptor: &DeviceDescriptor,
+    ) -> DescriptorStrings {
+        let manufacturer = if let Some(index) = descriptor.manufacturer_string_index() {
+            if index > 0 {
+                handle.read_manufacturer_string_ascii(descriptor).ok()
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        
+        let product = if let Some(index) = descriptor.product_string_index() {
+            if index > 0 {
+                handle.read_product_string_ascii(descriptor).ok()
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        
+        let serial_number = if let Some(index) = descriptor.serial_number_string_index() {
+            if index > 0 {
+                handle.read_serial_number_string_ascii(descriptor).ok()
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        
+        (manufacturer, product, serial_number)
+    }
+}
+
+impl UsbBackend for RusbBackend {
+    fn enumerate(&self) -> Result<Vec<UsbDeviceInfo>> {
+        self.enumerate_cached(&mut DescriptorCache::default())
+    }
+
+    fn enumerate_cached(&self, cache: &mut DescriptorCache) -> Result<Vec<UsbDeviceInfo>> {
+        let devices = self.context.devices()
+            .context("Failed to get device list")?;
+        
+        let mut device_info_list = Vec::new();
+        let mut unread = Vec::new();
+        
+        for device in devices.iter() {
+            match self.get_device_info(&device, cache) {
+                Ok((info, wanted)) => {
+                    if wanted.strings || wanted.raw {
+                        unread.push((device_info_list.len(), device, wanted));
+                    }
+                    device_info_list.push(info);
+                }
+                Err(e) => {
+                    debug!("Failed to get device info: {}", e);
+                }
+            }
+        }
+        
+        // One slow device must not hold up the others, so their descriptors are read in parallel
+        self.read_descriptors(&mut device_info_list, unread, cache);
+        
+        let present = device_info_list.iter()
+            .map(|info| DescriptorCache::key(info.vendor_id, info.product_id, info.bus_number, info.device_address))
+            .collect();
+        cache.retain_present(&present);
+        
+        // Fall back to the OS's friendly names where descriptors were unreadable
+        #[cfg(windows)]
+        crate::windows_names::enrich(&mut device_info_list);
+        
+        #[cfg(all(target_os = "linux", feature = "udev"))]
+        if self.udev_enrichment {
+            crate::udev_enrichment::enrich(&mut device_info_list);
+        }
+        
+        Ok(device_info_list)
+    }
+
+    /// Detaches any active kernel drivers (Linux only) and, if none could be detached,
+    /// issues a port reset.
+    fn quarantine(&self, device: &UsbDeviceInfo) -> std::result::Result<String, String> {
+        let devices = self.context.devices()
+            .map_err(|e| format!("failed to list devices: {}", e))?;
+        
+        let usb_device = devices.iter()
+            .find(|d| d.bus_number() == device.bus_number && d.address() == device.device_address)
+            .ok_or_else(|| "device is no longer present".to_string())?;
+        
+        let handle = usb_device.open()
+            .map_err(|e| format!("cannot open device: {}", e))?;
+        
+        #[cfg(target_os = "linux")]
+        let detached = {
+            let mut count = 0;
+            if let Ok(config) = usb_device.active_config_descriptor() {
+                for interface in config.interfaces() {
+                    let number = interface.number();
+                    if handle.kernel_driver_active(number).unwrap_or(false) {
+                        match handle.detach_kernel_driver(number) {
+                            Ok(()) => count += 1,
+                            Err(e) => debug!("Failed to detach kernel driver from interface {}: {}", number, e),
+                        }
+                    }
+                }
+            }
+            count
+        };
+        
+        #[cfg(not(target_os = "linux"))]
+        let detached = 0;
+        
+        if detached > 0 {
+            return Ok(format!("detached {} kernel driver(s)", detached));
+        }
+        
+        handle.reset()
+            .map(|_| "device reset".to_string())
+            .map_err(|e| format!("reset not permitted or unsupported: {}", e))
+    }
+}
+
+/// Whether the kernel reports the device as runtime-suspended (`power/runtime_status` in sysfs)
+#[cfg(target_os = "linux")]
+fn is_runtime_suspended(device: &Device<Context>) -> bool {
+    let ports = match device.port_numbers() {
+        Ok(ports) if !ports.is_empty() => ports,
+        // Root hubs have no port path
+        _ => return false,
+    };
+
+    let port_path: Vec<String> = ports.iter().map(u8::to_string).collect();
+    let status_path = format!("/sys/bus/usb/devices/{}-{}/power/runtime_status", device.bus_number(), port_path.join("."));
+
+    std::fs::read_to_string(status_path)
+        .map(|status| status.trim() == "suspended")
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_runtime_suspended(_device: &Device<Context>) -> bool {
+    false
+}
+
+/// Lowercase hex of `bytes` with no separators
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Progress of one `run_bounded` job, reported by the worker running it
+enum JobProgress<T> {
+    Started(usize, Instant),
+    Finished(usize, T),
+}
+
+/// One unit of work for `run_bounded`
+type Job<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// Jobs not yet picked up by a worker, with their position in the original list
+type JobQueue<T> = Arc<Mutex<VecDeque<(usize, Job<T>)>>>;
+
+/// Run `jobs` on at most `workers` threads and return their results in the order of `jobs`.
+///
+/// A job still running after `timeout` yields `None`. Its thread is abandoned to finish on its
+/// own, and a new worker takes its place so the remaining jobs still run.
+fn run_bounded<T: Send + 'static>(
+    jobs: Vec<Job<T>>,
+    workers: usize,
+    timeout: Option<Duration>,
+) -> Vec<Option<T>> {
+    let mut results: Vec<Option<T>> = jobs.iter().map(|_| None).collect();
+    let mut remaining = jobs.len();
+    let queue = Arc::new(Mutex::new(jobs.into_iter().enumerate().collect::<VecDeque<_>>()));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..workers.max(1).min(remaining) {
+        spawn_job_worker(&queue, &sender);
+    }
+    // Without a timeout no replacement workers are needed, so a worker that dies ends the wait
+    let sender = timeout.map(|_| sender);
+
+    let mut running: HashMap<usize, Instant> = HashMap::new();
+    while remaining > 0 {
+        let next_deadline = timeout.and_then(|timeout| running.values().min().map(|started| *started + timeout));
+        let progress = match next_deadline {
+            Some(deadline) => match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(progress) => progress,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    let timeout = timeout.unwrap_or_default();
+                    let before = running.len();
+                    running.retain(|_, started| now.duration_since(*started) < timeout);
+                    let abandoned = before - running.len();
+                    if let Some(sender) = &sender {
+                        for _ in 0..abandoned {
+                            spawn_job_worker(&queue, sender);
+                        }
+                    }
+                    remaining -= abandoned;
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            },
+            None => match receiver.recv() {
+                Ok(progress) => progress,
+                Err(_) => break,
+            },
+        };
+
+        match progress {
+            JobProgress::Started(index, started) => {
+                running.insert(index, started);
+            }
+            // Results of abandoned jobs arrive too late and are dropped
+            JobProgress::Finished(index, result) => {
+                if running.remove(&index).is_some() {
+                    results[index] = Some(result);
+                    remaining -= 1;
+                }
+            }
+        }
+    }
+
+    results
+}
+
+fn spawn_job_worker<T: Send + 'static>(
+    queue: &JobQueue<T>,
+    sender: &mpsc::Sender<JobProgress<T>>,
+) {
+    let queue = Arc::clone(queue);
+    let sender = sender.clone();
+    std::thread::spawn(move || loop {
+        let Some((index, job)) = queue.lock().unwrap().pop_front() else {
+            break;
+        };
+        if sender.send(JobProgress::Started(index, Instant::now())).is_err() {
+            break;
+        }
+        let result = job();
+        if sender.send(JobProgress::Finished(index, result)).is_err() {
+            break;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_hung_job_times_out_without_holding_up_the_others() {
+        let jobs: Vec<Box<dyn FnOnce() -> u32 + Send>> = vec![
+            Box::new(|| 1),
+            Box::new(|| {
+                std::thread::sleep(Duration::from_secs(5));
+                2
+            }),
+            Box::new(|| 3),
+            Box::new(|| 4),
+        ];
+
+        // With one worker the jobs after the hung one only run because it is replaced
+        let start = Instant::now();
+        let results = run_bounded(jobs, 1, Some(Duration::from_millis(100)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(results, vec![Some(1), None, Some(3), Some(4)]);
+
+        let jobs: Vec<Box<dyn FnOnce() -> u32 + Send>> = (0..10u32).map(|i| Box::new(move || i) as Box<dyn FnOnce() -> u32 + Send>).collect();
+        assert_eq!(run_bounded(jobs, 4, None), (0..10).map(Some).collect::<Vec<_>>());
+    }
+}
@@ -1,8 +1,16 @@
-use rusb::{Context, Device, DeviceDescriptor, DeviceHandle, UsbContext};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Duration;
-use anyhow::{Result, Context as AnyhowContext};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio_stream::Stream;
+use tokio_stream::wrappers::ReceiverStream;
+use anyhow::Result;
+use crate::audit::AuditLog;
+use crate::config::MatchedRule;
+use crate::config::DeviceIdentity;
+use crate::config::NamePatternsMode;
+use crate::usb_backend::{DescriptorCache, RusbBackend, UsbBackend};
 use log::{debug, info, error, warn};
 use chrono::{DateTime, Utc};
 
@@ -23,14 +31,56 @@ pub struct UsbDeviceInfo {
     pub num_configurations: u8,
     pub timestamp: DateTime<Utc>,
     pub connection_status: ConnectionStatus,
+    /// Stable hash of `vid:pid:serial` (or `vid:pid` without a serial) for matching the same
+    /// device across exports and machines; see `device_fingerprint`
+    #[serde(default)]
+    pub fingerprint: String,
+    /// Hex of the descriptors read from the device when `monitoring.raw_descriptors` is on.
+    /// Only JSON device output includes it, so history, snapshots and audit logs stay small.
+    #[serde(skip)]
+    pub raw_descriptor: Option<String>,
+}
+
+/// Case-insensitive regex for a whole-string glob where `*` is any run of characters and `?` one character
+fn glob_regex(pattern: &str) -> regex::Regex {
+    let body = regex::escape(pattern).replace(r"\*", ".*").replace(r"\?", ".");
+    regex::Regex::new(&format!("(?i)^{}$", body)).expect("an escaped glob is a valid regex")
+}
+
+/// Fingerprint of a device, identical across runs, builds and machines
+pub fn device_fingerprint(vendor_id: u16, product_id: u16, serial: Option<&str>) -> String {
+    let identity = match serial.filter(|serial| !serial.is_empty()) {
+        Some(serial) => format!("{:04x}:{:04x}:{}", vendor_id, product_id, serial),
+        None => format!("{:04x}:{:04x}", vendor_id, product_id),
+    };
+    format!("{:016x}", crate::config::fnv1a(identity.as_bytes()))
+}
+
+impl UsbDeviceInfo {
+    /// Recompute `fingerprint`, e.g. after the serial number was filled in
+    pub fn update_fingerprint(&mut self) {
+        self.fingerprint = device_fingerprint(self.vendor_id, self.product_id, self.serial_number.as_deref());
+    }
+
+    /// A name for messages and lists: the product string, else the manufacturer, else the
+    /// product's name in the USB ID database, else `vid:pid`
+    pub fn display_name(&self) -> String {
+        self.product.clone()
+            .or_else(|| self.manufacturer.clone())
+            .or_else(|| usb_ids::Device::from_vid_pid(self.vendor_id, self.product_id).map(|device| device.name().to_string()))
+            .unwrap_or_else(|| format!("{:04x}:{:04x}", self.vendor_id, self.product_id))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
 pub enum ConnectionStatus {
     Connected,
     Disconnected,
     Reconnected,
     Blocked,
+    /// Still attached but in a low-power state
+    Suspended,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +91,7 @@ pub struct DeviceStatistics {
     pub first_seen: DateTime<Utc>,
     pub last_seen: DateTime<Utc>,
     pub connection_duration: Duration,
+    /// Allowed connections currently open; always `total_connections - total_disconnections`
     pub connection_count: u32,
 }
 
@@ -49,12 +100,121 @@ pub struct DeviceAnalytics {
     pub device_class_distribution: HashMap<u8, u32>,
     pub vendor_distribution: HashMap<u16, u32>,
     pub connection_frequency: Vec<(DateTime<Utc>, u32)>,
+    /// Arrivals over the session: connections, reconnections and blocks
     pub total_devices_seen: u32,
+    /// Devices with statistics; a device that re-enumerated under a new key counts once
     pub unique_devices: u32,
+    /// Distinct device fingerprints, so identical models with different serials count separately
+    #[serde(default)]
+    pub unique_fingerprints: u32,
+    /// Devices the last enumeration left out because of `filters.ignored_vendors`/`ignored_products`
+    #[serde(default)]
+    pub ignored_devices: u32,
     pub blocked_devices: u32,
     pub security_violations: u32,
 }
 
+/// A device's statistics together with the device they belong to
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStatisticsReport {
+    pub device_key: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub name: Option<String>,
+    pub statistics: DeviceStatistics,
+}
+
+/// One stretch of time a device was connected, for timeline charts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConnectionInterval {
+    pub device_key: String,
+    pub connected_at: DateTime<Utc>,
+    /// `None` while the device is still connected
+    pub disconnected_at: Option<DateTime<Utc>>,
+}
+
+/// Turn chronological connection history into intervals, one per connect/disconnect pair.
+///
+/// A disconnect whose connect was already trimmed from the history is skipped, and a blocked
+/// device ends any open interval since it is no longer usable.
+fn connection_timeline<'a>(
+    history: impl IntoIterator<Item = (DateTime<Utc>, &'a str, &'a ConnectionStatus)>,
+) -> Vec<ConnectionInterval> {
+    let mut intervals: Vec<ConnectionInterval> = Vec::new();
+    let mut open: HashMap<&str, usize> = HashMap::new();
+
+    for (timestamp, device_key, status) in history {
+        match status {
+            ConnectionStatus::Connected | ConnectionStatus::Reconnected => {
+                open.entry(device_key).or_insert_with(|| {
+                    intervals.push(ConnectionInterval {
+                        device_key: device_key.to_string(),
+                        connected_at: timestamp,
+                        disconnected_at: None,
+                    });
+                    intervals.len() - 1
+                });
+            }
+            ConnectionStatus::Disconnected | ConnectionStatus::Blocked => {
+                if let Some(index) = open.remove(device_key) {
+                    intervals[index].disconnected_at = Some(timestamp);
+                }
+            }
+            ConnectionStatus::Suspended => {}
+        }
+    }
+
+    intervals
+}
+
+/// Connections per hour over the 24 hours ending at `now`.
+///
+/// If the clock was set back after some events were recorded, the window ends at the newest
+/// event instead, so the wall-clock jump can't push recent events out of the window. Each event
+/// is placed by its own timestamp and clamped into the 24 buckets, so history that isn't in
+/// chronological order is counted correctly too.
+fn hourly_connection_frequency<'a>(
+    timestamps: impl Iterator<Item = &'a DateTime<Utc>> + Clone,
+    now: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, u32)> {
+    let end = timestamps.clone().max().map_or(now, |newest| now.max(*newest));
+    let start = end - chrono::Duration::hours(24);
+
+    let mut counts = [0u32; 24];
+    for timestamp in timestamps.filter(|timestamp| **timestamp >= start) {
+        let hour = (*timestamp - start).num_hours().clamp(0, 23) as usize;
+        counts[hour] += 1;
+    }
+
+    counts.iter()
+        .enumerate()
+        .map(|(hour, count)| (start + chrono::Duration::hours(hour as i64), *count))
+        .collect()
+}
+
+/// Recap of a monitoring session, computed from accumulated statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub total_connections: u32,
+    pub total_disconnections: u32,
+    pub total_blocked: u32,
+    pub unique_devices: u32,
+    /// Most frequently connecting devices, highest first
+    pub top_devices: Vec<TopDevice>,
+    /// Average time from the start of a poll cycle to emitting its changes, if any were detected
+    #[serde(default)]
+    pub avg_detection_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopDevice {
+    pub device_key: String,
+    pub name: Option<String>,
+    pub connections: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityEvent {
     pub timestamp: DateTime<Utc>,
@@ -62,9 +222,13 @@ pub struct SecurityEvent {
     pub device_info: UsbDeviceInfo,
     pub reason: String,
     pub action_taken: SecurityAction,
+    /// Id of the `DeviceRule` that decided, if one did
+    #[serde(default)]
+    pub rule_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum SecurityEventType {
     DeviceBlocked,
     DeviceAllowed,
@@ -73,58 +237,413 @@ pub enum SecurityEventType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum SecurityAction {
     Blocked,
     Allowed,
     Warned,
     Logged,
+    /// Blocked and actively detached/reset (see `device_rules.enforce`)
+    #[serde(alias = "Quarantined")]
+    Quarantine,
+}
+
+/// Invoked with the current device count when monitoring has been idle for the heartbeat interval.
+///
+/// `Sync` so that `UsbMonitor` is too: the monitoring service holds `&UsbMonitor` across awaits.
+type HeartbeatCallback = Box<dyn FnMut(usize) -> Result<()> + Send + Sync>;
+
+/// Default retention for connection history and security events (see `output.max_log_entries`)
+const DEFAULT_MAX_LOG_ENTRIES: usize = 1000;
+
+/// Time between polls unless `monitoring.poll_interval_ms` says otherwise
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Delay before the first enumeration retry; each further retry waits twice as long
+const ENUMERATION_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Whether a failure came from a libusb error that usually clears up on its own
+fn is_transient_usb_error(error: &anyhow::Error) -> bool {
+    error.chain()
+        .filter_map(|cause| cause.downcast_ref::<rusb::Error>())
+        .any(|error| matches!(error, rusb::Error::Busy | rusb::Error::Timeout | rusb::Error::Pipe))
+}
+
+/// How long to wait before retrying after `error` on attempt `attempt` (counting from 0), or
+/// `None` when it is permanent or the retries are used up
+fn retry_delay(retries: u32, base_delay: Duration, attempt: u32, error: &anyhow::Error) -> Option<Duration> {
+    if attempt >= retries || !is_transient_usb_error(error) {
+        return None;
+    }
+    let delay = base_delay * 2u32.saturating_pow(attempt);
+    debug!("Transient USB error: {}; retry {}/{} in {:?}", error, attempt + 1, retries, delay);
+    Some(delay)
+}
+
+/// Run `operation`, retrying transient USB errors up to `retries` times with exponential backoff.
+///
+/// Permanent errors such as `Access` or `NoDevice` are returned right away.
+fn retry_transient<T>(retries: u32, base_delay: Duration, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(e) => match retry_delay(retries, base_delay, attempt, &e) {
+                Some(delay) => {
+                    attempt += 1;
+                    std::thread::sleep(delay);
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// `retry_transient` for async callers, waiting without blocking the runtime
+async fn retry_transient_async<T>(retries: u32, base_delay: Duration, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(e) => match retry_delay(retries, base_delay, attempt, &e) {
+                Some(delay) => {
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
 }
 
 pub struct UsbMonitor {
-    context: Context,
+    backend: Box<dyn UsbBackend>,
     previous_devices: HashMap<String, UsbDeviceInfo>,
+    /// Devices that disconnected, keyed by `vid:pid:serial`, mapped to their last address-based key
+    disconnected_identities: HashMap<String, String>,
     device_filter: Option<String>,
+    /// Device classes that are reported; `None` reports every class
+    allowed_device_classes: Option<Vec<u8>>,
+    ignored_vendors: Vec<u16>,
+    ignored_products: Vec<u16>,
+    /// Compiled `filters.name_patterns` and whether a match hides the device or is required
+    name_patterns: Vec<regex::Regex>,
+    name_patterns_mode: NamePatternsMode,
+    /// Devices the last enumeration skipped because their vendor or product is ignored
+    ignored_device_count: std::sync::atomic::AtomicU32,
     device_statistics: HashMap<String, DeviceStatistics>,
-    connection_history: Vec<(DateTime<Utc>, String, ConnectionStatus)>,
-    security_events: Vec<SecurityEvent>,
+    connection_history: VecDeque<(DateTime<Utc>, String, ConnectionStatus)>,
+    security_events: VecDeque<SecurityEvent>,
+    /// Retention cap for `connection_history` and `security_events`
+    max_log_entries: usize,
     config_manager: Option<std::sync::Arc<tokio::sync::RwLock<crate::config::ConfigManager>>>,
+    session_start: DateTime<Utc>,
+    /// How long changes for devices present at startup are suppressed
+    startup_grace_period: Option<Duration>,
+    /// Address and identity keys of devices present at startup, with the end of the grace period
+    startup_devices: Option<(HashSet<String>, DateTime<Utc>)>,
+    heartbeat: Option<(Duration, HeartbeatCallback)>,
+    /// When set, monitoring compares full snapshots on this cadence instead of polling for events
+    snapshot_interval: Option<Duration>,
+    /// Time between polls in `start_monitoring_until` and `change_stream`
+    poll_interval: Duration,
+    /// Where allow/deny decisions are recorded, independent of the device output
+    audit_log: Option<AuditLog>,
+    /// Sum of per-change detection latencies and the number of changes measured
+    detection_latency: (Duration, u32),
+    /// How long a serial-bearing device's disconnect is held back in case it returns (suspend/resume)
+    suspend_window: Option<Duration>,
+    /// Held-back disconnects by identity key: address-based key, device and when it vanished
+    pending_disconnects: HashMap<String, (String, UsbDeviceInfo, Instant)>,
+    device_identity: DeviceIdentity,
+    /// Last known details of every device with statistics, including ones no longer connected
+    known_devices: HashMap<String, UsbDeviceInfo>,
+    /// Descriptor strings of attached devices, so each device is opened once rather than every poll
+    descriptor_cache: std::sync::Mutex<DescriptorCache>,
+    /// How often a transiently failing enumeration is retried within one cycle
+    enumeration_retries: u32,
+    /// Most devices tracked per cycle (`monitoring.max_devices`)
+    max_devices: Option<usize>,
+    /// Whether the last enumeration exceeded `max_devices`, so a storm is reported once
+    device_storm: bool,
+    /// Count serial-less devices by VID:PID rather than by port in `unique_devices`
+    merge_serialless_by_vidpid: bool,
 }
 
 impl UsbMonitor {
     /// Create a new USB monitor instance
     pub fn new() -> Result<Self> {
-        let context = Context::new()
-            .context("Failed to create USB context")?;
+        Ok(Self::with_backend(Box::new(RusbBackend::new()?)))
+    }
+
+    /// Create a USB monitor whose backend honours the monitoring configuration
+    pub fn from_config(config: &crate::config::Config) -> Result<Self> {
+        let backend = RusbBackend::new()?
+            .with_log_level(config.monitoring.usb_debug)
+            .with_udev_enrichment(config.monitoring.udev_enrichment)
+            .with_descriptor_strings(config.monitoring.descriptor_strings)
+            .with_enumeration_timeout(config.monitoring.enumeration_timeout_ms)
+            .with_raw_descriptors(config.monitoring.raw_descriptors);
         
-        Ok(Self {
-            context,
+        let mut monitor = Self::with_backend(Box::new(backend));
+        monitor.apply_config(config)?;
+        Ok(monitor)
+    }
+
+    /// Apply the monitoring, filter and audit log settings of `config`; the backend is kept as is
+    pub fn apply_config(&mut self, config: &crate::config::Config) -> Result<()> {
+        self.set_device_identity(config.monitoring.device_identity);
+        self.set_poll_interval(Duration::from_millis(config.monitoring.poll_interval_ms));
+        self.apply_filter_config(&config.filters);
+        self.set_enumeration_retries(config.monitoring.enumeration_retries);
+        self.set_max_devices(match config.monitoring.max_devices {
+            0 => None,
+            max => Some(max),
+        });
+        self.set_suspend_window(match config.monitoring.suspend_window_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        });
+        self.set_merge_serialless_by_vidpid(config.analytics.merge_serialless_by_vidpid);
+        if let Some(path) = &config.logging.audit_log_path {
+            self.set_audit_log(AuditLog::open(path)?);
+        }
+        Ok(())
+    }
+
+    /// Create a USB monitor on top of a specific backend
+    pub fn with_backend(backend: Box<dyn UsbBackend>) -> Self {
+        Self {
+            backend,
             previous_devices: HashMap::new(),
+            disconnected_identities: HashMap::new(),
             device_filter: None,
+            allowed_device_classes: None,
+            ignored_vendors: Vec::new(),
+            ignored_products: Vec::new(),
+            name_patterns: Vec::new(),
+            name_patterns_mode: NamePatternsMode::Ignore,
+            ignored_device_count: std::sync::atomic::AtomicU32::new(0),
             device_statistics: HashMap::new(),
-            connection_history: Vec::new(),
-            security_events: Vec::new(),
+            connection_history: VecDeque::new(),
+            security_events: VecDeque::new(),
+            max_log_entries: DEFAULT_MAX_LOG_ENTRIES,
             config_manager: None,
-        })
+            session_start: Utc::now(),
+            startup_grace_period: None,
+            startup_devices: None,
+            heartbeat: None,
+            snapshot_interval: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            audit_log: None,
+            detection_latency: (Duration::ZERO, 0),
+            suspend_window: None,
+            pending_disconnects: HashMap::new(),
+            device_identity: DeviceIdentity::PortBased,
+            known_devices: HashMap::new(),
+            descriptor_cache: std::sync::Mutex::new(DescriptorCache::default()),
+            enumeration_retries: crate::config::DEFAULT_ENUMERATION_RETRIES,
+            max_devices: None,
+            device_storm: false,
+            merge_serialless_by_vidpid: false,
+        }
     }
 
     /// Set the configuration manager for device rules
     pub fn set_config_manager(&mut self, config_manager: std::sync::Arc<tokio::sync::RwLock<crate::config::ConfigManager>>) {
+        if let Ok(config) = config_manager.try_read() {
+            self.set_max_log_entries(config.get_config().output.max_log_entries);
+        }
         self.config_manager = Some(config_manager);
         info!("Configuration manager set for device rules");
     }
 
+    /// Set how many connection-history entries and security events are retained
+    pub fn set_max_log_entries(&mut self, max_log_entries: usize) {
+        self.max_log_entries = max_log_entries;
+        trim_to(&mut self.connection_history, max_log_entries);
+        trim_to(&mut self.security_events, max_log_entries);
+    }
+
+    /// Suppress changes for devices that were already present at startup for `grace_period`
+    pub fn set_startup_grace_period(&mut self, grace_period: Option<Duration>) {
+        self.startup_grace_period = grace_period;
+    }
+
+    /// Choose how devices are keyed; call before monitoring starts, as existing keys are not migrated
+    pub fn set_device_identity(&mut self, device_identity: DeviceIdentity) {
+        self.device_identity = device_identity;
+    }
+
+    /// Retry enumerations that fail with Busy, Timeout or Pipe up to `retries` times per cycle
+    pub fn set_enumeration_retries(&mut self, retries: u32) {
+        self.enumeration_retries = retries;
+    }
+
+    /// Count serial-less devices with the same VID:PID as one unique device, on any port
+    pub fn set_merge_serialless_by_vidpid(&mut self, merge: bool) {
+        self.merge_serialless_by_vidpid = merge;
+    }
+
+    /// Number of distinct devices with statistics, for the `unique_devices` counts
+    fn unique_device_count(&self) -> u32 {
+        if !self.merge_serialless_by_vidpid {
+            return self.device_statistics.len() as u32;
+        }
+        self.device_statistics.keys()
+            .map(|key| match self.known_devices.get(key) {
+                Some(device) if device.serial_number.as_deref().is_none_or(str::is_empty) => {
+                    format!("{}:{}", device.vendor_id, device.product_id)
+                }
+                _ => key.clone(),
+            })
+            .collect::<HashSet<_>>()
+            .len() as u32
+    }
+
+    /// Track at most `max_devices` devices per cycle; more than that is reported as a device storm
+    pub fn set_max_devices(&mut self, max_devices: Option<usize>) {
+        self.max_devices = max_devices;
+    }
+
+    /// Treat a serial-bearing device that disconnects and returns within `window` as suspended.
+    ///
+    /// Disconnects of such devices are reported up to `window` late while waiting for them to return.
+    pub fn set_suspend_window(&mut self, window: Option<Duration>) {
+        self.suspend_window = window;
+    }
+
+    /// Record every allow/deny decision to `audit_log`
+    pub fn set_audit_log(&mut self, audit_log: AuditLog) {
+        self.audit_log = Some(audit_log);
+    }
+
+    /// Enumerate every `interval` and report only devices added or removed since the last snapshot.
+    ///
+    /// Snapshot changes are plain `Connected`/`Disconnected`; reconnect, suspend and rule handling
+    /// only apply to regular polling.
+    pub fn set_snapshot_interval(&mut self, interval: Option<Duration>) {
+        self.snapshot_interval = interval;
+    }
+
+    /// Wait `interval` between polls
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Call `callback` with the device count whenever `interval` passes without any changes
+    pub fn set_heartbeat<F>(&mut self, interval: Duration, callback: F)
+    where
+        F: FnMut(usize) -> Result<()> + Send + Sync + 'static,
+    {
+        self.heartbeat = Some((interval, Box::new(callback)));
+    }
+
     /// Set a device filter pattern
     pub fn set_filter(&mut self, filter: Option<String>) {
         self.device_filter = filter;
     }
 
+    /// Only report devices whose class is in `classes`; combined with the name filter, a device must pass both
+    pub fn set_allowed_classes(&mut self, classes: Option<Vec<u8>>) {
+        self.allowed_device_classes = classes;
+    }
+
+    /// Leave devices from `vendors`, or with a product ID in `products`, out of enumeration and change detection
+    pub fn set_ignored_devices(&mut self, vendors: Vec<u16>, products: Vec<u16>) {
+        self.ignored_vendors = vendors;
+        self.ignored_products = products;
+    }
+
+    /// Hide devices whose product or manufacturer matches one of the glob `patterns`, or with
+    /// `NamePatternsMode::Allow`, show only those; no patterns means no name filtering
+    pub fn set_name_patterns(&mut self, patterns: &[String], mode: NamePatternsMode) {
+        self.name_patterns = patterns.iter().map(|pattern| glob_regex(pattern)).collect();
+        self.name_patterns_mode = mode;
+    }
+
+    /// Apply the class, ignore and name pattern lists from the `filters` section of the configuration
+    pub fn apply_filter_config(&mut self, filters: &crate::config::FilterConfig) {
+        self.set_allowed_classes(filters.allowed_device_classes.clone());
+        self.set_ignored_devices(filters.ignored_vendors.clone(), filters.ignored_products.clone());
+        self.set_name_patterns(&filters.name_patterns, filters.name_patterns_mode);
+    }
+
+    /// Whether `device` passes `filters.name_patterns`
+    fn passes_name_patterns(&self, device: &UsbDeviceInfo) -> bool {
+        if self.name_patterns.is_empty() {
+            return true;
+        }
+        let matched = [&device.product, &device.manufacturer].into_iter()
+            .flatten()
+            .any(|name| self.name_patterns.iter().any(|pattern| pattern.is_match(name)));
+        match self.name_patterns_mode {
+            NamePatternsMode::Ignore => !matched,
+            NamePatternsMode::Allow => matched,
+        }
+    }
+
+    /// Suspend or resume rule enforcement for this session; a no-op without a configuration manager
+    pub async fn set_blocking_suspended(&self, suspended: bool) {
+        if let Some(config_manager) = &self.config_manager {
+            config_manager.write().await.set_blocking_suspended(suspended);
+        }
+    }
+
     /// Get device statistics for a specific device
     pub fn get_device_statistics(&self, device_key: &str) -> Option<&DeviceStatistics> {
         self.device_statistics.get(device_key)
     }
 
+    /// Statistics for every device seen this session, in no particular order
+    pub fn get_statistics_report(&self) -> Vec<DeviceStatisticsReport> {
+        self.device_statistics
+            .iter()
+            .filter_map(|(key, stats)| {
+                let device = self.get_device_info_from_key(key)?;
+                let mut statistics = stats.clone();
+                
+                // Durations are only recomputed on changes; bring still-connected devices up to now
+                if self.previous_devices.contains_key(key) {
+                    if let Some((first_connection, _, _)) = self.connection_history
+                        .iter()
+                        .find(|(_, history_key, status)| history_key == key && matches!(status, ConnectionStatus::Connected))
+                    {
+                        statistics.connection_duration = Utc::now().signed_duration_since(*first_connection)
+                            .to_std()
+                            .unwrap_or(statistics.connection_duration);
+                    }
+                }
+                
+                Some(DeviceStatisticsReport {
+                    device_key: key.clone(),
+                    vendor_id: device.vendor_id,
+                    product_id: device.product_id,
+                    name: Some(device.display_name()),
+                    statistics,
+                })
+            })
+            .collect()
+    }
+
+    /// Descriptor cache hits and misses (device opens) over the session
+    pub fn descriptor_cache_stats(&self) -> (u64, u64) {
+        let cache = self.descriptor_cache.lock().unwrap();
+        (cache.hits, cache.misses)
+    }
+
+    /// Total time spent opening devices for string descriptors over the session
+    pub fn descriptor_read_time(&self) -> Duration {
+        self.descriptor_cache.lock().unwrap().read_time
+    }
+
+    /// Drop cached descriptor strings so the next scan reads every device again
+    pub fn clear_descriptor_cache(&self) {
+        self.descriptor_cache.lock().unwrap().clear();
+    }
+
     /// Get security events
-    pub fn get_security_events(&self) -> &[SecurityEvent] {
+    pub fn get_security_events(&self) -> &VecDeque<SecurityEvent> {
         &self.security_events
     }
 
@@ -132,13 +651,12 @@ impl UsbMonitor {
     pub fn get_device_analytics(&self) -> DeviceAnalytics {
         let mut class_distribution = HashMap::new();
         let mut vendor_distribution = HashMap::new();
-        let mut unique_devices = std::collections::HashSet::new();
+        let mut total_devices_seen = 0;
         let mut blocked_count = 0;
-        let mut security_violations = 0;
         
         // Analyze all devices we've seen
         for (key, stats) in &self.device_statistics {
-            unique_devices.insert(key.clone());
+            total_devices_seen += stats.total_connections + stats.total_blocked;
             blocked_count += stats.total_blocked;
             
             // Get device info from connection history
@@ -149,39 +667,68 @@ impl UsbMonitor {
         }
         
         // Count security violations
-        security_violations = self.security_events.len() as u32;
+        let security_violations = self.security_events.len() as u32;
         
         // Generate connection frequency data (last 24 hours, hourly buckets)
-        let mut connection_frequency = Vec::new();
-        let now = Utc::now();
-        let one_day_ago = now - chrono::Duration::hours(24);
-        
-        for hour in 0..24 {
-            let hour_start = one_day_ago + chrono::Duration::hours(hour);
-            let hour_end = hour_start + chrono::Duration::hours(1);
-            
-            let connections_in_hour = self.connection_history
-                .iter()
-                .filter(|(timestamp, _, status)| {
-                    *timestamp >= hour_start && *timestamp < hour_end && 
-                    matches!(status, ConnectionStatus::Connected)
-                })
-                .count();
-            
-            connection_frequency.push((hour_start, connections_in_hour as u32));
-        }
+        let connections = self.connection_history
+            .iter()
+            .filter(|(_, _, status)| matches!(status, ConnectionStatus::Connected))
+            .map(|(timestamp, _, _)| timestamp);
+        let connection_frequency = hourly_connection_frequency(connections, Utc::now());
         
         DeviceAnalytics {
             device_class_distribution: class_distribution,
-            vendor_distribution: vendor_distribution,
+            vendor_distribution,
             connection_frequency,
-            total_devices_seen: self.connection_history.len() as u32,
-            unique_devices: unique_devices.len() as u32,
+            total_devices_seen,
+            unique_devices: self.unique_device_count(),
+            unique_fingerprints: self.known_devices.values()
+                .map(|device| device.fingerprint.as_str())
+                .collect::<HashSet<_>>()
+                .len() as u32,
+            ignored_devices: self.ignored_device_count.load(std::sync::atomic::Ordering::Relaxed),
             blocked_devices: blocked_count,
             security_violations,
         }
     }
 
+    /// Summarize the session so far: totals, unique devices and the top 5 most-connecting devices
+    pub fn get_session_summary(&self) -> SessionSummary {
+        let mut top_devices: Vec<TopDevice> = self.device_statistics
+            .iter()
+            .filter(|(_, stats)| stats.total_connections > 0)
+            .map(|(key, stats)| TopDevice {
+                device_key: key.clone(),
+                name: self.get_device_info_from_key(key).map(UsbDeviceInfo::display_name),
+                connections: stats.total_connections,
+            })
+            .collect();
+        
+        top_devices.sort_by(|a, b| b.connections.cmp(&a.connections).then_with(|| a.device_key.cmp(&b.device_key)));
+        top_devices.truncate(5);
+        
+        SessionSummary {
+            started_at: self.session_start,
+            ended_at: Utc::now(),
+            total_connections: self.device_statistics.values().map(|s| s.total_connections).sum(),
+            total_disconnections: self.device_statistics.values().map(|s| s.total_disconnections).sum(),
+            total_blocked: self.device_statistics.values().map(|s| s.total_blocked).sum(),
+            unique_devices: self.unique_device_count(),
+            top_devices,
+            avg_detection_latency_ms: self.average_detection_latency()
+                .map(|latency| latency.as_secs_f64() * 1000.0),
+        }
+    }
+
+    /// Average time from poll cycle start to change emission over the session.
+    ///
+    /// Polling cannot see when a device was actually plugged in, so this is a lower bound
+    /// on the real latency; the poll interval adds up to one more interval on top.
+    pub fn average_detection_latency(&self) -> Option<Duration> {
+        let (total, samples) = self.detection_latency;
+        (samples > 0).then(|| total / samples)
+    }
+
     /// Get connection history for a specific device
     pub fn get_device_connection_history(&self, device_key: &str) -> Vec<(DateTime<Utc>, ConnectionStatus)> {
         self.connection_history
@@ -191,30 +738,55 @@ impl UsbMonitor {
             .collect()
     }
 
+    /// Connection intervals for every device in the retained history, oldest first
+    pub fn get_connection_timeline(&self) -> Vec<ConnectionInterval> {
+        connection_timeline(self.connection_history
+            .iter()
+            .map(|(timestamp, device_key, status)| (*timestamp, device_key.as_str(), status)))
+    }
+
     /// Check if a device should be blocked based on current rules
     async fn check_device_security(&mut self, device: &UsbDeviceInfo) -> (bool, Option<String>, SecurityAction) {
         if let Some(config_manager) = &self.config_manager {
             let config = config_manager.read().await;
-            let (should_block, reason) = config.should_block_device(device);
+            let (should_block, reason, matched_rule) = config.should_block_device(device);
+            let rule_id = matched_rule.map(|matched| matched.rule.id.clone());
             
             if should_block {
-                let action = SecurityAction::Blocked;
+                let mut action = SecurityAction::Blocked;
+                let mut event_reason = reason.clone().unwrap_or_else(|| "Unknown reason".to_string());
+                if let Some(rule_id) = &rule_id {
+                    event_reason = format!("{} (rule {})", event_reason, rule_id);
+                }
+                
+                if config.get_config().device_rules.enforce {
+                    match self.backend.quarantine(device) {
+                        Ok(outcome) => {
+                            info!("Quarantined device {:04X}:{:04X}: {}", device.vendor_id, device.product_id, outcome);
+                            action = SecurityAction::Quarantine;
+                            event_reason = format!("{} (quarantined: {})", event_reason, outcome);
+                        }
+                        Err(e) => {
+                            warn!("Could not quarantine device {:04X}:{:04X}: {}", device.vendor_id, device.product_id, e);
+                            event_reason = format!("{} (quarantine failed: {})", event_reason, e);
+                        }
+                    }
+                }
+                
                 let event = SecurityEvent {
                     timestamp: Utc::now(),
                     event_type: SecurityEventType::DeviceBlocked,
                     device_info: device.clone(),
-                    reason: reason.clone().unwrap_or_else(|| "Unknown reason".to_string()),
+                    reason: event_reason.clone(),
                     action_taken: action.clone(),
+                    rule_id,
                 };
                 
-                self.security_events.push(event);
-                
-                // Keep only last 1000 security events
-                if self.security_events.len() > 1000 {
-                    self.security_events.remove(0);
-                }
+                record_audit(&mut self.audit_log, &event, matched_rule);
+                push_bounded(&mut self.security_events, event, self.max_log_entries);
                 
-                return (true, reason, action);
+                // The event's reason, which also names the rule and any quarantine outcome
+                return (true, Some(event_reason), action);
             } else {
                 let action = SecurityAction::Allowed;
                 let event = SecurityEvent {
@@ -223,14 +795,11 @@ impl UsbMonitor {
                     device_info: device.clone(),
                     reason: "Device passed security checks".to_string(),
                     action_taken: action.clone(),
+                    rule_id,
                 };
                 
-                self.security_events.push(event);
-                
-                // Keep only last 1000 security events
-                if self.security_events.len() > 1000 {
-                    self.security_events.remove(0);
-                }
+                record_audit(&mut self.audit_log, &event, matched_rule);
+                push_bounded(&mut self.security_events, event, self.max_log_entries);
                 
                 return (false, None, action);
             }
@@ -239,50 +808,153 @@ impl UsbMonitor {
         (false, None, SecurityAction::Allowed)
     }
 
+    /// Cut an enumeration down to `max_devices`, keeping already-tracked devices first so a
+    /// storm of phantom devices can't push real ones out. The first cycle over the limit records
+    /// a `SuspiciousActivity` event; the next one back under it ends the storm.
+    fn limit_devices(&mut self, devices: Vec<UsbDeviceInfo>) -> Vec<UsbDeviceInfo> {
+        let Some(max_devices) = self.max_devices.filter(|max| devices.len() > *max) else {
+            if std::mem::take(&mut self.device_storm) {
+                info!("Device storm over; all enumerated devices are tracked again");
+            }
+            return devices;
+        };
+
+        let total = devices.len();
+        let (known, new): (Vec<_>, Vec<_>) = devices.into_iter()
+            .partition(|device| self.previous_devices.contains_key(&self.create_device_key(device)));
+        let mut ordered = known.into_iter().chain(new);
+        let kept: Vec<_> = ordered.by_ref().take(max_devices).collect();
+
+        if !std::mem::replace(&mut self.device_storm, true) {
+            let reason = format!("Device storm detected: {} devices enumerated, only {} are tracked", total, max_devices);
+            warn!("{}", reason);
+            if let Some(first_dropped) = ordered.next() {
+                let event = SecurityEvent {
+                    timestamp: Utc::now(),
+                    event_type: SecurityEventType::SuspiciousActivity,
+                    device_info: first_dropped,
+                    reason,
+                    action_taken: SecurityAction::Logged,
+                    rule_id: None,
+                };
+                push_bounded(&mut self.security_events, event, self.max_log_entries);
+            }
+        }
+        kept
+    }
+
+    /// Whether a device with this VID/PID (and serial number, if given) is connected.
+    /// Uses the last scan while monitoring has state, and enumerates once otherwise;
+    /// an enumeration failure is logged and counts as not connected.
+    pub fn is_connected(&self, vendor_id: u16, product_id: u16, serial: Option<&str>) -> bool {
+        let matches = |device: &UsbDeviceInfo| {
+            device.vendor_id == vendor_id
+                && device.product_id == product_id
+                && serial.is_none_or(|serial| device.serial_number.as_deref() == Some(serial))
+        };
+
+        if !self.previous_devices.is_empty() {
+            return self.previous_devices.values()
+                .any(|device| device.connection_status != ConnectionStatus::Disconnected && matches(device));
+        }
+
+        match self.get_connected_devices() {
+            Ok(devices) => devices.iter().any(matches),
+            Err(e) => {
+                warn!("Could not enumerate USB devices: {}", e);
+                false
+            }
+        }
+    }
+
     /// Get all currently connected USB devices
     pub fn get_connected_devices(&self) -> Result<Vec<UsbDeviceInfo>> {
-        let devices = self.context.devices()
-            .context("Failed to get device list")?;
-        
+        let devices = retry_transient(self.enumeration_retries, ENUMERATION_RETRY_DELAY, || self.enumerate_once())?;
+        Ok(self.filter_connected(devices))
+    }
+
+    /// `get_connected_devices` for async callers: retries wait without blocking the runtime
+    pub async fn get_connected_devices_async(&self) -> Result<Vec<UsbDeviceInfo>> {
+        let devices = retry_transient_async(self.enumeration_retries, ENUMERATION_RETRY_DELAY, || self.enumerate_once()).await?;
+        Ok(self.filter_connected(devices))
+    }
+
+    /// Enumerate once through the descriptor cache, which is only locked for this attempt
+    fn enumerate_once(&self) -> Result<Vec<UsbDeviceInfo>> {
+        let mut cache = self.descriptor_cache.lock().unwrap();
+        let (hits, misses) = (cache.hits, cache.misses);
+        let devices = self.backend.enumerate_cached(&mut cache)?;
+        if cache.misses > misses {
+            debug!("Descriptor strings: {} from cache, {} read by opening the device",
+                   cache.hits - hits, cache.misses - misses);
+        }
+        Ok(devices)
+    }
+
+    /// Drop the devices excluded by the filters, counting the ignored ones
+    fn filter_connected(&self, devices: Vec<UsbDeviceInfo>) -> Vec<UsbDeviceInfo> {
         let mut device_info_list = Vec::new();
+        let mut ignored = 0;
         
-        for device in devices.iter() {
-            match self.get_device_info(&device) {
-                Ok(mut info) => {
-                    // Check device security before adding to list (synchronous for now)
-                    // TODO: Implement async security checking in a separate method
-                    
-                    // Apply filter if set
-                    if let Some(ref filter) = self.device_filter {
-                        if let Some(ref product) = info.product {
-                            if !product.to_lowercase().contains(&filter.to_lowercase()) {
-                                continue;
-                            }
-                        } else if let Some(ref manufacturer) = info.manufacturer {
-                            if !manufacturer.to_lowercase().contains(&manufacturer.to_lowercase()) {
-                                continue;
-                            }
-                        } else {
-                            continue;
-                        }
-                    }
-                    
-                    device_info_list.push(info);
+        for info in devices {
+            // Device rules are applied by `get_connected_devices_with_policy`
+
+            if self.ignored_vendors.contains(&info.vendor_id) || self.ignored_products.contains(&info.product_id) {
+                ignored += 1;
+                continue;
+            }
+
+            if let Some(ref classes) = self.allowed_device_classes {
+                if !classes.contains(&info.device_class) {
+                    continue;
                 }
-                Err(e) => {
-                    debug!("Failed to get device info: {}", e);
+            }
+
+            if !self.passes_name_patterns(&info) {
+                continue;
+            }
+
+            // Apply filter if set
+            if let Some(ref filter) = self.device_filter {
+                if let Some(ref product) = info.product {
+                    if !product.to_lowercase().contains(&filter.to_lowercase()) {
+                        continue;
+                    }
+                } else if let Some(ref manufacturer) = info.manufacturer {
+                    if !manufacturer.to_lowercase().contains(&manufacturer.to_lowercase()) {
+                        continue;
+                    }
+                } else {
+                    continue;
                 }
             }
+            
+            device_info_list.push(info);
         }
         
-        Ok(device_info_list)
+        self.ignored_device_count.store(ignored, std::sync::atomic::Ordering::Relaxed);
+        device_info_list
     }
     
+    /// Get all currently connected USB devices with the device rules' decision for each,
+    /// marking those the rules block as `Blocked`
+    pub async fn get_connected_devices_with_policy(&self) -> Result<Vec<(UsbDeviceInfo, crate::config::PolicyDecision)>> {
+        let mut devices = Vec::new();
+        for mut device in self.get_connected_devices_async().await? {
+            let (blocked, reason, _) = self.check_device_security_async(&device).await;
+            if blocked {
+                device.connection_status = ConnectionStatus::Blocked;
+            }
+            devices.push((device, crate::config::PolicyDecision { blocked, reason }));
+        }
+        Ok(devices)
+    }
+
     /// Check device security asynchronously (separate from device enumeration)
     pub async fn check_device_security_async(&self, device: &UsbDeviceInfo) -> (bool, Option<String>, SecurityAction) {
         if let Some(config_manager) = &self.config_manager {
             let config = config_manager.read().await;
-            let (should_block, reason) = config.should_block_device(device);
+            let (should_block, reason, _) = config.should_block_device(device);
             
             if should_block {
                 let action = SecurityAction::Blocked;
@@ -298,7 +970,9 @@ impl UsbMonitor {
 
     /// Monitor for device changes (connect/disconnect events)
     pub async fn monitor_changes(&mut self) -> Result<Vec<UsbDeviceChange>> {
-        let current_devices = self.get_connected_devices()?;
+        let cycle_start = Instant::now();
+        let current_devices = self.get_connected_devices_async().await?;
+        let current_devices = self.limit_devices(current_devices);
         let mut changes = Vec::new();
         
         // Create a map of current devices by their unique identifier
@@ -308,14 +982,40 @@ impl UsbMonitor {
             current_device_map.insert(device_key, device.clone());
         }
         
-        // Check for disconnected devices
+        // Held-back disconnects whose device did not return in time are real disconnects
         let mut disconnected_keys = Vec::new();
+        if let Some(window) = self.suspend_window {
+            let expired: Vec<String> = self.pending_disconnects.iter()
+                .filter(|(_, (_, _, since))| since.elapsed() >= window)
+                .map(|(identity, _)| identity.clone())
+                .collect();
+            
+            for identity in expired {
+                if let Some((key, device)) = self.pending_disconnects.remove(&identity).map(|(key, device, _)| (key, device)) {
+                    self.disconnected_identities.insert(identity, key.clone());
+                    disconnected_keys.push((key, device.clone()));
+                    changes.push(UsbDeviceChange::Disconnected(device));
+                }
+            }
+        }
+        
+        // Check for disconnected devices
         for (key, prev_device) in &self.previous_devices {
             if !current_device_map.contains_key(key) {
                 let mut disconnected_device = prev_device.clone();
                 disconnected_device.connection_status = ConnectionStatus::Disconnected;
                 disconnected_device.timestamp = Utc::now();
                 
+                let identity = self.create_identity_key(prev_device);
+                if let (Some(_), Some(identity)) = (self.suspend_window, identity.clone()) {
+                    self.pending_disconnects.insert(identity, (key.clone(), disconnected_device, Instant::now()));
+                    continue;
+                }
+                
+                if let Some(identity) = identity {
+                    self.disconnected_identities.insert(identity, key.clone());
+                }
+                
                 disconnected_keys.push((key.clone(), disconnected_device.clone()));
                 changes.push(UsbDeviceChange::Disconnected(disconnected_device));
             }
@@ -324,20 +1024,52 @@ impl UsbMonitor {
         // Check for new/reconnected devices
         let mut new_devices = Vec::new();
         let mut reconnected_devices = Vec::new();
+        let mut suspended_devices = Vec::new();
+        let mut rekeyed_devices = Vec::new();
         
         for (key, current_device) in &current_device_map {
             match self.previous_devices.get(key) {
                 None => {
+                    // Came back within the suspend window: a power-state blip, not a new arrival
+                    let returned = self.create_identity_key(current_device)
+                        .and_then(|identity| self.pending_disconnects.remove(&identity));
+                    if let Some((previous_key, _, _)) = returned {
+                        debug!("Device {} returned as {} within the suspend window", previous_key, key);
+                        rekeyed_devices.push((previous_key, key.clone()));
+                        let mut suspended_device = current_device.clone();
+                        suspended_device.connection_status = ConnectionStatus::Suspended;
+                        
+                        suspended_devices.push((key.clone(), suspended_device.clone()));
+                        changes.push(UsbDeviceChange::Suspended(suspended_device));
+                        continue;
+                    }
+                    
                     // New device - check security
-                    let (is_blocked, reason, action) = self.check_device_security(current_device).await;
+                    let (is_blocked, reason, _action) = self.check_device_security(current_device).await;
+                    
+                    // A device with a serial that disconnected earlier (possibly from a
+                    // different address) is the same physical device coming back
+                    let previous_key = self.create_identity_key(current_device)
+                        .and_then(|identity| self.disconnected_identities.remove(&identity));
+                    if let Some(previous_key) = &previous_key {
+                        rekeyed_devices.push((previous_key.clone(), key.clone()));
+                    }
                     
                     let mut new_device = current_device.clone();
+                    let reason = reason.unwrap_or_else(|| "Unknown reason".to_string());
                     if is_blocked {
                         new_device.connection_status = ConnectionStatus::Blocked;
                         warn!("New device blocked: {} (VID:{:04X}, PID:{:04X}) - {}", 
-                              new_device.product.as_deref().unwrap_or("Unknown"),
+                              new_device.display_name(),
                               new_device.vendor_id, new_device.product_id, 
-                              reason.unwrap_or_else(|| "Unknown reason".to_string()));
+                              reason);
+                    } else if let Some(previous_key) = previous_key {
+                        debug!("Device {} re-enumerated as {}", previous_key, key);
+                        new_device.connection_status = ConnectionStatus::Reconnected;
+                        
+                        reconnected_devices.push((key.clone(), new_device.clone()));
+                        changes.push(UsbDeviceChange::Reconnected(new_device));
+                        continue;
                     } else {
                         new_device.connection_status = ConnectionStatus::Connected;
                     }
@@ -345,7 +1077,7 @@ impl UsbMonitor {
                     new_devices.push((key.clone(), new_device.clone()));
                     
                     if is_blocked {
-                        changes.push(UsbDeviceChange::Blocked(new_device));
+                        changes.push(UsbDeviceChange::Blocked(new_device, reason));
                     } else {
                         changes.push(UsbDeviceChange::Connected(new_device));
                     }
@@ -358,6 +1090,18 @@ impl UsbMonitor {
                         
                         reconnected_devices.push((key.clone(), reconnected_device.clone()));
                         changes.push(UsbDeviceChange::Reconnected(reconnected_device));
+                    } else if current_device.connection_status == ConnectionStatus::Suspended
+                        && prev_device.connection_status != ConnectionStatus::Suspended
+                    {
+                        suspended_devices.push((key.clone(), current_device.clone()));
+                        changes.push(UsbDeviceChange::Suspended(current_device.clone()));
+                    } else if prev_device.connection_status == ConnectionStatus::Suspended
+                        && current_device.connection_status != ConnectionStatus::Suspended
+                    {
+                        // Resumed without re-enumerating; it never left, so statistics are unchanged
+                        let mut resumed_device = current_device.clone();
+                        resumed_device.connection_status = ConnectionStatus::Reconnected;
+                        changes.push(UsbDeviceChange::Reconnected(resumed_device));
                     }
                 }
             }
@@ -368,6 +1112,11 @@ impl UsbMonitor {
             self.update_device_statistics(&key, &device, ConnectionStatus::Disconnected);
         }
         
+        // A device back under a new key keeps its history, so it isn't counted twice
+        for (previous_key, key) in rekeyed_devices {
+            self.move_device_statistics(&previous_key, &key);
+        }
+        
         for (key, device) in new_devices {
             let status = if device.connection_status == ConnectionStatus::Blocked {
                 ConnectionStatus::Blocked
@@ -381,23 +1130,109 @@ impl UsbMonitor {
             self.update_device_statistics(&key, &device, ConnectionStatus::Reconnected);
         }
         
+        for (key, device) in suspended_devices {
+            self.update_device_statistics(&key, &device, ConnectionStatus::Suspended);
+        }
+        
         // Update previous devices state
         self.previous_devices = current_device_map;
         
+        changes.retain(|change| !self.is_startup_noise(change.get_device_info()));
+        
+        if !changes.is_empty() {
+            let latency = cycle_start.elapsed();
+            debug!("Detected {} change(s) {:?} after cycle start", changes.len(), latency);
+            self.detection_latency.0 += latency * changes.len() as u32;
+            self.detection_latency.1 += changes.len() as u32;
+        }
+        
+        Ok(changes)
+    }
+
+    /// Enumerate and diff against the previous snapshot: removed devices first, then added ones,
+    /// each sorted by device key so the same transition always prints the same way
+    pub fn snapshot_changes(&mut self) -> Result<Vec<UsbDeviceChange>> {
+        let devices = self.get_connected_devices()?;
+        let current: HashMap<String, UsbDeviceInfo> = self.limit_devices(devices)
+            .into_iter()
+            .map(|device| (self.create_device_key(&device), device))
+            .collect();
+
+        let mut removed: Vec<_> = self.previous_devices.iter()
+            .filter(|(key, _)| !current.contains_key(*key))
+            .map(|(key, device)| (key.clone(), device.clone()))
+            .collect();
+        removed.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut added: Vec<_> = current.iter()
+            .filter(|(key, _)| !self.previous_devices.contains_key(*key))
+            .map(|(key, device)| (key.clone(), device.clone()))
+            .collect();
+        added.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut changes = Vec::with_capacity(removed.len() + added.len());
+        for (key, mut device) in removed {
+            device.connection_status = ConnectionStatus::Disconnected;
+            device.timestamp = Utc::now();
+            self.update_device_statistics(&key, &device, ConnectionStatus::Disconnected);
+            changes.push(UsbDeviceChange::Disconnected(device));
+        }
+        for (key, device) in added {
+            self.update_device_statistics(&key, &device, ConnectionStatus::Connected);
+            changes.push(UsbDeviceChange::Connected(device));
+        }
+
+        self.previous_devices = current;
         Ok(changes)
     }
 
+    /// Run a recorded change through the security check and statistics as if it were live.
+    ///
+    /// Arrivals are re-evaluated against the current rules, so a recorded `Blocked` may
+    /// replay as `Connected` (and vice versa) after the rules have changed.
+    pub async fn apply_recorded_change(&mut self, change: UsbDeviceChange) -> UsbDeviceChange {
+        let key = self.create_device_key(change.get_device_info());
+        let was_reconnect = matches!(change, UsbDeviceChange::Reconnected(_));
+        
+        let change = match change {
+            UsbDeviceChange::Disconnected(mut device) => {
+                device.connection_status = ConnectionStatus::Disconnected;
+                UsbDeviceChange::Disconnected(device)
+            }
+            UsbDeviceChange::Suspended(mut device) => {
+                device.connection_status = ConnectionStatus::Suspended;
+                UsbDeviceChange::Suspended(device)
+            }
+            UsbDeviceChange::Connected(mut device)
+            | UsbDeviceChange::Reconnected(mut device)
+            | UsbDeviceChange::Blocked(mut device, _) => {
+                let (is_blocked, reason, _) = self.check_device_security(&device).await;
+                
+                if is_blocked {
+                    device.connection_status = ConnectionStatus::Blocked;
+                    UsbDeviceChange::Blocked(device, reason.unwrap_or_else(|| "Unknown reason".to_string()))
+                } else if was_reconnect {
+                    device.connection_status = ConnectionStatus::Reconnected;
+                    UsbDeviceChange::Reconnected(device)
+                } else {
+                    device.connection_status = ConnectionStatus::Connected;
+                    UsbDeviceChange::Connected(device)
+                }
+            }
+        };
+        
+        let device = change.get_device_info().clone();
+        self.update_device_statistics(&key, &device, device.connection_status.clone());
+        change
+    }
+
     /// Update device statistics when a change occurs
     fn update_device_statistics(&mut self, device_key: &str, device: &UsbDeviceInfo, status: ConnectionStatus) {
         let now = Utc::now();
+        self.known_devices.insert(device_key.to_string(), device.clone());
         
         // Record connection history
-        self.connection_history.push((now, device_key.to_string(), status.clone()));
-        
-        // Keep only last 1000 entries to prevent memory bloat
-        if self.connection_history.len() > 1000 {
-            self.connection_history.remove(0);
-        }
+        // Oldest entries are dropped to prevent memory bloat
+        push_bounded(&mut self.connection_history, (now, device_key.to_string(), status.clone()), self.max_log_entries);
         
         // Update device statistics
         let stats = self.device_statistics.entry(device_key.to_string()).or_insert(DeviceStatistics {
@@ -418,8 +1253,9 @@ impl UsbMonitor {
                 stats.connection_count += 1;
             }
             ConnectionStatus::Disconnected => {
-                stats.total_disconnections += 1;
+                // A blocked device leaving never counted as connected, so it isn't a disconnection either
                 if stats.connection_count > 0 {
+                    stats.total_disconnections += 1;
                     stats.connection_count -= 1;
                 }
             }
@@ -431,6 +1267,9 @@ impl UsbMonitor {
                 stats.total_blocked += 1;
                 // Don't increment connection count for blocked devices
             }
+            ConnectionStatus::Suspended => {
+                // Still attached; only `last_seen` changes
+            }
         }
         
         // Calculate total connection duration
@@ -444,35 +1283,64 @@ impl UsbMonitor {
         }
     }
 
+    /// Carry a device's statistics and history over to the key it re-enumerated under
+    fn move_device_statistics(&mut self, previous_key: &str, key: &str) {
+        if previous_key == key {
+            return;
+        }
+        
+        if let Some(previous) = self.device_statistics.remove(previous_key) {
+            match self.device_statistics.get_mut(key) {
+                Some(stats) => {
+                    stats.total_connections += previous.total_connections;
+                    stats.total_disconnections += previous.total_disconnections;
+                    stats.total_blocked += previous.total_blocked;
+                    stats.connection_count += previous.connection_count;
+                    stats.first_seen = stats.first_seen.min(previous.first_seen);
+                    stats.last_seen = stats.last_seen.max(previous.last_seen);
+                }
+                None => {
+                    self.device_statistics.insert(key.to_string(), previous);
+                }
+            }
+        }
+        
+        for (_, history_key, _) in self.connection_history.iter_mut() {
+            if history_key == previous_key {
+                *history_key = key.to_string();
+            }
+        }
+        self.known_devices.remove(previous_key);
+    }
+
     /// Start continuous monitoring
-    pub async fn start_monitoring<F>(&mut self, mut callback: F) -> Result<()>
+    pub async fn start_monitoring<F>(&mut self, callback: F) -> Result<()>
+    where
+        F: FnMut(Vec<UsbDeviceChange>) -> Result<()>,
+    {
+        self.start_monitoring_until(callback, Arc::new(AtomicBool::new(false))).await
+    }
+
+    /// Start continuous monitoring until `shutdown` is set
+    pub async fn start_monitoring_until<F>(&mut self, mut callback: F, shutdown: Arc<AtomicBool>) -> Result<()>
     where
         F: FnMut(Vec<UsbDeviceChange>) -> Result<()>,
     {
         info!("Starting USB device monitoring with security enforcement...");
         
-        // Initial device scan
-        let initial_devices = self.get_connected_devices()?;
-        info!("Found {} initial USB devices", initial_devices.len());
+        self.initialize_state()?;
         
-        // Populate initial state
-        for device in initial_devices {
-            let key = self.create_device_key(&device);
-            self.previous_devices.insert(key.clone(), device.clone());
-            
-            // Initialize statistics for initial devices
-            let status = if device.connection_status == ConnectionStatus::Blocked {
-                ConnectionStatus::Blocked
-            } else {
-                ConnectionStatus::Connected
-            };
-            self.update_device_statistics(&key, &device, status);
-        }
+        let mut last_activity = Instant::now();
         
-        loop {
-            match self.monitor_changes().await {
+        while !shutdown.load(Ordering::Relaxed) {
+            let result = match self.snapshot_interval {
+                Some(_) => self.snapshot_changes(),
+                None => self.monitor_changes().await,
+            };
+            match result {
                 Ok(changes) => {
                     if !changes.is_empty() {
+                        last_activity = Instant::now();
                         debug!("Detected {} USB device changes", changes.len());
                         if let Err(e) = callback(changes) {
                             error!("Callback error: {}", e);
@@ -484,97 +1352,133 @@ impl UsbMonitor {
                 }
             }
             
-            // Poll interval
-            tokio::time::sleep(Duration::from_millis(500)).await;
+            let device_count = self.previous_devices.len();
+            if let Some((interval, heartbeat)) = &mut self.heartbeat {
+                if last_activity.elapsed() >= *interval {
+                    if let Err(e) = heartbeat(device_count) {
+                        error!("Heartbeat error: {}", e);
+                    }
+                    last_activity = Instant::now();
+                }
+            }
+            
+            // Poll interval; long intervals are slept in slices so shutdown stays prompt
+            let wake_at = Instant::now() + self.snapshot_interval.unwrap_or(self.poll_interval);
+            while !shutdown.load(Ordering::Relaxed) {
+                let remaining = wake_at.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(remaining.min(DEFAULT_POLL_INTERVAL)).await;
+            }
         }
+        
+        info!("USB device monitoring stopped");
+        Ok(())
     }
 
-    /// Get detailed information about a USB device
-    fn get_device_info(&self, device: &Device<Context>) -> Result<UsbDeviceInfo> {
-        let descriptor = device.device_descriptor()
-            .context("Failed to get device descriptor")?;
-        
-        let bus_number = device.bus_number();
-        let device_address = device.address();
+    /// Consume the monitor and stream each non-empty batch of device changes.
+    ///
+    /// Polling runs on a spawned task and stops once the stream is dropped.
+    pub fn change_stream(mut self) -> impl Stream<Item = Vec<UsbDeviceChange>> {
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
         
-        // Try to open device to get string descriptors
-        let (manufacturer, product, serial_number) = match device.open() {
-            Ok(handle) => self.get_string_descriptors(&handle, &descriptor),
-            Err(_) => {
-                debug!("Could not open device {}:{} for string descriptors", 
-                       bus_number, device_address);
-                (None, None, None)
+        tokio::spawn(async move {
+            info!("Starting USB device change stream...");
+            
+            if let Err(e) = self.initialize_state() {
+                error!("Failed to start change stream: {}", e);
+                return;
             }
-        };
-        
-        Ok(UsbDeviceInfo {
-            bus_number,
-            device_address,
-            vendor_id: descriptor.vendor_id(),
-            product_id: descriptor.product_id(),
-            device_version: {
-                let version = descriptor.device_version();
-                (version.major() as u16) << 8 | (version.minor() as u16)
-            },
-            manufacturer,
-            product,
-            serial_number,
-            device_class: descriptor.class_code(),
-            device_subclass: descriptor.sub_class_code(),
-            device_protocol: descriptor.protocol_code(),
-            max_packet_size: descriptor.max_packet_size(),
-            num_configurations: descriptor.num_configurations(),
-            timestamp: Utc::now(),
-            connection_status: ConnectionStatus::Connected,
-        })
-    }
-
-    /// Extract string descriptors from device
-    fn get_string_descriptors(
-        &self,
-        handle: &DeviceHandle<Context>,
-        descriptor: &DeviceDescriptor,
-    ) -> (Option<String>, Option<String>, Option<String>) {
-        let manufacturer = if let Some(index) = descriptor.manufacturer_string_index() {
-            if index > 0 {
-                handle.read_manufacturer_string_ascii(descriptor).ok()
-            } else {
-                None
+            
+            while !tx.is_closed() {
+                match self.monitor_changes().await {
+                    Ok(changes) => {
+                        if !changes.is_empty() && tx.send(changes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Monitoring error: {}", e);
+                    }
+                }
+                
+                // Poll interval
+                tokio::time::sleep(self.poll_interval).await;
             }
-        } else {
-            None
-        };
+            
+            info!("USB device change stream closed");
+        });
         
-        let product = if let Some(index) = descriptor.product_string_index() {
-            if index > 0 {
-                handle.read_product_string_ascii(descriptor).ok()
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        ReceiverStream::new(rx)
+    }
+
+    /// Record the currently connected devices as the baseline for change detection
+    fn initialize_state(&mut self) -> Result<()> {
+        // Initial device scan
+        let initial_devices = self.get_connected_devices()?;
+        info!("Found {} initial USB devices", initial_devices.len());
+        let initial_devices = self.limit_devices(initial_devices);
+        
+        if let Some(grace_period) = self.startup_grace_period {
+            let keys = initial_devices.iter()
+                .flat_map(|device| std::iter::once(self.create_device_key(device)).chain(self.create_identity_key(device)))
+                .collect();
+            let grace_until = Utc::now() + chrono::Duration::from_std(grace_period).unwrap_or_else(|_| chrono::Duration::zero());
+            self.startup_devices = Some((keys, grace_until));
+        }
         
-        let serial_number = if let Some(index) = descriptor.serial_number_string_index() {
-            if index > 0 {
-                handle.read_serial_number_string_ascii(descriptor).ok()
+        // Populate initial state
+        for device in initial_devices {
+            let key = self.create_device_key(&device);
+            self.previous_devices.insert(key.clone(), device.clone());
+            
+            // Initialize statistics for initial devices
+            let status = if device.connection_status == ConnectionStatus::Blocked {
+                ConnectionStatus::Blocked
             } else {
-                None
-            }
-        } else {
-            None
-        };
+                ConnectionStatus::Connected
+            };
+            self.update_device_statistics(&key, &device, status);
+        }
         
-        (manufacturer, product, serial_number)
+        Ok(())
     }
 
-    /// Create a unique key for device identification
+    /// Create a unique key for device identification according to the configured strategy.
+    ///
+    /// Serial-based strategies fall back to the port-based key for devices without a serial.
     fn create_device_key(&self, device: &UsbDeviceInfo) -> String {
-        format!("{}:{}:{}:{}", 
-                device.vendor_id, 
-                device.product_id, 
-                device.bus_number, 
-                device.device_address)
+        let serial = device.serial_number.as_deref().filter(|serial| !serial.is_empty());
+        
+        match (self.device_identity, serial) {
+            (DeviceIdentity::SerialBased, Some(serial)) => format!("serial:{}", serial),
+            (DeviceIdentity::VidPidSerial, Some(_)) => self.create_identity_key(device).unwrap_or_default(),
+            _ => format!("{}:{}:{}:{}", 
+                    device.vendor_id, 
+                    device.product_id, 
+                    device.bus_number, 
+                    device.device_address),
+        }
+    }
+
+    /// Create a port-independent identity key (`vid:pid:serial`) for devices that report a serial
+    fn create_identity_key(&self, device: &UsbDeviceInfo) -> Option<String> {
+        device.serial_number
+            .as_deref()
+            .filter(|serial| !serial.is_empty())
+            .map(|serial| format!("{}:{}:{}", device.vendor_id, device.product_id, serial))
+    }
+
+    /// Whether a change concerns a device present at startup while the grace period is still running
+    fn is_startup_noise(&self, device: &UsbDeviceInfo) -> bool {
+        match &self.startup_devices {
+            Some((keys, grace_until)) if Utc::now() < *grace_until => {
+                keys.contains(&self.create_device_key(device))
+                    || self.create_identity_key(device).is_some_and(|identity| keys.contains(&identity))
+            }
+            _ => false,
+        }
     }
 
     /// Helper method to get device info from a device key
@@ -591,25 +1495,97 @@ impl UsbMonitor {
             }
         }
         
-        None
+        // Devices that have since disconnected
+        self.known_devices.get(device_key)
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Write a decision to the audit log, if one is configured; failures are logged, not fatal
+fn record_audit(audit_log: &mut Option<AuditLog>, event: &SecurityEvent, matched_rule: Option<MatchedRule<'_>>) {
+    if let Some(audit_log) = audit_log {
+        if let Err(e) = audit_log.record(event, matched_rule) {
+            error!("Failed to write audit log: {}", e);
+        }
+    }
+}
+
+/// Append to a bounded log, dropping the oldest entries beyond `max_len`
+fn push_bounded<T>(log: &mut VecDeque<T>, item: T, max_len: usize) {
+    log.push_back(item);
+    trim_to(log, max_len);
+}
+
+/// Drop the oldest entries until at most `max_len` remain
+fn trim_to<T>(log: &mut VecDeque<T>, max_len: usize) {
+    while log.len() > max_len {
+        log.pop_front();
+    }
+}
+
+/// Serialized as `{"change_type": "CONNECTED", "device": {...}, "reason": ...}`, the same shape
+/// as change records in JSON/NDJSON output, so recordings parse back into changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "ChangeRepr", try_from = "ChangeRepr")]
+#[non_exhaustive]
 pub enum UsbDeviceChange {
     Connected(UsbDeviceInfo),
     Disconnected(UsbDeviceInfo),
     Reconnected(UsbDeviceInfo),
-    Blocked(UsbDeviceInfo),
+    /// A new device rejected by the device rules, with the reason from its security event
+    Blocked(UsbDeviceInfo, String),
+    /// Entered a low-power state, or briefly dropped off the bus and came back within the suspend window
+    Suspended(UsbDeviceInfo),
+}
+
+/// Serialized form of `UsbDeviceChange`, with the variant as an explicit `change_type` tag
+#[derive(Serialize, Deserialize)]
+struct ChangeRepr {
+    change_type: String,
+    device: UsbDeviceInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl From<UsbDeviceChange> for ChangeRepr {
+    fn from(change: UsbDeviceChange) -> Self {
+        let change_type = change.get_change_type().to_string();
+        let reason = change.blocked_reason().map(str::to_string);
+        let device = match change {
+            UsbDeviceChange::Connected(device)
+            | UsbDeviceChange::Disconnected(device)
+            | UsbDeviceChange::Reconnected(device)
+            | UsbDeviceChange::Blocked(device, _)
+            | UsbDeviceChange::Suspended(device) => device,
+        };
+        Self { change_type, device, reason }
+    }
+}
+
+impl TryFrom<ChangeRepr> for UsbDeviceChange {
+    type Error = String;
+
+    fn try_from(mut repr: ChangeRepr) -> std::result::Result<Self, Self::Error> {
+        // Records made before fingerprints existed lack the field
+        if repr.device.fingerprint.is_empty() {
+            repr.device.update_fingerprint();
+        }
+        let change_type = repr.change_type;
+        Self::from_change_type(&change_type, repr.device, repr.reason)
+            .ok_or_else(|| format!("unknown change_type '{}'", change_type))
+    }
 }
 
 impl UsbDeviceChange {
+    /// Every `change_type` that `get_change_type` produces and `from_change_type` accepts
+    pub const CHANGE_TYPES: [&'static str; 5] = ["CONNECTED", "DISCONNECTED", "RECONNECTED", "BLOCKED", "SUSPENDED"];
+
     pub fn get_device_info(&self) -> &UsbDeviceInfo {
         match self {
             UsbDeviceChange::Connected(info) => info,
             UsbDeviceChange::Disconnected(info) => info,
             UsbDeviceChange::Reconnected(info) => info,
-            UsbDeviceChange::Blocked(info) => info,
+            UsbDeviceChange::Blocked(info, _) => info,
+            UsbDeviceChange::Suspended(info) => info,
         }
     }
     
@@ -618,7 +1594,670 @@ impl UsbDeviceChange {
             UsbDeviceChange::Connected(_) => "CONNECTED",
             UsbDeviceChange::Disconnected(_) => "DISCONNECTED",
             UsbDeviceChange::Reconnected(_) => "RECONNECTED",
-            UsbDeviceChange::Blocked(_) => "BLOCKED",
+            UsbDeviceChange::Blocked(..) => "BLOCKED",
+            UsbDeviceChange::Suspended(_) => "SUSPENDED",
+        }
+    }
+
+    /// Why a blocked change was blocked; `None` for every other change
+    pub fn blocked_reason(&self) -> Option<&str> {
+        match self {
+            UsbDeviceChange::Blocked(_, reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// Rebuild a change from the `change_type` written by `get_change_type`
+    pub fn from_change_type(change_type: &str, device: UsbDeviceInfo, reason: Option<String>) -> Option<Self> {
+        match change_type {
+            "CONNECTED" => Some(UsbDeviceChange::Connected(device)),
+            "DISCONNECTED" => Some(UsbDeviceChange::Disconnected(device)),
+            "RECONNECTED" => Some(UsbDeviceChange::Reconnected(device)),
+            "BLOCKED" => Some(UsbDeviceChange::Blocked(device, reason.unwrap_or_default())),
+            "SUSPENDED" => Some(UsbDeviceChange::Suspended(device)),
+            _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// A change together with the device's state before it
+#[derive(Debug, Clone)]
+pub struct ChangeRecord {
+    pub change: UsbDeviceChange,
+    /// Status after the device's previous change; `None` the first time it is seen
+    pub prev_status: Option<ConnectionStatus>,
+    /// Milliseconds since the device's previous change, by device timestamps
+    pub since_ms: Option<i64>,
+}
+
+/// Remembers each device's last change, so a reconnect after two seconds can be told from one
+/// after two minutes. Devices with a serial number are followed across ports.
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    last_change: HashMap<String, (ConnectionStatus, DateTime<Utc>)>,
+}
+
+impl ChangeTracker {
+    pub fn record(&mut self, change: UsbDeviceChange) -> ChangeRecord {
+        let device = change.get_device_info();
+        let key = match device.serial_number.as_deref().filter(|serial| !serial.is_empty()) {
+            Some(serial) => format!("{}:{}:{}", device.vendor_id, device.product_id, serial),
+            None => format!("{}:{}:{}:{}", device.vendor_id, device.product_id, device.bus_number, device.device_address),
+        };
+
+        let previous = self.last_change.insert(key, (device.connection_status.clone(), device.timestamp));
+        let (prev_status, since_ms) = match previous {
+            Some((status, at)) => (Some(status), Some((device.timestamp - at).num_milliseconds().max(0))),
+            None => (None, None),
+        };
+
+        ChangeRecord { change, prev_status, since_ms }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigManager, DeviceRule};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use tokio_stream::StreamExt;
+
+    /// Backend that replays scripted scans, repeating the last one once exhausted
+    struct MockBackend {
+        scans: Mutex<VecDeque<Vec<UsbDeviceInfo>>>,
+    }
+
+    impl MockBackend {
+        fn new(scans: Vec<Vec<UsbDeviceInfo>>) -> Self {
+            Self { scans: Mutex::new(scans.into()) }
+        }
+    }
+
+    impl UsbBackend for MockBackend {
+        fn enumerate(&self) -> Result<Vec<UsbDeviceInfo>> {
+            let mut scans = self.scans.lock().unwrap();
+            if scans.len() > 1 {
+                Ok(scans.pop_front().unwrap())
+            } else {
+                Ok(scans.front().cloned().unwrap_or_default())
+            }
+        }
+    }
+
+    fn device(vendor_id: u16, address: u8, serial: Option<&str>) -> UsbDeviceInfo {
+        let mut device = UsbDeviceInfo {
+            bus_number: 1,
+            device_address: address,
+            vendor_id,
+            product_id: 0x0001,
+            device_version: 0x0100,
+            manufacturer: Some("Acme".to_string()),
+            product: Some("Widget".to_string()),
+            serial_number: serial.map(str::to_string),
+            device_class: 0x08,
+            device_subclass: 0,
+            device_protocol: 0,
+            max_packet_size: 64,
+            num_configurations: 1,
+            timestamp: Utc::now(),
+            connection_status: ConnectionStatus::Connected,
+            fingerprint: String::new(),
+            raw_descriptor: None,
+        };
+        device.update_fingerprint();
+        device
+    }
+
+    fn monitor(scans: Vec<Vec<UsbDeviceInfo>>) -> UsbMonitor {
+        let mut monitor = UsbMonitor::with_backend(Box::new(MockBackend::new(scans)));
+        monitor.initialize_state().unwrap();
+        monitor
+    }
+
+    #[tokio::test]
+    async fn test_is_connected_matches_ids_and_serial() {
+        let backend = MockBackend::new(vec![vec![device(0x1234, 4, Some("A1"))], vec![]]);
+        let unscanned = UsbMonitor::with_backend(Box::new(backend));
+        assert!(unscanned.is_connected(0x1234, 0x0001, None));
+
+        let mut monitor = monitor(vec![vec![device(0x1234, 4, Some("A1"))], vec![]]);
+        assert!(monitor.is_connected(0x1234, 0x0001, Some("A1")));
+        assert!(!monitor.is_connected(0x1234, 0x0001, Some("B2")));
+        assert!(!monitor.is_connected(0x5678, 0x0001, None));
+
+        monitor.monitor_changes().await.unwrap();
+        assert!(!monitor.is_connected(0x1234, 0x0001, None));
+    }
+
+    #[tokio::test]
+    async fn test_fingerprints_tell_identical_models_apart_by_serial() {
+        assert_eq!(device(0x1234, 4, Some("A1")).fingerprint, device(0x1234, 9, Some("A1")).fingerprint);
+        assert_ne!(device(0x1234, 4, Some("A1")).fingerprint, device(0x1234, 4, None).fingerprint);
+        assert_eq!(device(0x1234, 4, None).fingerprint, device_fingerprint(0x1234, 0x0001, Some("")));
+
+        let mut monitor = monitor(vec![
+            vec![],
+            vec![device(0x1234, 4, Some("A1")), device(0x1234, 5, Some("B2"))],
+        ]);
+        monitor.monitor_changes().await.unwrap();
+
+        assert_eq!(monitor.get_device_analytics().unique_fingerprints, 2);
+    }
+
+    #[test]
+    fn test_display_name_falls_back_to_manufacturer_database_then_ids() {
+        let mut root_hub = device(0x1d6b, 1, None);
+        root_hub.product_id = 0x0002;
+        assert_eq!(root_hub.display_name(), "Widget");
+
+        root_hub.product = None;
+        assert_eq!(root_hub.display_name(), "Acme");
+
+        root_hub.manufacturer = None;
+        assert_eq!(root_hub.display_name(), "2.0 root hub");
+
+        // A product the USB ID database doesn't list
+        root_hub.product_id = 0xfffe;
+        assert_eq!(root_hub.display_name(), "1d6b:fffe");
+    }
+
+    #[tokio::test]
+    async fn test_serialless_devices_on_two_ports_can_count_as_one() {
+        let mut monitor = monitor(vec![
+            vec![],
+            vec![device(0x1234, 4, None)],
+            vec![],
+            vec![device(0x1234, 5, None)],
+        ]);
+        for _ in 0..3 {
+            monitor.monitor_changes().await.unwrap();
+        }
+        assert_eq!(monitor.get_device_analytics().unique_devices, 2);
+
+        monitor.set_merge_serialless_by_vidpid(true);
+        assert_eq!(monitor.get_device_analytics().unique_devices, 1);
+        // Statistics stay per port
+        assert_eq!(monitor.get_statistics_report().len(), 2);
+    }
+
+    #[test]
+    fn test_hourly_frequency_survives_clock_jumps() {
+        let now = Utc::now();
+        let hours = |n: i64| chrono::Duration::hours(n);
+
+        // The clock was set back 3h after the last event; one event predates the window
+        let history = [now - hours(2), now + hours(3), now - chrono::Duration::minutes(30), now - hours(30)];
+        let buckets = hourly_connection_frequency(history.iter(), now);
+
+        assert_eq!(buckets.len(), 24);
+        assert_eq!(buckets[0].0, now + hours(3) - hours(24));
+        assert_eq!(buckets.iter().map(|(_, count)| count).sum::<u32>(), 3);
+        assert_eq!(buckets[23].1, 1);
+        assert!(buckets.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        // After a jump forward, events older than a day drop out instead of going negative
+        let buckets = hourly_connection_frequency(history.iter(), now + hours(48));
+        assert_eq!(buckets.iter().map(|(_, count)| count).sum::<u32>(), 0);
+    }
+
+    #[test]
+    fn test_class_filter_excludes_other_classes() {
+        let mut hid = device(0x1234, 5, None);
+        hid.device_class = 0x03;
+        let mut hub = device(0x1234, 6, None);
+        hub.device_class = 0x09;
+        let mut monitor = UsbMonitor::with_backend(Box::new(MockBackend::new(vec![vec![
+            device(0x1234, 4, None),
+            hid,
+            hub,
+        ]])));
+
+        monitor.set_allowed_classes(Some(vec![0x08, 0x03]));
+        let classes: Vec<u8> = monitor.get_connected_devices().unwrap().iter().map(|d| d.device_class).collect();
+        assert_eq!(classes, vec![0x08, 0x03]);
+
+        // Both filters must pass
+        monitor.set_filter(Some("gadget".to_string()));
+        assert!(monitor.get_connected_devices().unwrap().is_empty());
+
+        monitor.set_allowed_classes(Some(Vec::new()));
+        monitor.set_filter(None);
+        assert!(monitor.get_connected_devices().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_name_patterns_apply_after_vendor_and_class_filters() {
+        let mut keyboard = device(0x046d, 5, None);
+        keyboard.product = Some("USB Keyboard".to_string());
+        keyboard.device_class = 0x03;
+        let mut hub = device(0x1d6b, 1, None);
+        hub.product = Some("xHCI Host Controller".to_string());
+        hub.device_class = 0x09;
+        let mut monitor = UsbMonitor::with_backend(Box::new(MockBackend::new(vec![vec![
+            device(0x1234, 4, None),
+            keyboard,
+            hub,
+        ]])));
+        let products = |monitor: &UsbMonitor| -> Vec<String> {
+            monitor.get_connected_devices().unwrap().into_iter().filter_map(|d| d.product).collect()
+        };
+
+        monitor.set_name_patterns(&["*host controller".to_string()], NamePatternsMode::Ignore);
+        assert_eq!(products(&monitor), vec!["Widget", "USB Keyboard"]);
+
+        // Manufacturer names match too: every device here is made by Acme
+        monitor.set_name_patterns(&["ac?e".to_string()], NamePatternsMode::Allow);
+        assert_eq!(products(&monitor).len(), 3);
+
+        // Allowing a name does not override the vendor ignore list or the class filter
+        monitor.set_ignored_devices(vec![0x1d6b], Vec::new());
+        monitor.set_allowed_classes(Some(vec![0x08, 0x09]));
+        assert_eq!(products(&monitor), vec!["Widget"]);
+    }
+
+    #[tokio::test]
+    async fn test_ignored_vendors_are_hidden_and_counted() {
+        let mut monitor = monitor(vec![
+            vec![device(0x1234, 4, None)],
+            vec![device(0x1234, 4, None), device(0x1d6b, 1, None)],
+        ]);
+        monitor.set_ignored_devices(vec![0x1d6b], Vec::new());
+
+        // The ignored hub arriving is not a change
+        assert!(monitor.monitor_changes().await.unwrap().is_empty());
+        assert_eq!(monitor.get_device_analytics().ignored_devices, 1);
+    }
+
+    /// Variant names appear in every output format, so renaming one breaks downstream parsers
+    #[test]
+    fn test_every_public_enum_variant_keeps_its_name() {
+        let statuses = [
+            (ConnectionStatus::Connected, "Connected"),
+            (ConnectionStatus::Disconnected, "Disconnected"),
+            (ConnectionStatus::Reconnected, "Reconnected"),
+            (ConnectionStatus::Blocked, "Blocked"),
+            (ConnectionStatus::Suspended, "Suspended"),
+        ];
+        for (status, name) in statuses {
+            assert_eq!(format!("{:?}", status), name);
+            assert_eq!(serde_json::to_value(&status).unwrap(), name);
+        }
+
+        let event_types = [
+            (SecurityEventType::DeviceBlocked, "DeviceBlocked"),
+            (SecurityEventType::DeviceAllowed, "DeviceAllowed"),
+            (SecurityEventType::RuleViolation, "RuleViolation"),
+            (SecurityEventType::SuspiciousActivity, "SuspiciousActivity"),
+        ];
+        for (event_type, name) in event_types {
+            assert_eq!(format!("{:?}", event_type), name);
+            assert_eq!(serde_json::to_value(&event_type).unwrap(), name);
+        }
+
+        let actions = [
+            (SecurityAction::Blocked, "Blocked"),
+            (SecurityAction::Allowed, "Allowed"),
+            (SecurityAction::Warned, "Warned"),
+            (SecurityAction::Logged, "Logged"),
+            (SecurityAction::Quarantine, "Quarantine"),
+        ];
+        for (action, name) in actions {
+            assert_eq!(format!("{:?}", action), name);
+            assert_eq!(serde_json::to_value(&action).unwrap(), name);
+        }
+
+        let widget = device(0x1234, 4, None);
+        let changes = [
+            (UsbDeviceChange::Connected(widget.clone()), "Connected("),
+            (UsbDeviceChange::Disconnected(widget.clone()), "Disconnected("),
+            (UsbDeviceChange::Reconnected(widget.clone()), "Reconnected("),
+            (UsbDeviceChange::Blocked(widget.clone(), "Untrusted vendor".to_string()), "Blocked("),
+            (UsbDeviceChange::Suspended(widget), "Suspended("),
+        ];
+        for ((change, debug_prefix), change_type) in changes.iter().zip(UsbDeviceChange::CHANGE_TYPES) {
+            assert!(format!("{:?}", change).starts_with(debug_prefix));
+            assert_eq!(change.get_change_type(), change_type);
+        }
+    }
+
+    #[test]
+    fn test_every_change_round_trips_through_json() {
+        let changes = [
+            UsbDeviceChange::Connected(device(0x1234, 4, Some("A1"))),
+            UsbDeviceChange::Disconnected(device(0x1234, 4, None)),
+            UsbDeviceChange::Reconnected(device(0x1234, 5, Some("A1"))),
+            UsbDeviceChange::Blocked(device(0xdead, 6, None), "Untrusted vendor (rule 0123456789abcdef)".to_string()),
+            UsbDeviceChange::Suspended(device(0x1234, 4, Some("A1"))),
+        ];
+
+        for change in changes {
+            let json = serde_json::to_value(&change).unwrap();
+            assert_eq!(json["change_type"], change.get_change_type());
+
+            let parsed: UsbDeviceChange = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(parsed.get_change_type(), change.get_change_type());
+            assert_eq!(parsed.blocked_reason(), change.blocked_reason());
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        }
+
+        let unknown = serde_json::json!({"change_type": "EXPLODED", "device": serde_json::to_value(device(1, 1, None)).unwrap()});
+        assert!(serde_json::from_value::<UsbDeviceChange>(unknown).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_device_storm_is_capped_and_reported_once() {
+        let storm = vec![device(0x1234, 4, None), device(0x5678, 5, None), device(0x9abc, 6, None), device(0xdef0, 7, None)];
+        let mut monitor = UsbMonitor::with_backend(Box::new(MockBackend::new(vec![
+            vec![device(0x1234, 4, None)],
+            storm.clone(),
+            storm,
+        ])));
+        monitor.set_max_devices(Some(2));
+        monitor.initialize_state().unwrap();
+
+        // The device tracked before the storm stays; one new device fills the remaining slot
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], UsbDeviceChange::Connected(device) if device.vendor_id == 0x5678));
+
+        assert!(monitor.monitor_changes().await.unwrap().is_empty());
+        let storm_events = monitor.get_security_events()
+            .iter()
+            .filter(|event| matches!(event.event_type, SecurityEventType::SuspiciousActivity))
+            .count();
+        assert_eq!(storm_events, 1);
+    }
+
+    #[test]
+    fn test_only_transient_usb_errors_are_retried() {
+        let attempts = std::cell::Cell::new(0);
+        let result = retry_transient(3, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(anyhow::Error::new(rusb::Error::Busy).context("Failed to get device list"))
+            } else {
+                Ok(attempts.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts.set(0);
+        let result: Result<()> = retry_transient(3, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            Err(anyhow::Error::new(rusb::Error::Access))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+
+        attempts.set(0);
+        let result: Result<()> = retry_transient(2, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            Err(anyhow::Error::new(rusb::Error::Timeout))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_async_retries_wait_and_stop_at_permanent_errors() {
+        let mut attempts = 0;
+        let result: Result<()> = retry_transient_async(3, Duration::from_millis(1), || {
+            attempts += 1;
+            match attempts {
+                1 => Err(anyhow::Error::new(rusb::Error::Pipe)),
+                _ => Err(anyhow::Error::new(rusb::Error::NoDevice)),
+            }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn test_snapshot_changes_report_the_set_difference() {
+        let mut monitor = monitor(vec![
+            vec![device(0x1234, 4, None), device(0x5678, 5, None)],
+            vec![device(0x5678, 5, None), device(0x9abc, 6, None), device(0x2222, 7, None)],
+            vec![device(0x5678, 5, None), device(0x9abc, 6, None), device(0x2222, 7, None)],
+        ]);
+
+        let changes = monitor.snapshot_changes().unwrap();
+        let summary: Vec<_> = changes.iter()
+            .map(|change| (change.get_change_type().to_string(), change.get_device_info().vendor_id))
+            .collect();
+        assert_eq!(summary, vec![
+            ("DISCONNECTED".to_string(), 0x1234),
+            // Keys are "vid:pid:bus:address" in decimal: "39612:..." sorts before "8738:..."
+            ("CONNECTED".to_string(), 0x9abc),
+            ("CONNECTED".to_string(), 0x2222),
+        ]);
+
+        assert!(monitor.snapshot_changes().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connect_then_disconnect() {
+        let mut monitor = monitor(vec![vec![], vec![device(0x1234, 4, None)], vec![]]);
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], UsbDeviceChange::Connected(_)));
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], UsbDeviceChange::Disconnected(_)));
+
+        let stats = monitor.get_device_statistics("4660:1:1:4").unwrap();
+        assert_eq!(stats.total_connections, 1);
+        assert_eq!(stats.total_disconnections, 1);
+        assert_eq!(stats.connection_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_at_new_address() {
+        let mut monitor = monitor(vec![
+            vec![device(0x1234, 4, Some("SN1"))],
+            vec![],
+            vec![device(0x1234, 5, Some("SN1"))],
+        ]);
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert!(matches!(changes[..], [UsbDeviceChange::Disconnected(_)]));
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert_eq!(changes.len(), 1);
+        match &changes[0] {
+            UsbDeviceChange::Reconnected(info) => {
+                assert_eq!(info.device_address, 5);
+                assert_eq!(info.connection_status, ConnectionStatus::Reconnected);
+            }
+            other => panic!("expected reconnect, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vid_pid_serial_identity_keeps_statistics_across_ports() {
+        let backend = MockBackend::new(vec![
+            vec![device(0x1234, 4, Some("SN1"))],
+            vec![],
+            vec![device(0x1234, 5, Some("SN1"))],
+        ]);
+        let mut monitor = UsbMonitor::with_backend(Box::new(backend));
+        monitor.set_device_identity(DeviceIdentity::VidPidSerial);
+        monitor.initialize_state().unwrap();
+
+        monitor.monitor_changes().await.unwrap();
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert!(matches!(changes[..], [UsbDeviceChange::Reconnected(_)]));
+
+        let stats = monitor.get_device_statistics("4660:1:SN1").unwrap();
+        assert_eq!(stats.total_connections, 2);
+        assert_eq!(stats.total_disconnections, 1);
+        assert!(monitor.get_device_statistics("4660:1:1:5").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_quick_return_within_suspend_window_is_suspended() {
+        let mut monitor = monitor(vec![
+            vec![device(0x1234, 4, Some("SN1"))],
+            vec![],
+            vec![device(0x1234, 5, Some("SN1"))],
+        ]);
+        monitor.set_suspend_window(Some(Duration::from_secs(60)));
+
+        // The disconnect is held back while waiting for the device to return
+        assert!(monitor.monitor_changes().await.unwrap().is_empty());
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        match &changes[..] {
+            [UsbDeviceChange::Suspended(info)] => assert_eq!(info.connection_status, ConnectionStatus::Suspended),
+            other => panic!("expected a single suspend, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enumeration_with_policy_marks_blocked_devices() {
+        let config_path = std::env::temp_dir()
+            .join(format!("ironwatch-test-{}-policy-list.json", std::process::id()));
+        let mut config = ConfigManager::new(Some(config_path)).unwrap();
+        config.add_blacklisted_device(DeviceRule {
+            vendor_id: Some(0xdead),
+            reason: "Untrusted vendor".to_string(),
+            ..DeviceRule::new()
+        }).unwrap();
+
+        let mut monitor = monitor(vec![vec![], vec![device(0xdead, 7, None), device(0x1234, 8, None)]]);
+        monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config)));
+
+        let devices = monitor.get_connected_devices_with_policy().await.unwrap();
+        let find = |vendor_id| devices.iter().find(|(d, _)| d.vendor_id == vendor_id).unwrap();
+        assert_eq!(find(0xdead).0.connection_status, ConnectionStatus::Blocked);
+        assert_eq!(find(0xdead).1.reason.as_deref(), Some("Untrusted vendor"));
+        assert_eq!(find(0x1234).0.connection_status, ConnectionStatus::Connected);
+        assert!(!find(0x1234).1.blocked);
+    }
+
+    #[tokio::test]
+    async fn test_blacklisted_device_is_blocked() {
+        let config_path = std::env::temp_dir()
+            .join(format!("ironwatch-test-{}-missing.json", std::process::id()));
+        let mut config = ConfigManager::new(Some(config_path)).unwrap();
+        config.add_blacklisted_device(DeviceRule {
+            vendor_id: Some(0xdead),
+            reason: "Untrusted vendor".to_string(),
+            ..DeviceRule::new()
+        }).unwrap();
+
+        let mut monitor = monitor(vec![vec![], vec![device(0xdead, 7, None), device(0x1234, 8, None)]]);
+        monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config)));
+
+        let changes = monitor.monitor_changes().await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes.iter().filter(|c| matches!(c, UsbDeviceChange::Blocked(..))).count(), 1);
+        assert_eq!(changes.iter().filter(|c| matches!(c, UsbDeviceChange::Connected(_))).count(), 1);
+
+        let blocked_event = monitor.get_security_events()
+            .iter()
+            .find(|event| matches!(event.event_type, SecurityEventType::DeviceBlocked))
+            .unwrap();
+        assert_eq!(blocked_event.device_info.vendor_id, 0xdead);
+        let rule_id = blocked_event.rule_id.as_deref().unwrap();
+        assert_eq!(blocked_event.reason, format!("Untrusted vendor (rule {})", rule_id));
+
+        let stats = monitor.get_device_statistics("57005:1:1:7").unwrap();
+        assert_eq!(stats.total_blocked, 1);
+        assert_eq!(stats.total_connections, 0);
+    }
+
+    #[tokio::test]
+    async fn test_analytics_stay_consistent_over_connect_block_reconnect() {
+        let config_path = std::env::temp_dir()
+            .join(format!("ironwatch-test-{}-analytics-missing.json", std::process::id()));
+        let mut config = ConfigManager::new(Some(config_path)).unwrap();
+        config.add_blacklisted_device(DeviceRule {
+            vendor_id: Some(0xdead),
+            reason: "Untrusted vendor".to_string(),
+            ..DeviceRule::new()
+        }).unwrap();
+
+        let allowed = |address| device(0x1234, address, Some("SN1"));
+        let blocked = |address| device(0xdead, address, Some("SN2"));
+        let mut monitor = monitor(vec![
+            vec![],
+            vec![allowed(4)],
+            vec![allowed(4), blocked(7)],
+            vec![blocked(7)],
+            vec![],
+            vec![allowed(5)],
+            vec![allowed(5), blocked(8)],
+        ]);
+        monitor.set_config_manager(Arc::new(tokio::sync::RwLock::new(config)));
+
+        for _ in 0..6 {
+            monitor.monitor_changes().await.unwrap();
+        }
+
+        // Both devices came back at new addresses; their history moved with them
+        assert!(monitor.get_device_statistics("4660:1:1:4").is_none());
+        assert!(monitor.get_device_statistics("57005:1:1:7").is_none());
+
+        let allowed_stats = monitor.get_device_statistics("4660:1:1:5").unwrap();
+        assert_eq!(allowed_stats.total_connections, 2);
+        assert_eq!(allowed_stats.total_disconnections, 1);
+        assert_eq!(allowed_stats.connection_count, 1);
+
+        let blocked_stats = monitor.get_device_statistics("57005:1:1:8").unwrap();
+        assert_eq!(blocked_stats.total_blocked, 2);
+        assert_eq!(blocked_stats.total_connections, 0);
+        assert_eq!(blocked_stats.total_disconnections, 0);
+        assert_eq!(blocked_stats.connection_count, 0);
+
+        let analytics = monitor.get_device_analytics();
+        assert_eq!(analytics.unique_devices, 2);
+        assert_eq!(analytics.total_devices_seen, 4);
+        assert_eq!(analytics.blocked_devices, 2);
+    }
+
+    #[tokio::test]
+    async fn test_change_stream_yields_batches() {
+        let backend = MockBackend::new(vec![
+            vec![],
+            vec![device(0x1234, 4, None)],
+            vec![device(0x1234, 4, None), device(0x5678, 5, None)],
+        ]);
+        let mut stream = Box::pin(UsbMonitor::with_backend(Box::new(backend)).change_stream());
+
+        let first = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.unwrap().unwrap();
+        assert!(matches!(&first[..], [UsbDeviceChange::Connected(info)] if info.vendor_id == 0x1234));
+
+        let second = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.unwrap().unwrap();
+        assert!(matches!(&second[..], [UsbDeviceChange::Connected(info)] if info.vendor_id == 0x5678));
+    }
+
+    #[test]
+    fn test_connection_timeline_has_one_interval_per_connection() {
+        let at = |minute: u32| chrono::TimeZone::with_ymd_and_hms(&Utc, 2024, 1, 1, 12, minute, 0).unwrap();
+        let history = [
+            (at(0), "a", ConnectionStatus::Disconnected),
+            (at(1), "a", ConnectionStatus::Connected),
+            (at(2), "b", ConnectionStatus::Connected),
+            (at(3), "b", ConnectionStatus::Suspended),
+            (at(4), "a", ConnectionStatus::Disconnected),
+            (at(5), "a", ConnectionStatus::Reconnected),
+            (at(6), "c", ConnectionStatus::Blocked),
+        ];
+
+        let timeline = connection_timeline(history.iter().map(|(timestamp, key, status)| (*timestamp, *key, status)));
+
+        let interval = |key: &str, connected: u32, disconnected: Option<u32>| ConnectionInterval {
+            device_key: key.to_string(),
+            connected_at: at(connected),
+            disconnected_at: disconnected.map(at),
+        };
+        assert_eq!(timeline, vec![
+            interval("a", 1, Some(4)),
+            interval("b", 2, None),
+            interval("a", 5, None),
+        ]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Windows-only friendly-name lookup through SetupAPI.
+//!
+//! Many devices report no USB string descriptors on Windows, but the OS still knows a
+//! friendly name for them. Any failure here is ignored and the device is left unchanged.
+
+use crate::usb_monitor::UsbDeviceInfo;
+use log::debug;
+use std::ptr;
+use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
+    SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInfo, SetupDiGetClassDevsW,
+    SetupDiGetDeviceInstanceIdW, SetupDiGetDeviceRegistryPropertyW, DIGCF_ALLCLASSES,
+    DIGCF_PRESENT, SPDRP_DEVICEDESC, SPDRP_FRIENDLYNAME, SP_DEVINFO_DATA,
+};
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+
+/// A present USB device as known to SetupAPI
+struct SetupApiDevice {
+    vendor_id: u16,
+    product_id: u16,
+    /// Last segment of the instance ID, which is the serial number when the device has one
+    instance_suffix: String,
+    friendly_name: String,
+}
+
+/// Fill in missing product names from the Windows device manager
+pub fn enrich(devices: &mut [UsbDeviceInfo]) {
+    if devices.iter().all(|device| device.product.is_some()) {
+        return;
+    }
+
+    let known = query_usb_devices();
+    debug!("SetupAPI reported {} USB devices with friendly names", known.len());
+
+    for device in devices.iter_mut().filter(|device| device.product.is_none()) {
+        let candidates = || known.iter()
+            .filter(|entry| entry.vendor_id == device.vendor_id && entry.product_id == device.product_id);
+
+        // Prefer an exact serial match; otherwise any entry with the same VID/PID
+        let matched = device.serial_number.as_deref()
+            .and_then(|serial| candidates().find(|entry| entry.instance_suffix.eq_ignore_ascii_case(serial)))
+            .or_else(|| candidates().next());
+
+        if let Some(entry) = matched {
+            device.product = Some(entry.friendly_name.clone());
+        }
+    }
+}
+
+/// Enumerate present USB devices (excluding composite-device interfaces) and their names
+fn query_usb_devices() -> Vec<SetupApiDevice> {
+    let mut result = Vec::new();
+    let enumerator = to_wide("USB");
+
+    unsafe {
+        let device_set = SetupDiGetClassDevsW(ptr::null(), enumerator.as_ptr(), 0, DIGCF_PRESENT | DIGCF_ALLCLASSES);
+        if device_set == INVALID_HANDLE_VALUE {
+            debug!("SetupDiGetClassDevsW failed");
+            return result;
+        }
+
+        let mut index = 0;
+        loop {
+            let mut info: SP_DEVINFO_DATA = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<SP_DEVINFO_DATA>() as u32;
+
+            if SetupDiEnumDeviceInfo(device_set, index, &mut info) == 0 {
+                break;
+            }
+            index += 1;
+
+            let mut id_buffer = [0u16; 512];
+            if SetupDiGetDeviceInstanceIdW(device_set, &info, id_buffer.as_mut_ptr(), id_buffer.len() as u32, ptr::null_mut()) == 0 {
+                continue;
+            }
+            let instance_id = from_wide(&id_buffer);
+
+            let Some((vendor_id, product_id, instance_suffix)) = parse_instance_id(&instance_id) else {
+                continue;
+            };
+
+            let name = [SPDRP_FRIENDLYNAME, SPDRP_DEVICEDESC]
+                .into_iter()
+                .find_map(|property| {
+                    let mut buffer = [0u16; 256];
+                    let ok = SetupDiGetDeviceRegistryPropertyW(
+                        device_set,
+                        &info,
+                        property,
+                        ptr::null_mut(),
+                        buffer.as_mut_ptr() as *mut u8,
+                        (buffer.len() * 2) as u32,
+                        ptr::null_mut(),
+                    );
+                    let value = from_wide(&buffer);
+                    (ok != 0 && !value.is_empty()).then_some(value)
+                });
+
+            if let Some(friendly_name) = name {
+                result.push(SetupApiDevice { vendor_id, product_id, instance_suffix, friendly_name });
+            }
+        }
+
+        SetupDiDestroyDeviceInfoList(device_set);
+    }
+
+    result
+}
+
+/// Parse `USB\VID_046D&PID_C52B\<serial>` into its IDs and trailing segment
+fn parse_instance_id(instance_id: &str) -> Option<(u16, u16, String)> {
+    let mut parts = instance_id.split('\\');
+    let _bus = parts.next()?;
+    let hardware = parts.next()?.to_ascii_uppercase();
+    let suffix = parts.next()?.to_string();
+
+    // Interfaces of composite devices carry `&MI_xx`; only the parent device is wanted
+    if hardware.contains("&MI_") {
+        return None;
+    }
+
+    let hex_after = |tag: &str| {
+        let start = hardware.find(tag)? + tag.len();
+        u16::from_str_radix(hardware.get(start..start + 4)?, 16).ok()
+    };
+
+    Some((hex_after("VID_")?, hex_after("PID_")?, suffix))
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
@@ -0,0 +1,77 @@
+//! The library entry point, driven by a scripted backend instead of real hardware
+
+use ironwatch::usb_backend::UsbBackend;
+use ironwatch::usb_monitor::ConnectionStatus;
+use ironwatch::{Config, Monitor, UsbDeviceChange, UsbDeviceInfo};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Returns one scan per enumeration, then repeats the last one
+struct ScriptedBackend {
+    scans: Mutex<VecDeque<Vec<UsbDeviceInfo>>>,
+}
+
+impl UsbBackend for ScriptedBackend {
+    fn enumerate(&self) -> anyhow::Result<Vec<UsbDeviceInfo>> {
+        let mut scans = self.scans.lock().unwrap();
+        if scans.len() > 1 {
+            Ok(scans.pop_front().unwrap())
+        } else {
+            Ok(scans.front().cloned().unwrap_or_default())
+        }
+    }
+}
+
+fn keyboard() -> UsbDeviceInfo {
+    let mut device = UsbDeviceInfo {
+        bus_number: 1,
+        device_address: 7,
+        vendor_id: 0x046d,
+        product_id: 0xc31c,
+        device_version: 0x0100,
+        manufacturer: Some("Logitech".to_string()),
+        product: Some("Keyboard".to_string()),
+        serial_number: None,
+        device_class: 0x03,
+        device_subclass: 0,
+        device_protocol: 0,
+        max_packet_size: 8,
+        num_configurations: 1,
+        timestamp: chrono::Utc::now(),
+        connection_status: ConnectionStatus::Connected,
+        fingerprint: String::new(),
+        raw_descriptor: None,
+    };
+    device.update_fingerprint();
+    device
+}
+
+#[tokio::test]
+async fn test_callback_receives_changes_until_stopped() {
+    let mut config = Config::default();
+    config.monitoring.poll_interval_ms = 10;
+    // Listing on start, then a plug-in and an unplug
+    let backend = ScriptedBackend {
+        scans: Mutex::new(vec![vec![], vec![keyboard()], vec![]].into()),
+    };
+
+    let (sender, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let handle = Monitor::new()
+        .config(config)
+        .backend(Box::new(backend))
+        .start(move |change| {
+            let _ = sender.send(change);
+        })
+        .unwrap();
+
+    let limit = Duration::from_secs(5);
+    let connected = tokio::time::timeout(limit, changes.recv()).await.unwrap().unwrap();
+    assert!(matches!(connected, UsbDeviceChange::Connected(ref device) if device.product_id == 0xc31c));
+    let disconnected = tokio::time::timeout(limit, changes.recv()).await.unwrap().unwrap();
+    assert!(matches!(disconnected, UsbDeviceChange::Disconnected(_)));
+
+    tokio::time::timeout(limit, handle.stop()).await.unwrap().unwrap();
+    // The callback, and with it the sender, is dropped once the monitor has stopped
+    assert!(changes.recv().await.is_none());
+}